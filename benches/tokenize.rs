@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use eyecite::find::get_citations;
+use eyecite::tokenizers::extractors::EXTRACTORS;
+use eyecite::tokenizers::{Ahocorasick, MatchKind, Scan, Tokenizer};
+
+/// A long opinion, mostly plain text with a citation every few sentences,
+/// which is where splitting text into words dominates.
+fn opinion() -> String {
+    let paragraph = "The court below held that the statute did not apply to the \
+        conduct at issue, and the parties do not dispute that finding on appeal. \
+        See Roe v. Wade, 410 U.S. 113, 116 (1973). We have   long recognized \
+        that such claims turn on the facts of each case.  Id. at 120. \
+        Nothing in the record before us suggests otherwise. ";
+
+    paragraph.repeat(2_000)
+}
+
+fn tokenize(c: &mut Criterion) {
+    let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+    let text = opinion();
+
+    let mut group = c.benchmark_group("opinion");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("tokenize", |b| {
+        b.iter(|| tokenizer.tokenize(black_box(&text)))
+    });
+    group.bench_function("get_citations", |b| {
+        b.iter(|| get_citations(black_box(&text), false, &tokenizer))
+    });
+    group.finish();
+}
+
+/// Scanning with overlaps runs more extractors in exchange for recall. See
+/// [`Scan`]. Scans a real opinion, which has more varied reporters than
+/// [`opinion`], and prints how many extractors each scan runs on it.
+fn scan(c: &mut Criterion) {
+    let text = include_str!("../tests/assets/opinion.txt");
+
+    let mut group = c.benchmark_group("scan");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    for (name, scan) in [
+        (
+            "leftmost_longest",
+            Scan::NonOverlapping(MatchKind::LeftmostLongest),
+        ),
+        ("standard", Scan::NonOverlapping(MatchKind::Standard)),
+        ("overlapping", Scan::Overlapping),
+    ] {
+        let tokenizer = Ahocorasick::with_scan(EXTRACTORS.as_slice(), scan).unwrap();
+        println!(
+            "scan/{name}: runs {} extractors on tests/assets/opinion.txt",
+            tokenizer.get_extractors(text).count()
+        );
+        group.bench_function(name, |b| b.iter(|| tokenizer.tokenize(black_box(text))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tokenize, scan);
+criterion_main!(benches);
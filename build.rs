@@ -0,0 +1,58 @@
+//! Records the version of the `reporters_db` crate eyecite is built with as
+//! `REPORTERS_DB_VERSION`, so results can say which data they came from.
+//! See `tokenizers::extractors::version`.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The value of a `key = "value"` pair in part of a manifest.
+fn string_value<'m>(entry: &'m str, key: &str) -> Option<&'m str> {
+    let start = entry.find(&format!("{key} = \""))? + key.len() + 4;
+    let end = entry[start..].find('"')?;
+    Some(&entry[start..start + end])
+}
+
+/// The part of a manifest describing a dependency: its line in
+/// `[dependencies]`, or its own `[dependencies.name]` table.
+fn dependency_entry<'m>(manifest: &'m str, name: &str) -> Option<&'m str> {
+    let table = format!("[dependencies.{name}]");
+    if let Some(start) = manifest.find(&table) {
+        let rest = &manifest[start + table.len()..];
+        return Some(&rest[..rest.find("\n[").unwrap_or(rest.len())]);
+    }
+
+    manifest
+        .lines()
+        .find(|line| line.trim_start().starts_with(&format!("{name} =")))
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+    let manifest_path = Path::new(&manifest_dir).join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).expect("eyecite's manifest is readable");
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+
+    let dependency =
+        dependency_entry(&manifest, "reporters-db").expect("eyecite depends on reporters-db");
+
+    // The version of a path dependency is in its own manifest; otherwise
+    // the exact version is pinned in eyecite's
+    let version = match string_value(dependency, "path") {
+        Some(path) => {
+            let path = Path::new(&manifest_dir).join(path).join("Cargo.toml");
+            println!("cargo:rerun-if-changed={}", path.display());
+            let package = fs::read_to_string(&path).expect("reporters-db's manifest is readable");
+            let package = &package[package.find("[package]").unwrap_or(0)..];
+            string_value(package, "version")
+                .expect("reporters-db has a version")
+                .to_string()
+        }
+        None => string_value(dependency, "version")
+            .expect("reporters-db has a version")
+            .trim_start_matches(['=', '^', '~'])
+            .to_string(),
+    };
+
+    println!("cargo:rustc-env=REPORTERS_DB_VERSION={version}");
+}
@@ -0,0 +1,402 @@
+/*!
+Insert markup, like links, around citations in the text they were found in,
+like python eyecite's `annotate`.
+
+Spans are byte offsets into the text, like the ones citations have, so text
+that was cleaned before finding citations should be annotated after
+cleaning, or have its spans mapped back with
+[`CleanedText::original_span`](crate::clean::CleanedText::original_span).
+
+When annotating HTML, a citation can straddle an element boundary, like
+"<i>Roe</i>, 410 U.S.</p><p>113", and markup inserted around it would
+nest elements wrongly. [`UnbalancedTags`] decides what to do then.
+
+Spans of annotations can also overlap, like a short citation found inside
+the span of a full one. [`OverlapPolicy`] decides whether the inner one is
+skipped, nested or merged with the outer one. Either way the output only
+depends on the set of annotations, not their order or repetition, so the
+same citations always annotate the same way.
+ */
+
+use crate::find::models::Citation;
+use std::cmp::Reverse;
+
+/// Text to insert before and after a span of the text, as
+/// `((start, end), before, after)`.
+pub type Annotation = ((usize, usize), String, String);
+
+/// What to do with annotations of HTML whose span has unbalanced tags.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum UnbalancedTags {
+    /// Insert the markup anyway. Right for plain text.
+    #[default]
+    Unchecked,
+    /// Leave the span alone.
+    Skip,
+    /// Insert the markup around each run of text between the tags of the
+    /// span, so elements stay nested correctly.
+    Wrap,
+}
+
+/// What to do with annotations whose spans overlap.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OverlapPolicy {
+    /// Keep the annotation that starts first, or the longest of those that
+    /// start at the same place, and skip the ones overlapping it.
+    #[default]
+    SkipInner,
+    /// Nest the markup of annotations inside the ones whose span contains
+    /// theirs. Annotations that cross the end of another are skipped.
+    Nest,
+    /// Annotate the whole of each run of overlapping spans, with the markup
+    /// of the annotation kept by [`OverlapPolicy::SkipInner`].
+    Merge,
+}
+
+/// Options for [`annotate_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct AnnotateOptions {
+    pub unbalanced_tags: UnbalancedTags,
+    pub overlaps: OverlapPolicy,
+}
+
+/// Elements that have no closing tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Spans of the tags in HTML, like "<i>" or "</p>". A "<" without a ">"
+/// after it isn't a tag.
+fn tags(html: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let start = offset + html.get(offset..)?.find('<')?;
+        let end = start + html[start..].find('>')? + 1;
+        offset = end;
+        Some((start, end))
+    })
+}
+
+/// Whether every element opened in HTML is closed in it, and the other way
+/// around.
+fn is_balanced(html: &str) -> bool {
+    let mut open = Vec::new();
+    for (start, end) in tags(html) {
+        let tag = &html[start + 1..end - 1];
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name = tag
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        // Comments, doctypes, void and self-closing elements
+        if name.is_empty() || VOID_ELEMENTS.contains(&name.as_str()) || tag.ends_with('/') {
+            continue;
+        }
+        if !closing {
+            open.push(name);
+        } else if open.pop().as_ref() != Some(&name) {
+            return false;
+        }
+    }
+
+    open.is_empty()
+}
+
+/// Insert the before and after text of each annotation around its span.
+///
+/// Annotations don't need to be sorted. Those whose span overlaps an
+/// earlier annotation, or isn't a slice of the text, are skipped.
+pub fn annotate(text: &str, annotations: impl IntoIterator<Item = Annotation>) -> String {
+    annotate_with_options(text, annotations, &AnnotateOptions::default())
+}
+
+/// Like [`annotate`], with more control over how annotations are inserted.
+pub fn annotate_with_options(
+    text: &str,
+    annotations: impl IntoIterator<Item = Annotation>,
+    options: &AnnotateOptions,
+) -> String {
+    let mut annotations: Vec<_> = annotations
+        .into_iter()
+        .flat_map(|annotation| balance(text, annotation, options.unbalanced_tags))
+        .collect();
+    // Outer annotations before the ones inside them, and the rest of the
+    // order fixed too, so that the output doesn't depend on the input order.
+    annotations.sort_by(
+        |((start, end), before, after), ((other_start, other_end), other_before, other_after)| {
+            (start, Reverse(end), before, after).cmp(&(
+                other_start,
+                Reverse(other_end),
+                other_before,
+                other_after,
+            ))
+        },
+    );
+    annotations.dedup();
+    let annotations = resolve_overlaps(annotations, options.overlaps);
+
+    let markup: usize = annotations
+        .iter()
+        .map(|(_, before, after)| before.len() + after.len())
+        .sum();
+    let mut annotated = String::with_capacity(text.len() + markup);
+    let mut copied = 0;
+    let mut open = Vec::new();
+
+    for ((start, end), before, after) in annotations {
+        close(text, &mut annotated, &mut copied, &mut open, start);
+        annotated.push_str(&text[copied..start]);
+        annotated.push_str(&before);
+        copied = start;
+        open.push((end, after));
+    }
+    close(text, &mut annotated, &mut copied, &mut open, text.len());
+    annotated.push_str(&text[copied..]);
+
+    annotated
+}
+
+/// Check an annotation's span is a slice of the text, and handle
+/// unbalanced tags in it.
+fn balance(text: &str, annotation: Annotation, unbalanced_tags: UnbalancedTags) -> Vec<Annotation> {
+    let ((start, end), before, after) = annotation;
+    let Some(span) = text.get(start..end) else {
+        return Vec::new();
+    };
+    if unbalanced_tags == UnbalancedTags::Unchecked || is_balanced(span) {
+        return vec![((start, end), before, after)];
+    }
+    if unbalanced_tags == UnbalancedTags::Skip {
+        return Vec::new();
+    }
+
+    let mut pieces = Vec::new();
+    let mut wrapped = 0;
+    for (tag_start, tag_end) in tags(span).chain([(span.len(), span.len())]) {
+        if wrapped < tag_start {
+            pieces.push((
+                (start + wrapped, start + tag_start),
+                before.clone(),
+                after.clone(),
+            ));
+        }
+        wrapped = tag_end;
+    }
+
+    pieces
+}
+
+/// Drop, or merge, sorted annotations so that the spans left don't overlap,
+/// or only nest.
+fn resolve_overlaps(annotations: Vec<Annotation>, policy: OverlapPolicy) -> Vec<Annotation> {
+    let mut resolved: Vec<Annotation> = Vec::with_capacity(annotations.len());
+    // Ends of the annotations the next one could nest in, innermost last
+    let mut ends: Vec<usize> = Vec::new();
+
+    for annotation in annotations {
+        let ((start, end), _, _) = annotation;
+        let last = resolved.last_mut().map(|((_, last_end), _, _)| last_end);
+        match (policy, last) {
+            (OverlapPolicy::SkipInner, Some(last_end)) if start < *last_end => {}
+            (OverlapPolicy::Merge, Some(last_end)) if start < *last_end => {
+                *last_end = (*last_end).max(end);
+            }
+            (OverlapPolicy::Nest, _) => {
+                while ends.last().is_some_and(|&open| open <= start) {
+                    ends.pop();
+                }
+                if ends.last().is_none_or(|&open| end <= open) {
+                    ends.push(end);
+                    resolved.push(annotation);
+                }
+            }
+            _ => resolved.push(annotation),
+        }
+    }
+
+    resolved
+}
+
+/// Insert the after text of the open annotations that end by `until`,
+/// innermost first.
+fn close(
+    text: &str,
+    annotated: &mut String,
+    copied: &mut usize,
+    open: &mut Vec<(usize, String)>,
+    until: usize,
+) {
+    while let Some((end, after)) = open.pop() {
+        if end > until {
+            open.push((end, after));
+            break;
+        }
+        annotated.push_str(&text[*copied..end]);
+        annotated.push_str(&after);
+        *copied = end;
+    }
+}
+
+/// Annotate the text matched by each citation, like "410 U.S. 113", with
+/// the before and after text returned by `markup`. Citations `markup`
+/// returns `None` for are left alone, so links can be added to only the
+/// citations that were resolved, or markup can vary by kind of citation.
+pub fn annotate_citations<'a, F>(text: &str, citations: &[Citation<'a>], markup: F) -> String
+where
+    F: Fn(&Citation<'a>) -> Option<(String, String)>,
+{
+    annotate_citations_with_options(text, citations, markup, &AnnotateOptions::default())
+}
+
+/// Like [`annotate_citations`], with more control over how annotations are
+/// inserted.
+pub fn annotate_citations_with_options<'a, F>(
+    text: &str,
+    citations: &[Citation<'a>],
+    markup: F,
+    options: &AnnotateOptions,
+) -> String
+where
+    F: Fn(&Citation<'a>) -> Option<(String, String)>,
+{
+    annotate_with_options(
+        text,
+        citations.iter().filter_map(|citation| {
+            let (before, after) = markup(citation)?;
+            Some((citation.source().matched_span(), before, after))
+        }),
+        options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        annotate, annotate_citations, annotate_with_options, is_balanced, AnnotateOptions,
+        OverlapPolicy, UnbalancedTags,
+    };
+    use crate::find::get_citations;
+    use crate::find::models::Citation;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn annotations() {
+        let text = "foo bar baz";
+        let annotated = annotate(
+            text,
+            vec![
+                ((8, 11), "<b>".into(), "</b>".into()),
+                ((0, 3), "<i>".into(), "</i>".into()),
+                // overlapping, out of bounds
+                ((2, 5), "[".into(), "]".into()),
+                ((10, 20), "[".into(), "]".into()),
+            ],
+        );
+        assert_eq!(annotated, "<i>foo</i> bar <b>baz</b>");
+    }
+
+    #[test]
+    fn citation_markup() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113 (1973). Id. at 115.";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let annotated = annotate_citations(text, &citations, |citation| match citation {
+            Citation::FullCase { source, .. } => Some((
+                format!("<a href=\"/{}\">", source.groups["volume"]),
+                "</a>".into(),
+            )),
+            _ => None,
+        });
+        assert_eq!(
+            annotated,
+            "See Roe v. Wade, <a href=\"/410\">410 U.S. 113</a> (1973). Id. at 115."
+        );
+    }
+
+    #[test]
+    fn unbalanced_tags() {
+        let text = "a <i>b c</i> d";
+        let annotated = |span, unbalanced_tags| {
+            let options = AnnotateOptions {
+                unbalanced_tags,
+                ..Default::default()
+            };
+            annotate_with_options(text, vec![(span, "[".into(), "]".into())], &options)
+        };
+
+        assert_eq!(
+            annotated((5, 14), UnbalancedTags::Unchecked),
+            "a <i>[b c</i> d]"
+        );
+        assert_eq!(annotated((5, 14), UnbalancedTags::Skip), text);
+        assert_eq!(
+            annotated((5, 14), UnbalancedTags::Wrap),
+            "a <i>[b c]</i>[ d]"
+        );
+        assert_eq!(annotated((2, 12), UnbalancedTags::Wrap), "a [<i>b c</i>] d");
+
+        assert!(is_balanced("<p>1 U.S.<br/> 1<img src=x></p><!-- note -->"));
+        assert!(!is_balanced("<i>1 U.S.</b>"));
+        assert!(is_balanced("1 < 2 U.S. 3"));
+    }
+
+    #[test]
+    fn overlap_policies() {
+        let text = "abcdef";
+        let annotations = vec![
+            ((0, 3), "[".to_string(), "]".to_string()),
+            ((1, 2), "<".to_string(), ">".to_string()),
+            ((2, 5), "{".to_string(), "}".to_string()),
+        ];
+        let annotated = |annotations: Vec<_>, overlaps| {
+            let options = AnnotateOptions {
+                overlaps,
+                ..Default::default()
+            };
+            annotate_with_options(text, annotations, &options)
+        };
+
+        assert_eq!(
+            annotated(annotations.clone(), OverlapPolicy::SkipInner),
+            "[abc]def"
+        );
+        assert_eq!(
+            annotated(annotations.clone(), OverlapPolicy::Nest),
+            "[a<b>c]def"
+        );
+        assert_eq!(
+            annotated(annotations.clone(), OverlapPolicy::Merge),
+            "[abcde]f"
+        );
+
+        // Order and repetition don't matter
+        let mut shuffled: Vec<_> = annotations.iter().rev().cloned().collect();
+        shuffled.extend(annotations.clone());
+        for overlaps in [
+            OverlapPolicy::SkipInner,
+            OverlapPolicy::Nest,
+            OverlapPolicy::Merge,
+        ] {
+            assert_eq!(
+                annotated(shuffled.clone(), overlaps),
+                annotated(annotations.clone(), overlaps)
+            );
+        }
+
+        // Nested spans ending together close innermost first
+        let nested = vec![
+            ((0, 6), "(".to_string(), ")".to_string()),
+            ((3, 6), "[".to_string(), "]".to_string()),
+            ((6, 6), "|".to_string(), "|".to_string()),
+        ];
+        assert_eq!(annotated(nested, OverlapPolicy::Nest), "(abc[def])||");
+    }
+}
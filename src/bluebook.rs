@@ -0,0 +1,223 @@
+/*!
+Check extracted citations against basic Bluebook formatting rules, for
+tools that help edit briefs.
+
+Only rules that can be checked from the text around a citation are
+covered: spacing in reporter abbreviations, commas after case names and
+before pin cites, "at" before the pin cites of short forms, and, when the
+text is HTML, italics for case names and short form signals. Each
+violation records the span to fix, or the empty span where something is
+missing.
+ */
+
+use crate::find::models::Citation;
+use crate::helpers::subslice_span;
+use crate::tokenizers::models::GroupName;
+
+/// A Bluebook rule a citation breaks.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum BluebookRule {
+    /// The reporter abbreviation is spaced differently than its edition's,
+    /// like "U. S." rather than "U.S." (rule 6.1(a)).
+    ReporterSpacing { expected: String },
+    /// A comma is missing after the case name, or before the pin cite of a
+    /// full citation or a supra citation.
+    MissingComma,
+    /// A comma comes before "at", like "410 U.S., at 115" or "Id., at 5",
+    /// rather than "410 U.S. at 115" (rule 3.2(a)).
+    CommaBeforeAt,
+    /// The pin cite of a short form citation isn't introduced by "at", like
+    /// "Id. 5" rather than "Id. at 5".
+    MissingAt,
+    /// A case name, "Id." or "supra" isn't italicized (rule 2.1).
+    NotItalicized,
+}
+
+/// A citation breaking a Bluebook rule.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RuleViolation {
+    /// Index of the citation in the checked list.
+    pub index: usize,
+    pub span: (usize, usize),
+    pub rule: BluebookRule,
+}
+
+/// Which rules to check.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct BluebookOptions {
+    /// The text is HTML, so check that case names, "Id." and "supra" are in
+    /// `<i>` or `<em>` tags.
+    pub markup: bool,
+}
+
+/// Whether an offset into HTML is inside an `<i>` or `<em>` tag.
+fn italicized(text: &str, offset: usize) -> bool {
+    let Some(before) = text.get(..offset) else {
+        return false;
+    };
+    let open = ["<i>", "<i ", "<em>", "<em "]
+        .iter()
+        .filter_map(|tag| before.rfind(tag))
+        .max();
+    let close = ["</i>", "</em>"]
+        .iter()
+        .filter_map(|tag| before.rfind(tag))
+        .max();
+
+    open.is_some_and(|open| close.is_none_or(|close| close < open))
+}
+
+/// Check citations found in a text against Bluebook rules, returning the
+/// violations in the order of the citations.
+pub fn check_bluebook(
+    text: &str,
+    citations: &[Citation],
+    options: &BluebookOptions,
+) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+
+    for (index, citation) in citations.iter().enumerate() {
+        let source = citation.source();
+        let (start, end) = source.matched_span();
+        let mut violate = |span: (usize, usize), rule: BluebookRule| {
+            violations.push(RuleViolation { index, span, rule })
+        };
+        let pin_cite_start = source.pin_cite_span().map(|(pin_start, _)| pin_start);
+        // Text between the citation's token and its pin cite, like ", " or
+        // ", at "
+        let before_pin_cite = pin_cite_start.and_then(|pin_start| text.get(end..pin_start));
+
+        if let Some(reporter) = source
+            .token
+            .try_data()
+            .and_then(|data| data.groups.get(GroupName::Reporter))
+        {
+            if let Some(expected) = source.corrected_reporter() {
+                let unspaced = |s: &str| s.replace(' ', "");
+                if reporter != expected && unspaced(reporter) == unspaced(expected) {
+                    let span = subslice_span(text, reporter).unwrap_or((start, end));
+                    violate(
+                        span,
+                        BluebookRule::ReporterSpacing {
+                            expected: expected.to_string(),
+                        },
+                    );
+                }
+            }
+        }
+
+        match citation {
+            Citation::FullCase { .. } => {
+                if let Some((_, name_end)) = source
+                    .defendant()
+                    .and_then(|defendant| subslice_span(text, defendant))
+                {
+                    let between = text.get(name_end..start).unwrap_or_default();
+                    if !between.contains(',') {
+                        violate((name_end, name_end), BluebookRule::MissingComma);
+                    }
+                }
+                if before_pin_cite.is_some_and(|between| !between.contains(',')) {
+                    violate((end, end), BluebookRule::MissingComma);
+                }
+                if options.markup {
+                    if let Some(name) = source
+                        .plaintiff()
+                        .and_then(|plaintiff| subslice_span(text, plaintiff))
+                    {
+                        if !italicized(text, name.0) {
+                            violate(name, BluebookRule::NotItalicized);
+                        }
+                    }
+                }
+            }
+            Citation::ShortCase { .. } => {
+                if let Some(comma) = source.matched_text().find(", at ") {
+                    violate(
+                        (start + comma, start + comma + 1),
+                        BluebookRule::CommaBeforeAt,
+                    );
+                }
+            }
+            Citation::Id { .. } | Citation::Supra { .. } => {
+                let is_supra = matches!(citation, Citation::Supra { .. });
+                let token = source.matched_text();
+                if let (Some(pin_cite), Some(pin_start), Some(between)) =
+                    (source.pin_cite(), pin_cite_start, before_pin_cite)
+                {
+                    let comma = token.ends_with(',') || between.contains(',');
+                    if !is_supra && comma {
+                        let at = text
+                            .get(start..pin_start)
+                            .and_then(|before| before.rfind(','))
+                            .map_or(end, |i| start + i);
+                        violate((at, at + 1), BluebookRule::CommaBeforeAt);
+                    }
+                    if is_supra && !comma {
+                        violate((end, end), BluebookRule::MissingComma);
+                    }
+                    if pin_cite.starts_with(|c: char| c.is_ascii_digit()) {
+                        violate((pin_start, pin_start), BluebookRule::MissingAt);
+                    }
+                }
+                if options.markup && !italicized(text, start) {
+                    violate((start, end), BluebookRule::NotItalicized);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_bluebook, italicized, BluebookOptions, BluebookRule};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn bluebook_rules() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U. S. 113, 115 (1973). Id., at 116. \
+            Roe, 410 U.S., at 117. Wade, supra 118.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 4);
+
+        let violations = check_bluebook(text, &citations, &BluebookOptions::default());
+        let rules: Vec<_> = violations
+            .iter()
+            .map(|v| (v.index, v.rule.clone()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (
+                    0,
+                    BluebookRule::ReporterSpacing {
+                        expected: "U.S.".to_string()
+                    }
+                ),
+                (1, BluebookRule::CommaBeforeAt),
+                (2, BluebookRule::CommaBeforeAt),
+                (3, BluebookRule::MissingComma),
+                (3, BluebookRule::MissingAt),
+            ]
+        );
+        let (start, end) = violations[0].span;
+        assert_eq!(&text[start..end], "U. S.");
+        let (start, end) = violations[2].span;
+        assert_eq!(&text[start..end], ",");
+    }
+
+    #[test]
+    fn italics() {
+        let html = "<p><i>Roe</i> v. Wade. <em>Id.</em> at 5</p>";
+        assert!(italicized(html, html.find("Roe").unwrap()));
+        assert!(!italicized(html, html.find("Wade").unwrap()));
+        assert!(italicized(html, html.find("Id.").unwrap()));
+        assert!(!italicized(html, html.find("at").unwrap()));
+    }
+}
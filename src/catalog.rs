@@ -0,0 +1,150 @@
+/*!
+List the editions eyecite finds citations to, with an example citation of
+each, so products can show which citation formats are supported and tests
+can be generated from them.
+
+Names, variations and jurisdictions come from `reporters_db`, and examples
+are synthesized from the regexes of the extractors that match each edition,
+like "1 U.S. 1", with [`regexes::example_match`].
+ */
+
+use crate::regexes;
+use crate::tokenizers::extractors::{default_extractors, TokenExtractor};
+use crate::tokenizers::models::TokenFactories;
+use chrono::Datelike;
+use reporters_db::reporters::reporters;
+
+/// An edition of a reporter eyecite can find citations to.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CatalogEntry {
+    /// Key of the reporter in `reporters.json`, like "S.W."
+    pub reporter: String,
+    /// Canonical abbreviation of the edition, like "S.W.2d"
+    pub edition: String,
+    /// Full name of the reporter, like "South Western Reporter"
+    pub name: String,
+    /// Kind of reporter, like "state" or "federal"
+    pub cite_type: String,
+    /// Jurisdictions the reporter publishes cases of, in the MLZ format,
+    /// like "us:tx".
+    pub jurisdictions: Vec<String>,
+    /// Other abbreviations found for the edition, like "S.W. 2d", sorted.
+    pub variations: Vec<String>,
+    /// Years of the first and last cases of the edition, if known.
+    pub start_year: Option<i32>,
+    pub end_year: Option<i32>,
+    /// A full citation to the edition eyecite finds, like "1 S.W.2d 1", or
+    /// None if no extractor matches the edition.
+    pub example: Option<String>,
+}
+
+/// Example full citation to an edition, from the first extractor that
+/// matches it, preferring examples with its canonical abbreviation over
+/// those with another of the extractor's reporter strings.
+fn example_citation(
+    extractors: &[TokenExtractor],
+    reporter: &str,
+    edition: &str,
+) -> Option<String> {
+    let mut examples = extractors
+        .iter()
+        .filter(|extractor| {
+            extractor.token_factory == TokenFactories::Citation
+                && !extractor.extra.short
+                && extractor
+                    .extra
+                    .exact_editions
+                    .iter()
+                    .any(|e| e.reporter == reporter && e.short_name == edition)
+        })
+        .filter_map(|extractor| regexes::example_match(extractor.regex.value()))
+        .peekable();
+    let first = examples.peek().cloned();
+
+    examples
+        .find(|example| {
+            example
+                .groups
+                .iter()
+                .any(|(name, value)| name == "reporter" && value == edition)
+        })
+        .or(first)
+        .map(|example| example.text.trim().to_string())
+}
+
+/// The editions of `reporters_db`, with examples from the
+/// [`default_extractors`], sorted by reporter and edition.
+pub fn catalog() -> Vec<CatalogEntry> {
+    catalog_of(default_extractors())
+}
+
+/// Like [`catalog`], with examples from the given extractors.
+pub fn catalog_of(extractors: &[TokenExtractor]) -> Vec<CatalogEntry> {
+    let mut entries = Vec::new();
+    for (reporter, cluster) in reporters() {
+        for source in cluster {
+            for (edition_name, edition) in &source.editions {
+                let mut variations: Vec<_> = source
+                    .variations
+                    .iter()
+                    .filter(|(_, v)| *v == edition_name)
+                    .map(|(k, _)| k.value().to_string())
+                    .collect();
+                variations.sort();
+
+                entries.push(CatalogEntry {
+                    reporter: reporter.clone(),
+                    edition: edition_name.value().to_string(),
+                    name: source.name.clone(),
+                    cite_type: source.cite_type.clone(),
+                    jurisdictions: source.mlz_jurisdiction.clone(),
+                    variations,
+                    start_year: edition.start.map(|start| start.year()),
+                    end_year: edition.end.map(|end| end.year()),
+                    example: example_citation(extractors, &reporter, edition_name.value()),
+                });
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| (&a.reporter, &a.edition).cmp(&(&b.reporter, &b.edition)));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::catalog;
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn supported_editions() {
+        let entries = catalog();
+        let missing: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.example.is_none())
+            .map(|entry| &entry.edition)
+            .collect();
+        assert!(missing.is_empty(), "no example for {missing:?}");
+
+        let us = entries
+            .iter()
+            .find(|entry| entry.edition == "U.S.")
+            .unwrap();
+        assert_eq!(us.name, "United States Supreme Court Reports");
+        assert!(us.variations.iter().any(|v| v == "U. S."));
+        assert_eq!(us.jurisdictions, vec!["us:c"]);
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        for entry in entries.iter().filter(|entry| entry.reporter == "S.W.") {
+            let example = entry.example.as_deref().unwrap();
+            let citations = get_citations(example, false, &tokenizer);
+            assert_eq!(citations.len(), 1, "{example}");
+            assert_eq!(
+                citations[0].source().corrected_reporter(),
+                Some(entry.edition.as_str())
+            );
+        }
+    }
+}
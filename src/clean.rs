@@ -0,0 +1,150 @@
+/*!
+Clean text before finding citations in it, keeping track of where each part
+of the cleaned text came from.
+
+Text extracted from PDFs is full of hard line breaks, often right inside
+citations, like "410 U.S.\n113" or "F.\nSupp. 2d", which the citation
+regexes don't match. [`join_line_breaks`] joins lines back together, and
+the returned [`CleanedText`] maps spans of citations found in the cleaned
+text back to the original.
+ */
+
+/// Text after cleaning, along with a map of offsets back to the original.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CleanedText {
+    pub text: String,
+    /// Offsets in the cleaned text where an edit ends, paired with the
+    /// matching offset in the original. Between checkpoints both texts are
+    /// the same.
+    checkpoints: Vec<(usize, usize)>,
+}
+
+impl CleanedText {
+    /// Text that wasn't changed by cleaning.
+    pub fn unchanged(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Convert an offset into the cleaned text to an offset into the
+    /// original. The space that replaced a line break maps to where the
+    /// line break's whitespace started.
+    pub fn to_original(&self, offset: usize) -> usize {
+        let i = self
+            .checkpoints
+            .partition_point(|&(cleaned, _)| cleaned <= offset);
+
+        match i.checked_sub(1) {
+            Some(i) => {
+                let (cleaned, original) = self.checkpoints[i];
+                original + (offset - cleaned)
+            }
+            None => offset,
+        }
+    }
+
+    /// Convert a span in the cleaned text, like
+    /// [`CitationSource::span`](crate::find::models::CitationSource::span),
+    /// to a span in the original text.
+    pub fn original_span(&self, (start, end): (usize, usize)) -> (usize, usize) {
+        (self.to_original(start), self.to_original(end))
+    }
+
+    /// Slice the original text with a span in the cleaned text, like
+    /// [`CitationSource::span`](crate::find::models::CitationSource::span)
+    /// or [`CitationSource::matched_span`](crate::find::models::CitationSource::matched_span).
+    /// Returns `None` if `original` isn't the text that was cleaned.
+    pub fn original_text<'t>(&self, original: &'t str, span: (usize, usize)) -> Option<&'t str> {
+        let (start, end) = self.original_span(span);
+        original.get(start..end)
+    }
+}
+
+/// Join lines broken in the middle of sentences back together.
+///
+/// Each line break, along with whitespace around it, becomes a single space,
+/// and words hyphenated across lines, like "Wash-\nington", are joined.
+/// Blank lines, which separate paragraphs, are kept.
+pub fn join_line_breaks(text: &str) -> CleanedText {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut checkpoints = Vec::new();
+    let mut copied = 0;
+
+    for newline in memchr::memchr_iter(b'\n', text.as_bytes()) {
+        if newline < copied {
+            continue;
+        }
+
+        let start = text[..newline].trim_end_matches([' ', '\t', '\r']).len();
+        let end = text.len() - text[newline + 1..].trim_start_matches([' ', '\t']).len();
+        let (before, after) = (&text[..start], &text[end..]);
+
+        // Keep the edges of the text and paragraph breaks
+        if before.is_empty()
+            || after.is_empty()
+            || before.ends_with('\n')
+            || after.starts_with(['\n', '\r'])
+        {
+            continue;
+        }
+
+        let mut hyphen = before.chars().rev();
+        let hyphenated = hyphen.next() == Some('-')
+            && hyphen.next().is_some_and(char::is_alphabetic)
+            && after.starts_with(char::is_lowercase);
+
+        if hyphenated {
+            cleaned.push_str(&text[copied..start - 1]);
+        } else {
+            cleaned.push_str(&text[copied..start]);
+            cleaned.push(' ');
+        }
+        checkpoints.push((cleaned.len(), end));
+        copied = end;
+    }
+    cleaned.push_str(&text[copied..]);
+
+    CleanedText {
+        text: cleaned,
+        checkpoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::join_line_breaks;
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn citations_across_line_breaks() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S.\n113 (1973); Smith v. Wash-\n\
+            ington, 1 F. \r\n  Supp. 2d 5 (1999).\n\nA new paragraph.";
+
+        let cleaned = join_line_breaks(text);
+        assert_eq!(
+            cleaned.text,
+            "See Roe v. Wade, 410 U.S. 113 (1973); Smith v. Washington, \
+            1 F. Supp. 2d 5 (1999).\n\nA new paragraph."
+        );
+
+        let citations = get_citations(&cleaned.text, false, &tokenizer);
+        let originals: Vec<_> = citations
+            .iter()
+            .map(|citation| {
+                let (start, end) = cleaned.original_span(citation.source().span());
+                &text[start..end]
+            })
+            .collect();
+        assert_eq!(originals, vec!["410 U.S.\n113", "1 F. \r\n  Supp. 2d 5"]);
+        assert_eq!(
+            cleaned.original_text(text, citations[1].source().matched_span()),
+            Some("1 F. \r\n  Supp. 2d 5")
+        );
+        assert_eq!(cleaned.to_original(cleaned.text.len()), text.len());
+    }
+}
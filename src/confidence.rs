@@ -0,0 +1,162 @@
+/*!
+Score how likely extracted citations are to be real citations, so callers
+can trade precision for recall by choosing a threshold.
+
+The score starts from a base and adds or takes away a weight for each
+signal: whether the reporter string is an edition's own abbreviation or a
+variation of it, whether the year falls within the edition's dates, whether
+there is a pin cite and a case name, and whether the citation was only
+found by fuzzy matching its reporter, is ambiguous or has a placeholder
+volume or page. Weights can be tuned with [`ConfidenceWeights`].
+ */
+
+use crate::find::models::{Citation, CitationMetadata};
+
+/// How much each signal adds to, or takes away from, the confidence of a
+/// citation. Scores are clamped to between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceWeights {
+    /// The confidence of a citation without any signal either way.
+    pub base: f32,
+    /// The reporter string is the abbreviation of an edition, like "U.S.".
+    /// Not given to fuzzy matched reporter strings.
+    pub exact_edition: f32,
+    /// The reporter string is only a known variation, like "U. S.". Not
+    /// given to fuzzy matched reporter strings.
+    pub variation_edition: f32,
+    /// The year is within the dates of an edition the citation might refer
+    /// to.
+    pub year_in_edition_dates: f32,
+    /// The year is outside of the dates of every edition the citation
+    /// might refer to.
+    pub year_outside_edition_dates: f32,
+    pub pin_cite: f32,
+    /// A case name, or the antecedent of a short form citation, was found.
+    pub case_name: f32,
+    /// The reporter string was fuzzy matched. See
+    /// [`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer).
+    pub fuzzy_reporter: f32,
+    /// The citation might refer to more than one edition.
+    pub ambiguous: f32,
+    /// The volume or page is a placeholder, like "___ U.S. ___".
+    pub placeholder: f32,
+}
+
+impl Default for ConfidenceWeights {
+    fn default() -> Self {
+        Self {
+            base: 0.5,
+            exact_edition: 0.2,
+            variation_edition: 0.05,
+            year_in_edition_dates: 0.1,
+            year_outside_edition_dates: -0.3,
+            pin_cite: 0.05,
+            case_name: 0.1,
+            fuzzy_reporter: -0.3,
+            ambiguous: -0.1,
+            placeholder: -0.1,
+        }
+    }
+}
+
+/// The confidence that a citation is real, between 0 and 1.
+pub fn confidence(citation: &Citation, weights: &ConfidenceWeights) -> f32 {
+    let source = citation.source();
+    let mut score = weights.base;
+
+    if let Some(editions) = source.editions() {
+        // A fuzzy matched reporter string is neither, even though its
+        // editions are those of the abbreviation it was corrected to
+        if source.fuzzy_reporter().is_none() {
+            if !editions.exact.is_empty() {
+                score += weights.exact_edition;
+            } else if !editions.variations.is_empty() {
+                score += weights.variation_edition;
+            }
+        }
+
+        let mut editions = editions.iter().peekable();
+        if let (Some(year), Some(_)) = (source.parsed_year(), editions.peek()) {
+            score += if editions.any(|edition| edition.includes_year(year.into())) {
+                weights.year_in_edition_dates
+            } else {
+                weights.year_outside_edition_dates
+            };
+        }
+    }
+
+    if source.pin_cite().is_some() {
+        score += weights.pin_cite;
+    }
+    if source.plaintiff().is_some()
+        || source.defendant().is_some()
+        || source.antecedent_guess().is_some()
+    {
+        score += weights.case_name;
+    }
+    if source.fuzzy_reporter().is_some() {
+        score += weights.fuzzy_reporter;
+    }
+    if source.metadata.contains(&CitationMetadata::Ambiguous) {
+        score += weights.ambiguous;
+    }
+    if source.has_placeholder() {
+        score += weights.placeholder;
+    }
+
+    score.clamp(0.0, 1.0)
+}
+
+impl Citation<'_> {
+    /// The confidence that the citation is real, between 0 and 1, with the
+    /// default weights. See [`confidence`].
+    pub fn confidence(&self) -> f32 {
+        confidence(self, &ConfidenceWeights::default())
+    }
+}
+
+/// Keep the citations with at least `threshold` confidence.
+pub fn filter_by_confidence<'a>(
+    citations: Vec<Citation<'a>>,
+    threshold: f32,
+    weights: &ConfidenceWeights,
+) -> Vec<Citation<'a>> {
+    citations
+        .into_iter()
+        .filter(|citation| confidence(citation, weights) >= threshold)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{confidence, filter_by_confidence, ConfidenceWeights};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, FuzzyTokenizer};
+
+    #[test]
+    fn confidence_scores() {
+        let tokenizer = FuzzyTokenizer::new(
+            Ahocorasick::new(EXTRACTORS.as_slice()).unwrap(),
+            EXTRACTORS.as_slice(),
+        );
+        let text = "Roe v. Wade, 410 U.S. 113, 115 (1973). See 1 U. S. 1 (1800); 2 SO.2d 3.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+
+        let scores: Vec<_> = citations.iter().map(|c| c.confidence()).collect();
+        assert!((scores[0] - 0.95).abs() < 1e-6, "{scores:?}");
+        assert!((scores[1] - 0.25).abs() < 1e-6, "{scores:?}");
+        assert!((scores[2] - 0.2).abs() < 1e-6, "{scores:?}");
+
+        let weights = ConfidenceWeights {
+            fuzzy_reporter: 0.0,
+            ..Default::default()
+        };
+        assert!((confidence(&citations[2], &weights) - 0.5).abs() < 1e-6);
+
+        let kept = filter_by_confidence(citations, 0.5, &ConfidenceWeights::default());
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].source().plaintiff(), Some("Roe"));
+    }
+}
@@ -0,0 +1,354 @@
+/*!
+Convert citations and the warnings found extracting them into diagnostics
+shaped like those of the Language Server Protocol, so a citation linter can
+publish them to editors as they are.
+
+Ranges are lines and UTF-16 code unit offsets within lines, like LSP's
+default position encoding, and each diagnostic also keeps the byte span it
+was made from. Diagnostics for near misses found by [`crate::suggest`] come
+with the text to replace their span with, for quick fixes.
+ */
+
+use crate::find::models::Citation;
+use crate::offsets::OffsetIndex;
+use crate::report::{ExtractionReport, ExtractionWarning};
+use crate::suggest::{fuzzy_suggestion, NearMiss, Suggestion};
+use crate::validate::VolumeProblem;
+
+/// A position in a text, as a zero-based line and a zero-based UTF-16 code
+/// unit offset within the line.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+/// Start and end positions of a span of text.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    /// The number LSP uses for the severity.
+    pub fn lsp(&self) -> u8 {
+        match self {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+            Severity::Information => 3,
+            Severity::Hint => 4,
+        }
+    }
+}
+
+/// A problem with a citation, or with how it was extracted.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Diagnostic {
+    pub range: Range,
+    /// Start and stop byte offsets the range was converted from.
+    pub span: (usize, usize),
+    pub severity: Severity,
+    /// What kind of problem this is, like "malformed-year", for linters to
+    /// filter or link to documentation by.
+    pub code: &'static str,
+    pub message: String,
+    /// Text to replace the span with to fix the problem, if known.
+    pub suggestion: Option<String>,
+}
+
+/// Precomputed index for converting byte offsets into a text to
+/// [`Position`]s.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    offsets: OffsetIndex<'a>,
+    /// Byte offsets each line starts at.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(memchr::memchr_iter(b'\n', text.as_bytes()).map(|i| i + 1))
+            .collect();
+
+        Self {
+            offsets: OffsetIndex::new(text),
+            line_starts,
+        }
+    }
+
+    /// Convert a byte offset to a position. Offsets past the end of the text
+    /// are clamped to it.
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        Position {
+            line,
+            character: self.offsets.to_utf16(offset)
+                - self.offsets.to_utf16(self.line_starts[line]),
+        }
+    }
+
+    /// Convert a span of byte offsets to a range.
+    pub fn range(&self, (start, end): (usize, usize)) -> Range {
+        Range {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+
+    fn diagnostic(
+        &self,
+        span: (usize, usize),
+        severity: Severity,
+        code: &'static str,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            range: self.range(span),
+            span,
+            severity,
+            code,
+            message,
+            suggestion: None,
+        }
+    }
+}
+
+/// Convert an extraction warning to a diagnostic.
+pub fn warning_diagnostic(lines: &LineIndex, warning: &ExtractionWarning) -> Diagnostic {
+    match warning {
+        ExtractionWarning::MalformedYear { span, year, .. } => lines.diagnostic(
+            *span,
+            Severity::Error,
+            "malformed-year",
+            format!("\"{year}\" isn't a plausible year"),
+        ),
+        ExtractionWarning::ImplausibleVolume(warning) => lines.diagnostic(
+            warning.span,
+            Severity::Warning,
+            "implausible-volume",
+            match warning.problem {
+                VolumeProblem::Zero => "volumes start at 1".to_string(),
+                VolumeProblem::AfterLastVolume { max_volume } => format!(
+                    "volume {} is after the last volume, {max_volume}",
+                    warning.volume
+                ),
+                VolumeProblem::OutsideEditionYears => {
+                    format!("the reporter wasn't published in {}", warning.volume)
+                }
+            },
+        ),
+        ExtractionWarning::UnresolvedShortForm { span, .. } => lines.diagnostic(
+            *span,
+            Severity::Warning,
+            "unresolved-short-form",
+            "doesn't refer to any earlier full citation".to_string(),
+        ),
+        ExtractionWarning::MergeConflict { span } => lines.diagnostic(
+            *span,
+            Severity::Information,
+            "merge-conflict",
+            "matched by more than one extractor".to_string(),
+        ),
+        ExtractionWarning::SkippedOverlap { span } => lines.diagnostic(
+            *span,
+            Severity::Hint,
+            "skipped-overlap",
+            "overlaps an earlier citation, so it was skipped".to_string(),
+        ),
+        ExtractionWarning::MalformedMatch { span, group } => lines.diagnostic(
+            *span,
+            Severity::Warning,
+            "malformed-match",
+            format!("the {group} is malformed, so the citation was skipped"),
+        ),
+    }
+}
+
+/// Convert a suggestion to a diagnostic offering it as a fix.
+pub fn suggestion_diagnostic(lines: &LineIndex, suggestion: &Suggestion) -> Diagnostic {
+    let (code, message) = match suggestion.near_miss {
+        NearMiss::MissingPage => ("missing-page", "citation has no page"),
+        NearMiss::FuzzyReporter => ("fuzzy-reporter", "reporter is misspelled"),
+    };
+
+    Diagnostic {
+        suggestion: Some(suggestion.replacement.clone()),
+        ..lines.diagnostic(
+            suggestion.span,
+            Severity::Warning,
+            code,
+            format!("{message}, did you mean \"{}\"?", suggestion.replacement),
+        )
+    }
+}
+
+/// Convert suggestions, like those of
+/// [`Suggester::suggest`](crate::suggest::Suggester::suggest), to
+/// diagnostics offering them as fixes.
+pub fn suggestion_diagnostics(lines: &LineIndex, suggestions: &[Suggestion]) -> Vec<Diagnostic> {
+    suggestions
+        .iter()
+        .map(|suggestion| suggestion_diagnostic(lines, suggestion))
+        .collect()
+}
+
+/// Diagnostics for citations themselves: short case and supra citations,
+/// and quoted full case citations, that have no pin cite, and citations
+/// with a misspelled reporter.
+pub fn citation_diagnostics(lines: &LineIndex, citations: &[Citation]) -> Vec<Diagnostic> {
+    let fuzzy = citations
+        .iter()
+        .filter_map(fuzzy_suggestion)
+        .map(|suggestion| suggestion_diagnostic(lines, &suggestion));
+
+    citations
+        .iter()
+        .filter(|citation| {
+            let source = citation.source();
+            let needs_pin_cite = match citation {
+                Citation::ShortCase { .. } | Citation::Supra { .. } => true,
+                Citation::FullCase { .. } => source.quotation().is_some(),
+                _ => false,
+            };
+            needs_pin_cite && source.pin_cite().is_none()
+        })
+        .map(|citation| {
+            lines.diagnostic(
+                citation.source().span(),
+                Severity::Information,
+                "missing-pin-cite",
+                "has no pin cite".to_string(),
+            )
+        })
+        .chain(fuzzy)
+        .collect()
+}
+
+/// All diagnostics for the citations found in a text and the report made
+/// while finding them, in the order they appear in the text.
+pub fn diagnostics(
+    text: &str,
+    citations: &[Citation],
+    report: &ExtractionReport,
+) -> Vec<Diagnostic> {
+    let lines = LineIndex::new(text);
+    let mut diagnostics = citation_diagnostics(&lines, citations);
+    diagnostics.extend(
+        report
+            .warnings
+            .iter()
+            .map(|warning| warning_diagnostic(&lines, warning)),
+    );
+    diagnostics.sort_by_key(|d| (d.span, d.code));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diagnostics, suggestion_diagnostics, LineIndex, Position, Severity};
+    use crate::find::{get_citations, get_citations_with_report, FindOptions};
+    use crate::suggest::Suggester;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, FuzzyTokenizer};
+
+    #[test]
+    fn positions() {
+        let lines = LineIndex::new("ab\n\u{1F600}é c\n");
+        assert_eq!(
+            lines.position(1),
+            Position {
+                line: 0,
+                character: 1
+            }
+        );
+        assert_eq!(
+            lines.position(3),
+            Position {
+                line: 1,
+                character: 0
+            }
+        );
+        // The emoji is two UTF-16 code units, and "é" one
+        assert_eq!(
+            lines.position(10),
+            Position {
+                line: 1,
+                character: 4
+            }
+        );
+        assert_eq!(
+            lines.position(12),
+            Position {
+                line: 2,
+                character: 0
+            }
+        );
+    }
+
+    #[test]
+    fn lint_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (3020).\nId. at 2. Baz, supra.";
+        let (citations, report) =
+            get_citations_with_report(text, &FindOptions::default(), &tokenizer);
+
+        let diagnostics = diagnostics(text, &citations, &report);
+        let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "malformed-year",
+                "missing-pin-cite",
+                "unresolved-short-form"
+            ]
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].severity.lsp(), 1);
+        assert_eq!(diagnostics[1].range.start.line, 1);
+        let line_start = text.find('\n').unwrap() + 1;
+        assert_eq!(
+            diagnostics[1].range.start.character,
+            citations[2].source().span().0 - line_start
+        );
+    }
+
+    #[test]
+    fn quick_fixes() {
+        let tokenizer = FuzzyTokenizer::new(
+            Ahocorasick::new(EXTRACTORS.as_slice()).unwrap(),
+            EXTRACTORS.as_slice(),
+        );
+        let text = "See 410 U.S. (1973).\nAnd 1 SO.2d 5.";
+        let citations = get_citations(text, false, &tokenizer);
+        let lines = LineIndex::new(text);
+
+        let fuzzy = diagnostics(text, &citations, &Default::default());
+        assert_eq!(fuzzy.len(), 1);
+        assert_eq!(fuzzy[0].code, "fuzzy-reporter");
+        assert_eq!(fuzzy[0].suggestion.as_deref(), Some("1 So.2d 5"));
+        assert_eq!(
+            fuzzy[0].range.start,
+            Position {
+                line: 1,
+                character: 4
+            }
+        );
+
+        let suggestions = Suggester::new(EXTRACTORS.as_slice()).missing_pages(text, &citations);
+        let missing = suggestion_diagnostics(&lines, &suggestions);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].code, "missing-page");
+        assert_eq!(missing[0].suggestion.as_deref(), Some("410 U.S. ___"));
+    }
+}
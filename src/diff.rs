@@ -0,0 +1,185 @@
+/*!
+Compare the citations found by two extraction runs over the same text, like
+two versions of eyecite, or eyecite and python's eyecite once its output is
+converted to [`Citation`]s.
+
+Citations are paired up by their
+[`matched_span`](crate::find::models::CitationSource::matched_span), the
+text their token matched, which stays put when only their metadata
+changes, and reported as added, removed or changed, along with their
+[`CitationKey`].
+ */
+
+use crate::find::models::Citation;
+use crate::resolve::{resolve_canonical_citation, Resource};
+use std::collections::HashMap;
+
+/// What identifies a citation across runs: the span of the text its token
+/// matched and, for full citations, the resource it cites.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CitationKey {
+    pub span: (usize, usize),
+    pub resource: Option<Resource>,
+}
+
+impl CitationKey {
+    pub fn of(citation: &Citation) -> Self {
+        Self {
+            span: citation.source().matched_span(),
+            resource: citation
+                .is_full()
+                .then(|| resolve_canonical_citation(citation)),
+        }
+    }
+}
+
+/// What changed about a citation found by both runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difference {
+    /// It's a different kind of citation, like a short case citation
+    /// rather than a full one.
+    Kind,
+    /// Its span, including metadata like the case name, is different.
+    Span,
+    /// Its token matched different groups, like another volume or page.
+    Groups,
+    /// Its metadata, like the pin cite or year, is different.
+    Metadata,
+    /// It cites a different resource.
+    Resource,
+}
+
+/// A citation found by both runs, with differences.
+#[derive(Debug, Clone)]
+pub struct ChangedCitation<'c, 'a> {
+    pub key: CitationKey,
+    pub old: &'c Citation<'a>,
+    pub new: &'c Citation<'a>,
+    pub differences: Vec<Difference>,
+}
+
+/// How the citations of two runs differ. Each list is in the order the
+/// citations were found.
+#[derive(Debug, Clone, Default)]
+pub struct CitationDiff<'c, 'a> {
+    /// Citations only the new run found.
+    pub added: Vec<(CitationKey, &'c Citation<'a>)>,
+    /// Citations only the old run found.
+    pub removed: Vec<(CitationKey, &'c Citation<'a>)>,
+    pub changed: Vec<ChangedCitation<'c, 'a>>,
+}
+
+impl CitationDiff<'_, '_> {
+    /// Whether both runs found the same citations.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn differences(
+    old: &Citation,
+    new: &Citation,
+    old_key: &CitationKey,
+    new_key: &CitationKey,
+) -> Vec<Difference> {
+    let (old_source, new_source) = (old.source(), new.source());
+    [
+        (old.kind() != new.kind(), Difference::Kind),
+        (old_source.span() != new_source.span(), Difference::Span),
+        (old_source.groups != new_source.groups, Difference::Groups),
+        (
+            old_source.metadata != new_source.metadata,
+            Difference::Metadata,
+        ),
+        (old_key.resource != new_key.resource, Difference::Resource),
+    ]
+    .into_iter()
+    .filter_map(|(differs, difference)| differs.then_some(difference))
+    .collect()
+}
+
+/// Compare the citations of an old and a new run over the same text.
+pub fn diff_citations<'c, 'a>(
+    old: &'c [Citation<'a>],
+    new: &'c [Citation<'a>],
+) -> CitationDiff<'c, 'a> {
+    let mut unmatched: HashMap<(usize, usize), Vec<(CitationKey, &'c Citation<'a>)>> =
+        HashMap::new();
+    for citation in old.iter().rev() {
+        let key = CitationKey::of(citation);
+        unmatched.entry(key.span).or_default().push((key, citation));
+    }
+
+    let mut diff = CitationDiff::default();
+    for citation in new {
+        let key = CitationKey::of(citation);
+        let Some((old_key, old_citation)) = unmatched.get_mut(&key.span).and_then(Vec::pop) else {
+            diff.added.push((key, citation));
+            continue;
+        };
+
+        let differences = differences(old_citation, citation, &old_key, &key);
+        if !differences.is_empty() {
+            diff.changed.push(ChangedCitation {
+                key,
+                old: old_citation,
+                new: citation,
+                differences,
+            });
+        }
+    }
+
+    let mut removed: Vec<_> = unmatched.into_values().flatten().collect();
+    removed.sort_by_key(|(key, _)| key.span);
+    diff.removed = removed;
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_citations, Difference};
+    use crate::find::get_citations;
+    use crate::find::models::Citation;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn diff_runs() {
+        fn matched<'a>(citation: &Citation<'a>) -> &'a str {
+            citation.source().matched_text()
+        }
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1; 2 U.S. 2; 3 U.S. 3; 4 U.S. 4.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert!(diff_citations(&citations, &citations).is_empty());
+
+        let old = citations[..3].to_vec();
+        let mut new = citations[1..].to_vec();
+        new[0].source_mut().set_custom("treatment", "followed");
+
+        let diff = diff_citations(&old, &new);
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|(_, c)| matched(c))
+                .collect::<Vec<_>>(),
+            vec!["4 U.S. 4"]
+        );
+        assert_eq!(
+            diff.removed
+                .iter()
+                .map(|(_, c)| matched(c))
+                .collect::<Vec<_>>(),
+            vec!["1 U.S. 1"]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(matched(diff.changed[0].new), "2 U.S. 2");
+        assert_eq!(diff.changed[0].differences, vec![Difference::Metadata]);
+        assert_eq!(
+            diff.removed[0].0.resource.as_ref().unwrap().groups["volume"],
+            "1"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+/*!
+Explain which extractors matched a citation, so data problems can be traced
+back to the reporters-db entry responsible and filed precisely.
+
+Tokens keep a reference to the extra data of the extractor that made them,
+so the extractor is found again by identity among the extractors the
+tokenizer was built with, rather than recorded on every citation while
+finding them. Explaining costs nothing until it's asked for.
+ */
+
+use crate::find::models::Citation;
+use crate::tokenizers::extractors::{TokenExtractor, TokenExtractorExtra};
+use crate::tokenizers::models::TokenFactories;
+
+/// An extractor that matched a citation.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Explanation<'a> {
+    /// Index of the extractor in the list it was found in.
+    pub index: usize,
+    /// The resolved regex the extractor matched with.
+    pub regex: &'a str,
+    /// Strings that trigger the extractor, sorted.
+    pub strings: Vec<&'a str>,
+    /// The regex is a short form pattern, like "410 U.S., at 115", rather
+    /// than a full citation pattern.
+    pub short: bool,
+    pub token_factory: TokenFactories,
+}
+
+impl<'a> Explanation<'a> {
+    fn of(index: usize, extractor: &'a TokenExtractor) -> Self {
+        let mut strings: Vec<_> = extractor.strings.iter().map(String::as_str).collect();
+        strings.sort_unstable();
+
+        Self {
+            index,
+            regex: extractor.regex.value(),
+            strings,
+            short: extractor.extra.short,
+            token_factory: extractor.token_factory,
+        }
+    }
+}
+
+/// The extractors among `extractors` that matched a citation: the one that
+/// made its token, followed by those whose tokens were merged into it.
+/// Citations whose token was made by an extractor that isn't in the list,
+/// like reporters matched by a
+/// [`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer), have no
+/// explanation.
+pub fn explain<'a>(citation: &Citation, extractors: &'a [TokenExtractor]) -> Vec<Explanation<'a>> {
+    let Some(data) = citation.source().token.try_data() else {
+        return Vec::new();
+    };
+    let find = |extra: &TokenExtractorExtra| {
+        extractors
+            .iter()
+            .enumerate()
+            .find(|(_, extractor)| std::ptr::eq(&extractor.extra, extra))
+            .map(|(index, extractor)| Explanation::of(index, extractor))
+    };
+
+    std::iter::once(data.extra)
+        .chain(data.merged.iter().copied())
+        .filter_map(find)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explain;
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::models::TokenFactories;
+    use crate::tokenizers::{Ahocorasick, FuzzyTokenizer};
+
+    #[test]
+    fn explanations() {
+        let tokenizer = FuzzyTokenizer::new(
+            Ahocorasick::new(EXTRACTORS.as_slice()).unwrap(),
+            EXTRACTORS.as_slice(),
+        );
+        let text = "Roe v. Wade, 410 U.S. 113 (1973). Roe, 410 U.S., at 115. 1 SO.2d 5.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+
+        let full = explain(&citations[0], EXTRACTORS.as_slice());
+        assert_eq!(full.len(), 1);
+        assert!(!full[0].short);
+        assert!(full[0].strings.contains(&"U.S."));
+        assert_eq!(full[0].token_factory, TokenFactories::Citation);
+        assert_eq!(EXTRACTORS[full[0].index].regex.value(), full[0].regex);
+
+        let short = explain(&citations[1], EXTRACTORS.as_slice());
+        assert!(short[0].short);
+
+        // The misspelled reporter was matched by the fuzzy tokenizer
+        assert!(explain(&citations[2], EXTRACTORS.as_slice()).is_empty());
+    }
+}
@@ -1,8 +1,184 @@
-use crate::find::models::Citation;
-use crate::tokenizers::Tokenizer;
+use crate::find::models::{ByteCitation, Citation, CitationMetadata, CitationSource};
+use crate::helpers::{self, Document, ScanLimits};
+use crate::limits::{FindLimits, InputLimits, Unlimited};
+use crate::report::ExtractionReport;
+use crate::tokenizers::models::{GroupName, Token, TokenKinds};
+use crate::tokenizers::{self, TokenArena, TokenBuffers, Tokenizer};
+use crate::validate::VolumeLimits;
+use crate::EyeciteError;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
 
 pub mod models;
 
+/// What [`get_citations`] should do with citations that might refer to more
+/// than one reporter and can't be narrowed down by date.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Ambiguity {
+    /// Return them like any other citation.
+    #[default]
+    Keep,
+    /// Leave them out of the results.
+    Remove,
+    /// Return them with [`CitationMetadata::Ambiguous`] and a
+    /// [`CitationMetadata::CandidateEdition`] for each edition they might
+    /// refer to, so they can be disambiguated downstream.
+    Mark,
+}
+
+impl From<bool> for Ambiguity {
+    /// `true` removes ambiguous citations, matching `remove_ambiguous` in
+    /// python's eyecite.
+    fn from(remove_ambiguous: bool) -> Self {
+        if remove_ambiguous {
+            Ambiguity::Remove
+        } else {
+            Ambiguity::Keep
+        }
+    }
+}
+
+/// A built-in step of finding the metadata of citations, which can be
+/// skipped with [`FindOptions::disabled_metadata`].
+///
+/// The first steps find each citation's own metadata, like its year, court
+/// and pin cite, as the citation is extracted. Skipping them leaves both
+/// the metadata and the citation's fields empty. The rest are passes over
+/// all the citations found, after they are extracted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MetadataPass {
+    /// The plaintiff and defendant of full case citations, found by
+    /// scanning back from the citation.
+    CaseNames,
+    /// Pin cites, like "115" in "410 U.S. 113, 115", and their spans.
+    /// Short case citations keep the page after "at".
+    PinCites,
+    /// Years, and their spans.
+    Years,
+    /// Courts, from the parenthetical after a citation or guessed from the
+    /// reporter.
+    Courts,
+    /// Parentheticals after citations, like "(holding that it is)", and
+    /// their spans.
+    Parentheticals,
+    /// [`CitationMetadata::EnclosingCitation`]
+    NestedCitations,
+    /// [`CitationMetadata::StringCite`]
+    StringCites,
+    /// [`CitationMetadata::Quotation`]
+    Quotations,
+    /// [`CitationMetadata::Quoted`]. Without it,
+    /// [`FindOptions::exclude_quoted`] leaves out nothing.
+    Quoted,
+    /// [`CitationMetadata::Disposition`] and the judge and kind of separate
+    /// opinion cited.
+    Dispositions,
+    /// [`CitationMetadata::DocumentSection`]
+    Sections,
+    /// [`CitationMetadata::EditionChoice`]
+    EditionChoices,
+}
+
+impl MetadataPass {
+    /// Every pass, in the order they run.
+    pub const ALL: [MetadataPass; 12] = [
+        MetadataPass::CaseNames,
+        MetadataPass::PinCites,
+        MetadataPass::Years,
+        MetadataPass::Courts,
+        MetadataPass::Parentheticals,
+        MetadataPass::NestedCitations,
+        MetadataPass::StringCites,
+        MetadataPass::Quotations,
+        MetadataPass::Quoted,
+        MetadataPass::Dispositions,
+        MetadataPass::Sections,
+        MetadataPass::EditionChoices,
+    ];
+
+    fn run<'a>(
+        &self,
+        document: &Document<'_, 'a>,
+        options: &FindOptions,
+        citations: &mut [Citation<'a>],
+    ) {
+        match self {
+            // Found along with each citation
+            MetadataPass::CaseNames
+            | MetadataPass::PinCites
+            | MetadataPass::Years
+            | MetadataPass::Courts
+            | MetadataPass::Parentheticals => {}
+            MetadataPass::NestedCitations => {
+                helpers::add_nested_citation_metadata(document, citations)
+            }
+            MetadataPass::StringCites => helpers::add_string_cite_metadata(document, citations),
+            MetadataPass::Quotations => helpers::add_quotation_metadata(document, citations),
+            MetadataPass::Quoted => helpers::add_quoted_metadata(document, citations),
+            MetadataPass::Dispositions => helpers::add_disposition_metadata(citations),
+            MetadataPass::Sections => helpers::add_section_metadata(&options.sections, citations),
+            MetadataPass::EditionChoices => helpers::add_edition_choice_metadata(citations),
+        }
+    }
+}
+
+/**
+A pass over the citations found in a document that adds metadata to them,
+for things eyecite doesn't know about, like appeal numbers. See
+[`FindOptions::metadata_extractors`].
+
+Extractors run after the built-in [`MetadataPass`]es, in order, so they can
+use and replace the metadata those found. Application-specific values can
+be recorded with [`CitationSource::set_custom`].
+ */
+pub trait MetadataExtractor: Debug + Send + Sync {
+    fn extract<'a>(&self, document: &Document<'_, 'a>, citations: &mut [Citation<'a>]);
+}
+
+/// Options for [`get_citations_with_options`].
+#[derive(Debug, Default, Clone)]
+pub struct FindOptions {
+    /// What to do with ambiguous citations.
+    pub ambiguous: Ambiguity,
+    /// How far to scan around citations for their metadata.
+    pub scan_limits: ScanLimits,
+    /// Kinds of tokens to extract. Leaving out kinds that can't appear in a
+    /// text, like supra and id citations in headnotes, skips running their
+    /// extractors. Record citations are only found if
+    /// [`TokenKinds::RECORD`] is added.
+    pub kinds: TokenKinds,
+    /// Start and stop offsets of the sections of the document, like the
+    /// syllabus, the majority opinion and each dissent in CourtListener's
+    /// XML. Each citation records the section it is in, and
+    /// [`resolve`](crate::resolve) doesn't resolve id, supra or short case
+    /// citations to citations in other sections.
+    pub sections: Vec<(usize, usize)>,
+    /// Leave out citations inside quotation marks, like those quoted from
+    /// other opinions, rather than returning them with
+    /// [`CitationMetadata::Quoted`].
+    pub exclude_quoted: bool,
+    /// Built-in metadata passes to skip, for metadata an application
+    /// doesn't need or finds itself.
+    pub disabled_metadata: HashSet<MetadataPass>,
+    /// Passes adding metadata of the application's own, run after the
+    /// built-in ones.
+    pub metadata_extractors: Vec<Arc<dyn MetadataExtractor>>,
+}
+
+impl FindOptions {
+    /// Whether a built-in metadata pass runs.
+    pub fn runs(&self, pass: MetadataPass) -> bool {
+        !self.disabled_metadata.contains(&pass)
+    }
+
+    /// A piece of metadata found by a pass, if the pass runs.
+    fn found_by<T>(&self, pass: MetadataPass, value: Option<T>) -> Option<T> {
+        value.filter(|_| self.runs(pass))
+    }
+}
+
 /**!
 This is eyecite's main workhorse function. Given a string of text
 (e.g., a judicial opinion or other legal document), return a list of
@@ -14,9 +190,11 @@ Args:
         The text to parse. You may wish to use the 'eyecite.clean.clean_text'
         function to pre-process your text
         before passing it here.
-    remove_ambiguous:
-        Whether to remove citations that might refer to more
-        than one reporter and can't be narrowed down by date.
+    ambiguous:
+        What to do with citations that might refer to more
+        than one reporter and can't be narrowed down by date. Passing
+        `true` removes them, like python's `remove_ambiguous`. See
+        [`Ambiguity`].
     tokenizer:
         An instance of a Tokenizer object. See 'eyecite.tokenizers'
         for information about available tokenizers. Uses the
@@ -27,13 +205,1675 @@ Returns:
  */
 pub fn get_citations<'a>(
     plain_text: &'a str,
-    _remove_ambiguous: bool,
+    ambiguous: impl Into<Ambiguity>,
     tokenizer: &'a (dyn Tokenizer<'a>),
 ) -> Vec<Citation<'a>> {
-    let (_words, citation_tokens) = tokenizer.tokenize(plain_text);
-    let citations = Vec::new();
+    let options = FindOptions {
+        ambiguous: ambiguous.into(),
+        ..Default::default()
+    };
+
+    get_citations_with_options(plain_text, &options, tokenizer)
+}
+
+/// Like [`get_citations`], with more control over how citations are found.
+pub fn get_citations_with_options<'a>(
+    plain_text: &'a str,
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> Vec<Citation<'a>> {
+    let tokens = tokenizer.tokenize_citations_of(plain_text, options.kinds);
+    // Words are only needed to scan for metadata around citations, so don't
+    // bother splitting documents without any
+    if tokens.is_empty() {
+        return Vec::new();
+    }
 
-    for (_i, _token) in citation_tokens {}
+    let (words, citation_tokens) = tokenizers::split_words(plain_text, tokens);
+    _find_all_citations(plain_text, &words, citation_tokens, options)
+}
 
+/// Like [`get_citations_with_options`], only matching citations within
+/// `range` of the text, like the paragraph an editor just changed, without
+/// re-extracting the whole document.
+///
+/// Spans of the returned citations, and of their metadata, are offsets into
+/// the whole text, but metadata like case names is only scanned for within
+/// the range, and token indexes are into the words of the range. Sections in
+/// `options` are offsets into the whole text too.
+///
+/// A `range` past the end of the text is clamped to it, and ends that fall
+/// inside a character are moved out to the character's boundaries.
+pub fn get_citations_in_range<'a>(
+    plain_text: &'a str,
+    range: Range<usize>,
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> Vec<Citation<'a>> {
+    let end = helpers::ceil_char_boundary(plain_text, range.end.min(plain_text.len()));
+    let offset = helpers::floor_char_boundary(plain_text, range.start.min(end));
+    let window = plain_text.get(offset..end).unwrap_or_default();
+    let clip = |position: usize| position.saturating_sub(offset).min(window.len());
+    let options = FindOptions {
+        sections: options
+            .sections
+            .iter()
+            .map(|&(start, end)| (clip(start), clip(end)))
+            .collect(),
+        ..options.clone()
+    };
+
+    let mut citations = get_citations_with_options(window, &options, tokenizer);
+    // Offsets into a str always fit in an isize
+    let delta = isize::try_from(offset).unwrap_or(isize::MAX);
+    for citation in &mut citations {
+        citation.source_mut().shift(delta);
+    }
     citations
 }
+
+/// Like [`get_citations_with_options`], for text that was already
+/// tokenized, so a document can be tokenized once and analyzed many times,
+/// or tokenized by a custom tokenizer.
+///
+/// `words` and `citation_tokens` should be what [`tokenizers::split_words`]
+/// returns for the text: the words, spaces and tokens that make up the
+/// whole text, in order, and the tokens worth extracting citations from,
+/// along with their index in `words`. Tokens of kinds that aren't
+/// citations are skipped.
+pub fn get_citations_from_tokens<'a>(
+    plain_text: &'a str,
+    words: &[Token<'a>],
+    citation_tokens: impl IntoIterator<Item = (usize, Token<'a>)>,
+    options: &FindOptions,
+) -> Vec<Citation<'a>> {
+    _find_all_citations(plain_text, words, citation_tokens, options)
+}
+
+/// Like [`get_citations_with_options`], giving up with an error when the
+/// input or the work needed to extract its citations exceeds `limits`. Use
+/// this on untrusted input.
+pub fn try_get_citations<'a>(
+    plain_text: &'a str,
+    options: &FindOptions,
+    limits: &InputLimits,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> Result<Vec<Citation<'a>>, EyeciteError> {
+    limits.check_input_len(plain_text.len())?;
+
+    let (words, citation_tokens, _) = tokenizer.try_tokenize(plain_text, options.kinds, limits)?;
+    _find_citations(plain_text, &words, citation_tokens, options, limits)
+}
+
+/// Like [`get_citations_with_options`], also returning an
+/// [`ExtractionReport`] of things that went wrong along the way.
+pub fn get_citations_with_report<'a>(
+    plain_text: &'a str,
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> (Vec<Citation<'a>>, ExtractionReport) {
+    let mut buffers = TokenBuffers::with_kinds(options.kinds);
+    tokenizer.tokenize_into(plain_text, &mut buffers);
+    let kept: Vec<_> = buffers
+        .citation_tokens
+        .iter()
+        .map(|(_, token)| (token.start(), token.end()))
+        .collect();
+    let mut report = ExtractionReport::default();
+    report.add_malformed_matches(buffers.citation_tokens.iter().map(|(_, token)| token));
+
+    let citations = _find_all_citations(
+        plain_text,
+        &buffers.words,
+        buffers.citation_tokens.drain(..),
+        options,
+    );
+
+    report
+        .warnings
+        .extend(ExtractionReport::new(&citations, &VolumeLimits::default()).warnings);
+    report.add_skipped_tokens(&buffers.skipped, &kept);
+
+    (citations, report)
+}
+
+/// Like [`get_citations_with_options`], tokenizing with the vectors of an
+/// arena that is reused across documents. See [`TokenArena`].
+pub fn get_citations_in<'a>(
+    plain_text: &'a str,
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+    arena: &mut TokenArena,
+) -> Vec<Citation<'a>> {
+    arena.with_tokens(
+        plain_text,
+        tokenizer,
+        options.kinds,
+        |words, citation_tokens| {
+            _find_all_citations(plain_text, words, citation_tokens.drain(..), options)
+        },
+    )
+}
+
+/// [`_find_citations`] without limits, which can't fail.
+fn _find_all_citations<'a>(
+    plain_text: &'a str,
+    words: &[Token<'a>],
+    citation_tokens: impl IntoIterator<Item = (usize, Token<'a>)>,
+    options: &FindOptions,
+) -> Vec<Citation<'a>> {
+    match _find_citations(plain_text, words, citation_tokens, options, &Unlimited) {
+        Ok(citations) => citations,
+        Err(never) => match never {},
+    }
+}
+
+fn _find_citations<'a, L: FindLimits>(
+    plain_text: &'a str,
+    words: &[Token<'a>],
+    citation_tokens: impl IntoIterator<Item = (usize, Token<'a>)>,
+    options: &FindOptions,
+    limits: &L,
+) -> Result<Vec<Citation<'a>>, L::Error> {
+    let document = Document::new(plain_text, words).with_limits(options.scan_limits);
+    let mut citations = Vec::new();
+    // Short names given by "[hereinafter ...]" so far
+    let mut aliases = Vec::new();
+
+    for (i, token) in citation_tokens {
+        limits.check_deadline()?;
+
+        let citation = match &token {
+            // CASE 1a: Token is a law, like a statute or regulation, matched by
+            // an extractor with `law` set. Laws are only parsed with the
+            // `laws` feature.
+            #[cfg(feature = "laws")]
+            Token::Citation(data) if data.extra.law && helpers::malformed_group(data).is_none() => {
+                _extract_law_citation(&document, i, options)
+            }
+
+            // CASE 1b: Token is a CitationToken (i.e., a reporter, or a law
+            // journal).
+            // In this case, first try extracting it as a standard, full citation,
+            // and if that fails try extracting it as a short form citation.
+            Token::Citation(data) => {
+                // Skip malformed matches rather than giving up on the whole
+                // document; reports record them as warnings
+                if helpers::malformed_group(data).is_some() || data.extra.law {
+                    continue;
+                }
+                let mut citation = if data.extra.short {
+                    _extract_shortform_citation(&document, i, &aliases, options)
+                } else {
+                    _extract_full_citation(&document, i, options)
+                };
+                citation
+                    .source_mut()
+                    .add_metadata(CitationMetadata::FuzzyReporter, data.extra.fuzzy.as_deref());
+                citation
+            }
+
+            // CASE 2: Token is an "Id." or "Ibid." reference.
+            // In this case, the citation should simply be to the item cited
+            // immediately prior, but for safety we will leave that resolution up
+            // to the user.
+            Token::Id(_) => _extract_id_citation(&document, i, options),
+
+            // CASE 3: Token is a "supra" reference.
+            // In this case, we're not sure yet what the citation's antecedent is.
+            // It could be any of the previous citations above. Thus, like an Id.
+            // citation, for safety we won't resolve this reference yet.
+            Token::Supra(_) => _extract_supra_citation(&document, i, &aliases, options),
+
+            // CASE 4: Token is an "infra" reference.
+            // In this case, the citation points forward to a part of the
+            // document that we haven't seen yet, so we just record what it
+            // points to.
+            Token::Infra(_) => _extract_infra_citation(&document, i),
+
+            // CASE 5: Token is a slip opinion, cited by docket number before
+            // being published in a reporter.
+            Token::SlipOpinion(_) => _extract_slip_opinion_citation(&document, i, options),
+
+            // CASE 6: Token refers to another part of the document, like
+            // "Section II.B". An infra or supra citation right before it, like
+            // "infra Part III", already points to it.
+            Token::CrossReference(data) => {
+                if citations
+                    .last()
+                    .is_some_and(|c: &Citation| c.source().span().1 > data.start)
+                {
+                    continue;
+                }
+                _extract_cross_reference_citation(&document, i)
+            }
+
+            // CASE 7: Token cites the record of the case, like "R. at 45".
+            Token::Record(_) => _extract_record_citation(&document, i, options),
+
+            // CASE 8: Token is a section marker.
+            // In this case, it's likely that this is a reference to a citation,
+            // but we're not sure what it is if it doesn't match any of the above.
+            // So we record this marker in order to keep an accurate list of the
+            // possible antecedents for id citations.
+            Token::Section(_) => Citation::Unknown {
+                source: CitationSource::new(token, i),
+            },
+
+            // CASE 9: Token is of a kind defined by a custom extractor.
+            Token::Custom(kind, _) => Citation::Custom {
+                kind,
+                source: CitationSource::new(token, i),
+            },
+
+            // CASE 10: The token is not a citation.
+            _ => continue,
+        };
+
+        if let Some(alias) = citation.source().hereinafter() {
+            aliases.push(alias);
+        }
+        citations.push(citation);
+        limits.check_citations(citations.len())?;
+    }
+
+    for pass in MetadataPass::ALL {
+        if options.runs(pass) {
+            pass.run(&document, options, &mut citations);
+        }
+    }
+    for extractor in &options.metadata_extractors {
+        extractor.extract(&document, &mut citations);
+    }
+
+    if options.exclude_quoted {
+        citations.retain(|citation| !citation.source().is_quoted());
+    }
+
+    // Remove citations with multiple reporter candidates where we couldn't
+    // guess correct reporter
+    let citations = match options.ambiguous {
+        Ambiguity::Keep => citations,
+        Ambiguity::Remove => helpers::disambiguate_reporters(citations),
+        Ambiguity::Mark => helpers::mark_ambiguous_reporters(citations),
+    };
+
+    // Only checked in eyecite's own tests, so a broken span doesn't panic
+    // in the debug builds of crates using it
+    #[cfg(test)]
+    {
+        let violations = crate::validate::validate_spans(plain_text, &citations);
+        debug_assert!(
+            violations.is_empty(),
+            "broken citation spans: {violations:?}"
+        );
+    }
+
+    // Returns a list of citations ordered in the sequence that they appear in
+    // the document. The ordering of this list is important for reconstructing
+    // the references of the ShortCase, Supra, and Id citations.
+    Ok(citations)
+}
+
+/// Split bytes into their runs of valid UTF-8, along with the offset each
+/// run starts at.
+fn valid_utf8_runs(bytes: &[u8]) -> Vec<(usize, &str)> {
+    let mut runs = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(_) => (rest.len(), 0),
+            Err(e) => (
+                e.valid_up_to(),
+                e.error_len().unwrap_or(rest.len() - e.valid_up_to()),
+            ),
+        };
+
+        if let Ok(run) = std::str::from_utf8(&rest[..valid]) {
+            if !run.is_empty() {
+                runs.push((offset, run));
+            }
+        }
+        offset += valid + invalid;
+    }
+
+    runs
+}
+
+/// Like [`get_citations_with_options`], for documents that may contain
+/// invalid UTF-8, which is common in scanned or converted archives.
+///
+/// Instead of converting the whole document lossily, which would shift
+/// offsets, each run of valid UTF-8 is searched on its own, and the spans of
+/// the returned citations are byte offsets into the original `bytes`.
+/// Citations interrupted by invalid bytes aren't found.
+pub fn get_citations_from_bytes<'a>(
+    bytes: &'a [u8],
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> Vec<ByteCitation<'a>> {
+    valid_utf8_runs(bytes)
+        .into_iter()
+        .flat_map(|(offset, text)| {
+            get_citations_with_options(text, options, tokenizer)
+                .into_iter()
+                .map(move |citation| ByteCitation { offset, citation })
+        })
+        .collect()
+}
+
+/// Record the pin cite and parenthetical after a short form citation, if
+/// their passes run.
+fn add_pin_cite_metadata<'a>(
+    source: &mut CitationSource<'a>,
+    post: &helpers::PinCite<'a>,
+    options: &FindOptions,
+) {
+    if options.runs(MetadataPass::PinCites) {
+        source.add_metadata(CitationMetadata::PinCite, post.pin_cite);
+        source.add_span(CitationMetadata::PinCiteSpan, post.pin_cite_span);
+    }
+    if options.runs(MetadataPass::Parentheticals) {
+        source.add_metadata(CitationMetadata::Parenthetical, post.parenthetical);
+        source.add_span(CitationMetadata::ParentheticalSpan, post.parenthetical_span);
+    }
+}
+
+/// Given a list of words and the index of a citation, return
+/// a full citation.
+fn _extract_full_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let post = helpers::extract_post_citation(document, index);
+    let (plaintiff, defendant) = if options.runs(MetadataPass::CaseNames) {
+        helpers::extract_defendant(document, index)
+    } else {
+        (None, None)
+    };
+    let hereinafter = helpers::extract_hereinafter(document, index);
+    let court = post.court.or_else(|| {
+        source
+            .editions()
+            .and_then(|editions| helpers::guess_court(editions))
+    });
+    let court = options.found_by(MetadataPass::Courts, court);
+    let pin_cite = options.found_by(MetadataPass::PinCites, post.pin_cite);
+    let year = options.found_by(MetadataPass::Years, post.year);
+
+    source.add_metadata(CitationMetadata::PinCite, pin_cite);
+    source.add_metadata(CitationMetadata::Extra, post.extra);
+    source.add_metadata(
+        CitationMetadata::Parenthetical,
+        options.found_by(MetadataPass::Parentheticals, post.parenthetical),
+    );
+    source.add_metadata(CitationMetadata::Year, year);
+    source.add_span(
+        CitationMetadata::PinCiteSpan,
+        options.found_by(MetadataPass::PinCites, post.pin_cite_span),
+    );
+    source.add_span(
+        CitationMetadata::ParentheticalSpan,
+        options.found_by(MetadataPass::Parentheticals, post.parenthetical_span),
+    );
+    source.add_span(
+        CitationMetadata::YearSpan,
+        options.found_by(MetadataPass::Years, post.year_span),
+    );
+    source.add_metadata(CitationMetadata::Court, court);
+    source.add_metadata(CitationMetadata::Plaintiff, plaintiff);
+    source.add_metadata(CitationMetadata::Defendant, defendant);
+    source.add_metadata(CitationMetadata::Hereinafter, hereinafter);
+
+    Citation::FullCase {
+        source,
+        pin_cite,
+        year,
+        court,
+        plaintiff,
+        defendant,
+        extra: post.extra,
+    }
+}
+
+/// Given a list of words and the index of a citation, construct and return
+/// a short case citation.
+///
+/// Shortform 1: Adarand, 515 U.S., at 241
+/// Shortform 2: 515 U.S., at 241
+fn _extract_shortform_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    aliases: &[&'a str],
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    // get antecedent word, or a short name given by an earlier citation
+    let antecedent_guess = helpers::extract_hereinafter_antecedent(document, index, aliases)
+        .or_else(|| helpers::extract_short_cite_antecedent(document, index));
+
+    // Get pin_cite, starting from the page of the short cite
+    let page_start = source.token.try_data().and_then(|data| {
+        data.groups
+            .get(GroupName::Page)
+            .map(|page| data.end - page.len())
+    });
+    let post = helpers::extract_pin_cite(document, index, page_start);
+    let pin_cite = options.found_by(MetadataPass::PinCites, post.pin_cite);
+    let court = source
+        .editions()
+        .and_then(|editions| helpers::guess_court(editions));
+    let court = options.found_by(MetadataPass::Courts, court);
+
+    // Separate the page after "at" from any pin cites after it, like "243"
+    // in "515 U.S., at 241, 243"
+    let page = source
+        .token
+        .try_data()
+        .and_then(|data| data.groups.get(GroupName::Page));
+    let additional_pin_cite = pin_cite
+        .zip(page)
+        .and_then(|(pin_cite, page)| pin_cite.strip_prefix(page)?.strip_prefix(','))
+        .and_then(helpers::clean_pin_cite);
+
+    source.span_end = Some(post.span_end);
+    source.add_metadata(CitationMetadata::AntecedentGuess, antecedent_guess);
+    add_pin_cite_metadata(&mut source, &post, options);
+    source.add_metadata(CitationMetadata::Court, court);
+
+    Citation::ShortCase {
+        source,
+        pin_cite,
+        page,
+        additional_pin_cite,
+        year: None,
+        court,
+        antecedent_guess,
+    }
+}
+
+/// Given a list of words and the index of a supra token, look before
+/// and after to see if this is a supra citation. If found, construct
+/// and return a supra citation.
+///
+/// Supra 1: Adarand, supra, at 240
+/// Supra 2: Adarand, 515 supra, at 240
+/// Supra 3: Adarand, supra, somethingelse
+/// Supra 4: Adrand, supra. somethingelse
+/// Supra 5: Smith Report, supra, at 240 (after "[hereinafter Smith Report]")
+///
+/// Without a case name before it, like "see supra Part III", this is a
+/// cross reference to another part of the document instead.
+fn _extract_supra_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    aliases: &[&'a str],
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let post = helpers::extract_pin_cite(document, index, None);
+    let (antecedent_guess, volume) =
+        match helpers::extract_hereinafter_antecedent(document, index, aliases) {
+            Some(alias) => (Some(alias), None),
+            None => helpers::extract_supra_antecedent(document, index),
+        };
+
+    // Without a case name before it, "supra" points to part of this
+    // document, like "see supra Part III", rather than to an earlier citation
+    if !antecedent_guess.is_some_and(helpers::is_case_name_word) {
+        if let Some((reference, span_end)) = helpers::extract_infra_reference(document, index) {
+            source.span_end = Some(span_end);
+            source.add_metadata(CitationMetadata::Reference, Some(reference));
+            return Citation::CrossReference { source, reference };
+        }
+    }
+
+    source.span_end = Some(post.span_end);
+    source.add_metadata(CitationMetadata::AntecedentGuess, antecedent_guess);
+    add_pin_cite_metadata(&mut source, &post, options);
+    source.add_metadata(CitationMetadata::Volume, volume);
+    source.add_metadata(
+        CitationMetadata::Note,
+        helpers::extract_supra_note(document, index),
+    );
+
+    Citation::Supra {
+        source,
+        pin_cite: options.found_by(MetadataPass::PinCites, post.pin_cite),
+        year: None,
+        court: None,
+        antecedent_guess,
+        volume,
+    }
+}
+
+/// Given a list of words and the index of an infra token, look after it
+/// for what it refers to and construct and return an infra citation.
+///
+/// Infra 1: infra note 45
+/// Infra 2: infra Part III.B
+fn _extract_infra_citation<'a>(document: &Document<'_, 'a>, index: usize) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let (reference, span_end) = match helpers::extract_infra_reference(document, index) {
+        Some((reference, span_end)) => (Some(reference), Some(span_end)),
+        None => (None, None),
+    };
+
+    source.span_end = span_end;
+    source.add_metadata(CitationMetadata::Reference, reference);
+
+    Citation::Infra { source, reference }
+}
+
+/// Given a list of words and the index of a cross reference token, return a
+/// cross reference citation.
+///
+/// Cross reference: see Section II.B
+fn _extract_cross_reference_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+    let reference = source.token.try_data().map_or("", |data| data.data);
+
+    source.add_metadata(CitationMetadata::Reference, Some(reference));
+
+    Citation::CrossReference { source, reference }
+}
+
+/// Given a list of words and the index of a record token, return a record
+/// citation. Bare labels like "R." are captured in their own groups, so
+/// they are stored as the `document` and `page` groups like the others.
+///
+/// Record 1: ECF No. 27 at 3
+/// Record 2: R. at 45
+fn _extract_record_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    for (bare, name) in [("bare_document", "document"), ("bare_page", "page")] {
+        if let Some(value) = source.groups.remove(bare) {
+            source.groups.insert(name, value);
+        }
+    }
+
+    let groups = source.token.try_data().map(|data| &data.groups);
+    let record = groups
+        .and_then(|g| g.get("document").or_else(|| g.get("bare_document")))
+        .unwrap_or_default();
+    let page = groups.and_then(|g| g.get("page").or_else(|| g.get("bare_page")));
+    if options.runs(MetadataPass::PinCites) {
+        source.add_metadata(CitationMetadata::PinCite, page);
+        let page_span = source
+            .group_span("page")
+            .or_else(|| source.group_span("bare_page"));
+        source.add_span(CitationMetadata::PinCiteSpan, page_span);
+    }
+
+    Citation::Record {
+        source,
+        document: record,
+        page,
+    }
+}
+
+/// Given a list of words and the index of an id token, gather the
+/// immediately succeeding tokens to construct and return an id
+/// citation.
+///
+/// Id 1: id., at 240
+/// Id 2: At 240, the Court ... (the pin cite is part of the token)
+fn _extract_id_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let mut post = helpers::extract_pin_cite(document, index, None);
+    // The pin cite may be part of the token, like "At 240"
+    if post.pin_cite.is_none() {
+        post.pin_cite = source
+            .token
+            .try_data()
+            .and_then(|data| data.groups.get(GroupName::PinCite));
+        post.pin_cite_span = source.group_span("pin_cite");
+    }
+    let pin_cite = options.found_by(MetadataPass::PinCites, post.pin_cite);
+
+    source.span_end = Some(post.span_end);
+    add_pin_cite_metadata(&mut source, &post, options);
+
+    Citation::Id {
+        source,
+        pin_cite,
+        year: None,
+        court: None,
+        antecedent_guess: None,
+        volume: None,
+    }
+}
+
+/// Given a list of words and the index of a slip opinion token, look
+/// after it for the court and date, and construct and return a slip opinion
+/// citation.
+///
+/// Slip opinion: No. 19-1392, slip op. at 12 (U.S. June 24, 2022)
+fn _extract_slip_opinion_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let groups = source.token.try_data().map(|data| &data.groups);
+    let docket_number = groups.and_then(|g| g.get(GroupName::DocketNumber));
+    let pin_cite = groups.and_then(|g| g.get(GroupName::PinCite));
+    let pin_cite = options.found_by(MetadataPass::PinCites, pin_cite);
+    let mut date = helpers::extract_slip_opinion_date(document, index).unwrap_or_default();
+    let span_end = date.year.map(|_| date.span_end);
+    date.court = options.found_by(MetadataPass::Courts, date.court);
+    date.year = options.found_by(MetadataPass::Years, date.year);
+    date.year_span = options.found_by(MetadataPass::Years, date.year_span);
+    date.parenthetical = options.found_by(MetadataPass::Parentheticals, date.parenthetical);
+    date.parenthetical_span =
+        options.found_by(MetadataPass::Parentheticals, date.parenthetical_span);
+
+    if span_end.is_some() {
+        source.span_end = span_end;
+    }
+    source.add_metadata(CitationMetadata::DocketNumber, docket_number);
+    source.add_metadata(CitationMetadata::PinCite, pin_cite);
+    source.add_metadata(CitationMetadata::Court, date.court);
+    source.add_metadata(CitationMetadata::Month, date.month);
+    source.add_metadata(CitationMetadata::Day, date.day);
+    source.add_metadata(CitationMetadata::Year, date.year);
+    source.add_metadata(CitationMetadata::Parenthetical, date.parenthetical);
+    source.add_span(
+        CitationMetadata::PinCiteSpan,
+        options.found_by(MetadataPass::PinCites, source.group_span("pin_cite")),
+    );
+    source.add_span(CitationMetadata::YearSpan, date.year_span);
+    source.add_span(CitationMetadata::ParentheticalSpan, date.parenthetical_span);
+
+    Citation::SlipOpinion {
+        source,
+        docket_number,
+        pin_cite,
+        court: date.court,
+        month: date.month,
+        day: date.day,
+        year: date.year,
+    }
+}
+
+/// Given a list of words and the index of a law citation, like a statute,
+/// return a law citation along with its publisher, date and status.
+#[cfg(feature = "laws")]
+fn _extract_law_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    options: &FindOptions,
+) -> Citation<'a> {
+    let mut source = CitationSource::new(document.words[index].clone(), index);
+
+    let mut post = helpers::extract_post_law_citation(document, index).unwrap_or_default();
+
+    if post.year.is_some() {
+        source.span_end = Some(post.span_end);
+    }
+    post.year = options.found_by(MetadataPass::Years, post.year);
+    post.year_span = options.found_by(MetadataPass::Years, post.year_span);
+    post.parenthetical = options.found_by(MetadataPass::Parentheticals, post.parenthetical);
+    post.parenthetical_span =
+        options.found_by(MetadataPass::Parentheticals, post.parenthetical_span);
+    source.add_metadata(CitationMetadata::Publisher, post.publisher);
+    source.add_metadata(CitationMetadata::Status, post.status);
+    source.add_metadata(CitationMetadata::Month, post.month);
+    source.add_metadata(CitationMetadata::Day, post.day);
+    source.add_metadata(CitationMetadata::Year, post.year);
+    source.add_metadata(CitationMetadata::Parenthetical, post.parenthetical);
+    source.add_span(CitationMetadata::YearSpan, post.year_span);
+    source.add_span(CitationMetadata::ParentheticalSpan, post.parenthetical_span);
+
+    Citation::Law {
+        source,
+        publisher: post.publisher,
+        day: post.day,
+        month: post.month,
+        year: post.year,
+        status: post.status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_citations, get_citations_from_bytes, get_citations_from_tokens, get_citations_in,
+        get_citations_in_range, get_citations_with_options, try_get_citations, Ambiguity,
+        FindOptions,
+    };
+    use crate::find::models::{Citation, CitationMetadata};
+    use crate::helpers::ScanLimits;
+    use crate::limits::InputLimits;
+    use crate::regexes;
+    use crate::tokenizers::extractors::{filtered, TokenExtractor, EXTRACTORS};
+    use crate::tokenizers::models::{Token, TokenFactories, TokenKinds};
+    use crate::tokenizers::{Ahocorasick, TokenArena, Tokenizer};
+    use crate::EyeciteError;
+    use reporters_db::regexes::ResolvedRegex;
+    use std::ops::Range;
+    use std::time::Instant;
+
+    #[test]
+    fn full_case_metadata() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+
+        let citations = get_citations("See Roe v. Wade, 410 U. S. 113 (1973)", false, &tokenizer);
+
+        assert_eq!(citations.len(), 1);
+        match &citations[0] {
+            Citation::FullCase {
+                source,
+                year,
+                court,
+                plaintiff,
+                defendant,
+                ..
+            } => {
+                assert_eq!(source.groups["volume"], "410");
+                assert_eq!(*year, Some("1973"));
+                assert_eq!(*court, Some("scotus"));
+                assert_eq!(*plaintiff, Some("Roe"));
+                assert_eq!(*defendant, Some("Wade"));
+            }
+            _ => panic!("expected a full case citation"),
+        }
+    }
+
+    #[test]
+    fn diacritics_in_party_names() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Peña v. Muñoz, 410 U.S. 113 (1973); Müller, 5 U.S. 6é.";
+
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        let source = citations[0].source();
+        assert_eq!(source.matched_text(), "410 U.S. 113");
+        assert_eq!(
+            (source.plaintiff(), source.defendant()),
+            (Some("Peña"), Some("Muñoz"))
+        );
+    }
+
+    #[test]
+    fn pretokenized_text() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113 (1973). Id. at 115; 1 U.S. 1.";
+        let (words, citation_tokens) = tokenizer.tokenize(text);
+
+        let citations = get_citations_from_tokens(
+            text,
+            &words,
+            citation_tokens.iter().cloned(),
+            &FindOptions::default(),
+        );
+        assert_eq!(citations, get_citations(text, false, &tokenizer));
+
+        // The same tokens can be analyzed again, differently
+        let ids = citation_tokens
+            .into_iter()
+            .filter(|(_, token)| matches!(token, Token::Id(_)));
+        let citations = get_citations_from_tokens(text, &words, ids, &FindOptions::default());
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().matched_text(), "Id.");
+    }
+
+    #[test]
+    fn metadata_accessors() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 120 (1973) (holding so).";
+
+        let mut citations = get_citations(text, false, &tokenizer);
+        let source = citations[0].source_mut();
+        assert_eq!(
+            (source.pin_cite(), source.year(), source.court()),
+            (Some("120"), Some("1973"), Some("scotus"))
+        );
+        assert_eq!(source.parenthetical(), Some("holding so"));
+        assert_eq!(source.signal(), Some("See"));
+        assert_eq!(source.docket_number(), None);
+
+        source.set_custom("treatment", "followed");
+        source.set_custom("treatment", "distinguished");
+        assert_eq!(source.custom("treatment"), Some("distinguished"));
+        assert_eq!(source.custom("other"), None);
+    }
+
+    #[test]
+    fn group_spans() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 120 (1973).";
+
+        let citations = get_citations(text, false, &tokenizer);
+        let source = citations[0].source();
+        let (start, end) = source.group_span("reporter").unwrap();
+        assert_eq!(&text[start..end], "U.S.");
+        assert_eq!(source.group_span("page"), Some((26, 29)));
+        assert_eq!(source.group_span("missing"), None);
+
+        // Values are slices of the document, not copies
+        let volume = source.groups["volume"];
+        assert_eq!(volume.as_ptr(), text[17..].as_ptr());
+        let names: Vec<_> = source.groups.iter().map(|(name, _)| name).collect();
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn uniform_accessors() {
+        use crate::find::models::{CitationBase, CitationKind};
+
+        fn describe<'a>(citation: &impl CitationBase<'a>) -> (CitationKind, (usize, usize)) {
+            (citation.kind(), citation.span())
+        }
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1. Id. at 2.";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let described: Vec<_> = citations.iter().map(describe).collect();
+        assert_eq!(
+            described,
+            vec![
+                (CitationKind::FullCase, (4, 12)),
+                (CitationKind::Id, (14, 22)),
+            ]
+        );
+        assert_eq!(CitationBase::groups(&citations[0])["page"], "1");
+        assert!(CitationBase::metadata(&citations[1]).contains(&CitationMetadata::PinCite("2")));
+    }
+
+    #[test]
+    fn disabled_metadata() {
+        use super::MetadataPass;
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 115 (1973) (holding that it is). \
+            Roe, 410 U.S. at 117 (noting that it is).";
+        let options = FindOptions {
+            disabled_metadata: [
+                MetadataPass::CaseNames,
+                MetadataPass::Years,
+                MetadataPass::Parentheticals,
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let citations = get_citations_with_options(text, &options, &tokenizer);
+        match &citations[0] {
+            Citation::FullCase {
+                source,
+                pin_cite,
+                year,
+                court,
+                plaintiff,
+                defendant,
+                ..
+            } => {
+                assert_eq!((*plaintiff, *defendant, *year), (None, None, None));
+                assert_eq!((*pin_cite, *court), (Some("115"), Some("scotus")));
+                assert_eq!(source.year_span(), None);
+                assert_eq!(source.parenthetical(), None);
+            }
+            citation => panic!("expected a full case citation, got {citation:?}"),
+        }
+        let short = citations[1].source();
+        assert_eq!(short.pin_cite(), Some("117"));
+        assert_eq!(short.parenthetical(), None);
+        assert_eq!(short.parenthetical_span(), None);
+    }
+
+    #[test]
+    fn metadata_extractors() {
+        use super::{MetadataExtractor, MetadataPass};
+        use crate::helpers::Document;
+        use std::sync::Arc;
+
+        #[derive(Debug)]
+        struct AppealNumbers;
+
+        impl MetadataExtractor for AppealNumbers {
+            fn extract<'a>(&self, document: &Document<'_, 'a>, citations: &mut [Citation<'a>]) {
+                for citation in citations.iter_mut() {
+                    let (_, end) = citation.source().span();
+                    let after = document.text.get(end..).unwrap_or_default();
+                    if let Some(number) = after
+                        .strip_prefix(", No. ")
+                        .and_then(|rest| rest.split_whitespace().next())
+                    {
+                        let number = number.trim_end_matches('.');
+                        citation.source_mut().set_custom("appeal_number", number);
+                    }
+                }
+            }
+        }
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973), No. 70-18. Id. at 115.";
+        let options = FindOptions {
+            disabled_metadata: [MetadataPass::EditionChoices].into(),
+            metadata_extractors: vec![Arc::new(AppealNumbers)],
+            ..Default::default()
+        };
+
+        let citations = get_citations_with_options(text, &options, &tokenizer);
+        assert_eq!(citations[0].source().custom("appeal_number"), Some("70-18"));
+        assert_eq!(citations[1].source().custom("appeal_number"), None);
+        assert_eq!(citations[0].source().edition_choice(), None);
+        assert_eq!(citations[0].source().year(), Some("1973"));
+    }
+
+    #[test]
+    fn filter_by_kind() {
+        use crate::find::models::{CitationIterator, CitationKind};
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1. Id. at 2. Foo, supra, at 3. 4 U.S. 4.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations[1].kind(), CitationKind::Id);
+
+        let full: Vec<_> = citations
+            .iter()
+            .filter_kind(CitationKind::FullCase)
+            .collect();
+        assert_eq!(full.len(), 2);
+        assert_eq!(full[1].source().groups["volume"], "4");
+
+        let short_forms = citations
+            .into_iter()
+            .filter_kinds(&[CitationKind::Id, CitationKind::Supra])
+            .map(|citation| citation.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(short_forms, vec![CitationKind::Id, CitationKind::Supra]);
+    }
+
+    #[test]
+    fn compare_and_hash_citations() {
+        use std::collections::HashSet;
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1. Id. at 2.";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let copy = citations[0].clone();
+        assert_eq!(copy, citations[0]);
+        assert_ne!(copy, citations[1]);
+
+        let mut set: HashSet<_> = citations.iter().cloned().collect();
+        assert_eq!(set.len(), 2);
+        assert!(!set.insert(copy));
+        assert!(format!("{:?}", citations[1]).starts_with("Id"));
+    }
+
+    #[test]
+    fn ambiguous_reporters() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        // "W.2d" is a variation of both "Wis. 2d" and "Wash. 2d"
+        let text = "1 W.2d 1";
+
+        assert_eq!(get_citations(text, false, &tokenizer).len(), 1);
+        assert!(get_citations(text, true, &tokenizer).is_empty());
+
+        let marked = get_citations(text, Ambiguity::Mark, &tokenizer);
+        let metadata = &marked[0].source().metadata;
+        assert!(metadata.contains(&CitationMetadata::Ambiguous));
+        assert!(metadata.contains(&CitationMetadata::CandidateEdition("Wis. 2d")));
+        assert!(metadata.contains(&CitationMetadata::CandidateEdition("Wash. 2d")));
+
+        // but it can be narrowed down by year
+        assert_eq!(get_citations("1 W.2d 1 (1854)", true, &tokenizer).len(), 1);
+    }
+
+    #[test]
+    fn nominative_reporters() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+
+        let citations = get_citations("Marbury v. Madison, 1 Cranch 137 (1803)", false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().groups["reporter"], "Cranch");
+        assert_eq!(citations[0].source().nominative(), None);
+
+        let citations = get_citations("5 Pet. 1", false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().groups["page"], "1");
+
+        let text = "McCulloch v. Maryland, 17 U.S. (4 Wheat.) 316 (1819)";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        let source = citations[0].source();
+        assert_eq!(source.groups["volume"], "17");
+        assert_eq!(source.groups["reporter"], "U.S.");
+        assert_eq!(source.groups["page"], "316");
+        assert_eq!(source.nominative(), Some(("4", "Wheat.")));
+        assert_eq!(source.year(), Some("1819"));
+    }
+
+    #[test]
+    fn slip_opinions() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Dobbs, No. 19-1392, slip op. at 12 (U.S. June 24, 2022).";
+
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        match &citations[0] {
+            Citation::SlipOpinion {
+                source,
+                docket_number,
+                pin_cite,
+                court,
+                month,
+                day,
+                year,
+            } => {
+                assert_eq!(*docket_number, Some("19-1392"));
+                assert_eq!(*pin_cite, Some("12"));
+                assert_eq!(*court, Some("U.S."));
+                assert_eq!(
+                    (*month, *day, *year),
+                    (Some("June"), Some("24"), Some("2022"))
+                );
+                assert_eq!(source.span(), (11, 59));
+            }
+            _ => panic!("expected a slip opinion citation"),
+        }
+    }
+
+    #[test]
+    fn tax_and_administrative_reporters() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+
+        for (text, volume, reporter, page) in [
+            ("T.C. Memo. 2020-123", "2020", "T.C. Memo.", "123"),
+            ("10 B.T.A. 100", "10", "B.T.A.", "100"),
+            ("29 F.C.C.2d 500", "29", "F.C.C.2d", "500"),
+        ] {
+            let citations = get_citations(text, false, &tokenizer);
+            assert_eq!(citations.len(), 1, "{}", text);
+
+            let groups = &citations[0].source().groups;
+            assert_eq!(groups["volume"], volume);
+            assert_eq!(groups["reporter"], reporter);
+            assert_eq!(groups["page"], page);
+        }
+    }
+
+    #[test]
+    fn infra_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "As discussed infra note 45, and see infra Part III.B.";
+
+        let references: Vec<_> = get_citations(text, false, &tokenizer)
+            .iter()
+            .map(|citation| match citation {
+                Citation::Infra { source, reference } => {
+                    (*reference, &text[source.span().0..source.span().1])
+                }
+                _ => panic!("expected an infra citation"),
+            })
+            .collect();
+
+        assert_eq!(
+            references,
+            vec![
+                (Some("note 45"), "infra note 45"),
+                (Some("Part III.B"), "infra Part III.B"),
+            ]
+        );
+    }
+
+    #[test]
+    fn source_text() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1. Id. at 2.";
+
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 2);
+        let source = citations[1].source();
+        assert_eq!(source.matched_text(), "Id.");
+        assert_eq!(
+            &text[source.matched_span().0..source.matched_span().1],
+            "Id."
+        );
+        assert_eq!(source.full_text(text), Some("Id. at 2"));
+        assert_eq!(citations[0].source().full_text(text), Some("1 U.S. 1"));
+
+        // the span doesn't fit in some other text
+        assert_eq!(source.full_text("See 1 U.S. 1."), None);
+    }
+
+    #[test]
+    fn nested_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 115 (1973) (quoting Smith v. Jones, \
+            1 U.S. 1, 5 (1800)); Doe v. Poe, 2 U.S. 2 (1801).";
+
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+
+        let outer = citations[0].source().index;
+        let enclosing: Vec<_> = citations
+            .iter()
+            .map(|citation| citation.source().enclosing_citation())
+            .collect();
+        assert_eq!(enclosing, vec![None, Some(outer), None]);
+
+        match &citations[1] {
+            Citation::FullCase {
+                source,
+                pin_cite,
+                year,
+                ..
+            } => {
+                assert_eq!((*pin_cite, *year), (Some("5"), Some("1800")));
+                assert!(!source
+                    .metadata
+                    .iter()
+                    .any(|m| matches!(m, CitationMetadata::StringCite(_))));
+            }
+            _ => panic!("expected a full case citation"),
+        }
+        assert!(citations[2]
+            .source()
+            .metadata
+            .contains(&CitationMetadata::StringCite(outer)));
+    }
+
+    #[test]
+    fn quotations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (1800). The court said “the statute \
+            is clear.” See Foo, 1 U.S. at 5. It isn't. 'Nor is this.' Id. at 6. \
+            The court's view, stated at length in 'Quotes' and elsewhere, was different. \
+            Id. at 7.";
+
+        let quotations: Vec<_> = get_citations(text, false, &tokenizer)
+            .iter()
+            .map(|citation| {
+                citation
+                    .source()
+                    .quotation()
+                    .map(|(start, end)| &text[start..end])
+            })
+            .collect();
+        assert_eq!(
+            quotations,
+            vec![
+                None,
+                Some("the statute is clear."),
+                Some("Nor is this."),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_citations() {
+        let extractors = vec![TokenExtractor::try_new(
+            ResolvedRegex::of(r"(Exhibit (?P<exhibit>\d+))".into()),
+            TokenFactories::Custom("exhibit"),
+            false,
+            vec!["Exhibit".into()].into_iter().collect(),
+            Default::default(),
+        )
+        .unwrap()];
+        let tokenizer = Ahocorasick::new(&extractors).unwrap();
+
+        let citations = get_citations("As shown in Exhibit 4, the", false, &tokenizer);
+        match citations.as_slice() {
+            [Citation::Custom { kind, source }] => {
+                assert_eq!(*kind, "exhibit");
+                assert_eq!(source.groups["exhibit"], "4");
+                assert_eq!(source.span(), (12, 21));
+            }
+            _ => panic!("expected one custom citation, got {:?}", citations),
+        }
+    }
+
+    #[cfg(feature = "laws")]
+    #[test]
+    fn law_parentheticals() {
+        use crate::tokenizers::extractors::TokenExtractorExtra;
+
+        let text = "See 42 U.S.C. § 1983 (West Jan. 1, 2019) (civil action); \
+            20 U.S.C. § 1 (as amended 2003); 5 U.S.C. § 2 (repealed 1988); \
+            7 U.S.C. § 3 (Supp. V 2017); 8 U.S.C. § 4.";
+
+        // The bundled extractors don't match laws
+        let bundled = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        assert!(get_citations(text, false, &bundled).is_empty());
+
+        // A law extractor passed in along with them does
+        let law = TokenExtractor::try_new(
+            ResolvedRegex::of(r"((?P<volume>\d+) U\.S\.C\. § ?(?P<page>\d+))".into()),
+            TokenFactories::Citation,
+            false,
+            vec!["U.S.C.".into()].into_iter().collect(),
+            TokenExtractorExtra {
+                law: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let extractors: Vec<_> = EXTRACTORS.iter().cloned().chain([law]).collect();
+        let tokenizer = Ahocorasick::new(&extractors).unwrap();
+
+        let laws: Vec<_> = get_citations(text, false, &tokenizer)
+            .iter()
+            .map(|citation| match citation {
+                Citation::Law {
+                    publisher,
+                    day,
+                    month,
+                    year,
+                    status,
+                    ..
+                } => (*status, *publisher, *month, *day, *year),
+                _ => panic!("expected a law citation"),
+            })
+            .collect();
+        assert_eq!(
+            laws,
+            vec![
+                (None, Some("West"), Some("Jan."), Some("1"), Some("2019")),
+                (Some("as amended"), None, None, None, Some("2003")),
+                (Some("repealed"), None, None, None, Some("1988")),
+                (None, Some("Supp. V"), None, None, Some("2017")),
+                (None, None, None, None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn cross_references() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "As argued, see supra Part III, and infra pp. 30–32. See Section II.B. \
+            Smith, supra note 12, and the Section 1983 claim.";
+
+        let citations = get_citations(text, false, &tokenizer);
+        let references: Vec<_> = citations
+            .iter()
+            .map(|citation| match citation {
+                Citation::CrossReference { reference, .. } => ("cross", Some(*reference)),
+                Citation::Infra { reference, .. } => ("infra", *reference),
+                Citation::Supra {
+                    antecedent_guess, ..
+                } => ("supra", *antecedent_guess),
+                _ => ("other", None),
+            })
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                ("cross", Some("Part III")),
+                ("infra", Some("pp. 30–32")),
+                ("cross", Some("Section II.B")),
+                ("supra", Some("Smith")),
+            ]
+        );
+        assert!(citations[..3].iter().all(|citation| citation.is_internal()));
+        assert!(!citations[3].is_internal());
+    }
+
+    #[test]
+    fn record_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "The court found otherwise. R. at 45; J.A. 123–24. \
+            See ECF No. 27 at 3; Ex. 14 at 2; Dkt. No. 9.";
+        assert!(get_citations(text, false, &tokenizer).is_empty());
+
+        let options = FindOptions {
+            kinds: TokenKinds::default() | TokenKinds::RECORD,
+            ..Default::default()
+        };
+        let records: Vec<_> = get_citations_with_options(text, &options, &tokenizer)
+            .iter()
+            .map(|citation| match citation {
+                Citation::Record {
+                    source,
+                    document,
+                    page,
+                } => {
+                    assert_eq!(source.groups.get("document"), Some(*document));
+                    (*document, *page)
+                }
+                _ => panic!("expected a record citation"),
+            })
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("R.", Some("45")),
+                ("J.A.", Some("123–24")),
+                ("ECF No. 27", Some("3")),
+                ("Ex. 14", Some("2")),
+                ("Dkt. No. 9", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn string_cites() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo. See Smith v. Jones, 1 U.S. 1 (1800); Jones, 2 U.S. 2 (1801); \
+            see also Acme Inc. v. Doe, 3 U.S. 3 (1802). Cf. Roe, 4 U.S. 4 (1803).";
+
+        let citations = get_citations(text, false, &tokenizer);
+        let metadata: Vec<_> = citations
+            .iter()
+            .map(|citation| {
+                let source = citation.source();
+                let signal = source.metadata.iter().find_map(|m| match m {
+                    CitationMetadata::Signal(signal) => Some(*signal),
+                    _ => None,
+                });
+                let string_cite = source.metadata.iter().find_map(|m| match m {
+                    CitationMetadata::StringCite(index) => Some(*index),
+                    _ => None,
+                });
+                (signal, string_cite)
+            })
+            .collect();
+
+        let first = citations[0].source().index;
+        let last = citations[3].source().index;
+        assert_eq!(
+            metadata,
+            vec![
+                (Some("See"), Some(first)),
+                (Some("See"), Some(first)),
+                (Some("see also"), Some(first)),
+                (Some("Cf."), Some(last)),
+            ]
+        );
+    }
+
+    #[test]
+    fn sentence_start_pin_cites() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973). At 153, the Court held. \
+            Look at 5, which isn't a citation.";
+
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 2);
+        match &citations[1] {
+            Citation::Id {
+                source, pin_cite, ..
+            } => {
+                assert_eq!(*pin_cite, Some("153"));
+                assert_eq!(&text[source.span().0..source.span().1], "At 153");
+            }
+            _ => panic!("expected an id citation"),
+        }
+    }
+
+    #[test]
+    fn short_case_pages() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+
+        for (text, expected) in [
+            (
+                "Adarand, 515 U.S., at 241, 243.",
+                (Some("241"), Some("243")),
+            ),
+            ("Adarand, 515 U.S., at 241-42.", (Some("241"), None)),
+            ("Adarand, 515 U.S. at 241.", (Some("241"), None)),
+        ] {
+            let citations = get_citations(text, false, &tokenizer);
+            match &citations[..] {
+                [Citation::ShortCase {
+                    page,
+                    additional_pin_cite,
+                    ..
+                }] => assert_eq!((*page, *additional_pin_cite), expected, "{}", text),
+                _ => panic!("expected a short case citation in {}", text),
+            }
+        }
+    }
+
+    #[test]
+    fn scan_limits() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973)";
+        let defendant = |options: &FindOptions| match &get_citations_with_options(
+            text, options, &tokenizer,
+        )[..]
+        {
+            [Citation::FullCase {
+                defendant, year, ..
+            }] => (*defendant, *year),
+            _ => panic!("expected a full case citation"),
+        };
+
+        assert_eq!(
+            defendant(&FindOptions::default()),
+            (Some("Wade"), Some("1973"))
+        );
+
+        let narrow = FindOptions {
+            scan_limits: ScanLimits {
+                backward_seek: 1,
+                max_match_chars: 3,
+            },
+            ..Default::default()
+        };
+        assert_eq!(defendant(&narrow), (None, None));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let bytes = b"Pe\xf1a v. Smith, 1 U.S. 1 (1800); Roe v. Wade, 410 U.S. 113";
+
+        let citations = get_citations_from_bytes(bytes, &FindOptions::default(), &tokenizer);
+        let spans: Vec<_> = citations
+            .iter()
+            .map(|c| &bytes[c.span().0..c.span().1])
+            .collect();
+
+        assert_eq!(spans, vec![&b"1 U.S. 1"[..], &b"410 U.S. 113"[..]]);
+    }
+
+    #[test]
+    fn input_limits() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "1 U.S. 1, 2 U.S. 2, 3 U.S. 3";
+        let options = FindOptions::default();
+
+        let limits = InputLimits {
+            max_citations: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            try_get_citations(text, &options, &limits, &tokenizer)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let limits = InputLimits {
+            max_citations: Some(2),
+            ..Default::default()
+        };
+        assert!(matches!(
+            try_get_citations(text, &options, &limits, &tokenizer),
+            Err(EyeciteError::TooManyCitations { max: 2 })
+        ));
+
+        let limits = InputLimits {
+            max_input_len: Some(10),
+            ..Default::default()
+        };
+        assert!(matches!(
+            try_get_citations(text, &options, &limits, &tokenizer),
+            Err(EyeciteError::InputTooLong { len: 28, max: 10 })
+        ));
+
+        let limits = InputLimits {
+            deadline: Some(Instant::now()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            try_get_citations(text, &options, &limits, &tokenizer),
+            Err(EyeciteError::DeadlineExceeded)
+        ));
+    }
+
+    #[test]
+    fn reused_arena() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let options = FindOptions::default();
+        let mut arena = TokenArena::new();
+
+        for text in [
+            "Roe v. Wade, 410 U.S. 113 (1973)",
+            "See 1 U.S. 1. Id. at 2.",
+        ] {
+            let expected = get_citations_with_options(text, &options, &tokenizer);
+            let citations = get_citations_in(text, &options, &tokenizer, &mut arena);
+
+            let spans = |citations: &[Citation]| -> Vec<_> {
+                citations.iter().map(|c| c.source().span()).collect()
+            };
+            assert_eq!(spans(&citations), spans(&expected));
+        }
+    }
+
+    #[test]
+    fn token_kinds() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (1800). Id. at 2. Bar, supra, at 3.";
+        assert_eq!(get_citations(text, false, &tokenizer).len(), 3);
+
+        let options = FindOptions {
+            kinds: TokenKinds::ALL - TokenKinds::ID - TokenKinds::SUPRA,
+            ..Default::default()
+        };
+        let citations = get_citations_with_options(text, &options, &tokenizer);
+        assert!(matches!(citations.as_slice(), [Citation::FullCase { .. }]));
+    }
+
+    #[test]
+    fn citations_in_range() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text =
+            "See 1 U.S. 1 (1800).\n\nRoe v. Wade, 410 U.S. 113, 115 (1973).\n\nSee 2 U.S. 2.";
+        let start = text.find("Roe").unwrap();
+        let end = text.rfind("\n\n").unwrap();
+
+        let citations =
+            get_citations_in_range(text, start..end, &FindOptions::default(), &tokenizer);
+        let everywhere = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().span(), everywhere[1].source().span());
+        assert_eq!(
+            citations[0].source().matched_text(),
+            &text[citations[0].source().matched_span().0..citations[0].source().matched_span().1]
+        );
+        assert_eq!(
+            citations[0].source().pin_cite_span(),
+            everywhere[1].source().pin_cite_span()
+        );
+        assert_eq!(citations[0].source().plaintiff(), Some("Roe"));
+
+        // Ranges past the end of the text are clamped to it
+        let clamped = get_citations_in_range(
+            text,
+            end..text.len() + 10,
+            &FindOptions::default(),
+            &tokenizer,
+        );
+        assert_eq!(clamped.len(), 1);
+        let backwards = Range { start: 100, end: 1 };
+        assert!(
+            get_citations_in_range(text, backwards, &FindOptions::default(), &tokenizer).is_empty()
+        );
+
+        let options = FindOptions {
+            sections: vec![(0, start), (start, text.len())],
+            ..Default::default()
+        };
+        let citations = get_citations_in_range(text, start..end, &options, &tokenizer);
+        assert_eq!(citations[0].source().document_section(), Some(1));
+    }
+
+    #[test]
+    fn normalized_ids() {
+        let extractors = filtered()
+            .with_id_variants(regexes::ID_VARIANT_REGEXES)
+            .try_build()
+            .unwrap();
+        let tokenizer = Ahocorasick::new(&extractors).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (1800). Id; see id at 5. IBID.";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let ids: Vec<_> = citations
+            .iter()
+            .map(|c| c.source().normalized_id())
+            .collect();
+        assert_eq!(ids, vec![None, Some("Id."), Some("Id."), Some("Ibid.")]);
+        assert_eq!(citations[2].source().pin_cite(), Some("at 5"));
+    }
+
+    #[test]
+    fn multi_word_signals() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo. But see Smith v. Jones, 1 U.S. 1 (1800), rev'd on other grounds, \
+            2 U.S. 2 (1801); see also Doe v. Roe, 3 U.S. 3 (1802), cert. denied, 4 U.S. 4 \
+            (1803).";
+
+        let (words, _) = tokenizer.tokenize(text);
+        let stop_words: Vec<_> = words
+            .iter()
+            .filter_map(|token| match token {
+                Token::StopWord(data) => Some(data.data),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            stop_words,
+            vec![
+                "But see",
+                "v.",
+                "rev'd on other grounds,",
+                "see also",
+                "v.",
+                "cert. denied,"
+            ]
+        );
+
+        let citations = get_citations(text, false, &tokenizer);
+        let signals: Vec<_> = citations.iter().map(|c| c.source().signal()).collect();
+        assert_eq!(
+            signals,
+            vec![
+                Some("But see"),
+                Some("rev'd on other grounds"),
+                Some("see also"),
+                Some("cert. denied"),
+            ]
+        );
+        assert_eq!(citations[2].source().plaintiff(), Some("Doe"));
+    }
+}
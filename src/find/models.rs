@@ -1,5 +1,8 @@
-use crate::tokenizers::models::Token;
-use std::collections::{HashMap, HashSet};
+use crate::helpers;
+use crate::tokenizers::extractors::{Editions, ReporterEdition, TokenExtractorExtra};
+use crate::tokenizers::models::{Groups, Token};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 pub enum CitationMetadata<'a> {
@@ -12,28 +15,586 @@ pub enum CitationMetadata<'a> {
     Extra(&'a str),
     AntecedentGuess(&'a str),
     Volume(&'a str),
+    DocketNumber(&'a str),
+    /// Part of the document an infra citation or cross reference points to,
+    /// like "note 45".
+    Reference(&'a str),
+    /// Number of the footnote a supra citation points to, like "45" in
+    /// "supra note 45".
+    Note(&'a str),
+    /// Publisher of the code a law is cited from, like "West".
+    Publisher(&'a str),
+    /// Status of a law, like "as amended" or "repealed".
+    Status(&'a str),
+    /// Introductory signal, like "See also", that applies to the citation.
+    Signal(&'a str),
+    /// The citation is part of the string cite starting with the citation
+    /// at this token index.
+    StringCite(usize),
+    /// The citation is inside a parenthetical of the citation at this token
+    /// index, like "(quoting Smith v. Jones, 1 U.S. 1 (1800))".
+    EnclosingCitation(usize),
+    /// Start and stop offsets of the quoted text the citation is given for,
+    /// like "the statute is clear." in "'the statute is clear.' Smith,
+    /// 1 U.S. at 5", without the quotation marks.
+    Quotation(usize, usize),
+    /// The citation is inside quotation marks, like one quoted from another
+    /// opinion, rather than made by the document itself.
+    Quoted,
+    /// Start and stop offsets of the pin cite, so the page can be linked
+    /// separately from the citation.
+    PinCiteSpan(usize, usize),
+    /// Start and stop offsets of the year.
+    YearSpan(usize, usize),
+    /// Start and stop offsets of the parenthetical, without the parentheses.
+    ParentheticalSpan(usize, usize),
+    /// Short name given to a full citation for later references.
+    Hereinafter(&'a str),
+    Month(&'a str),
+    Day(&'a str),
+    /// The citation might refer to more than one edition, and couldn't be
+    /// narrowed down by date.
+    Ambiguous,
+    /// The name of an edition an ambiguous citation might refer to.
+    CandidateEdition(&'a str),
+    /// How the edition the citation refers to was chosen among its
+    /// candidates, or why it couldn't be.
+    EditionChoice(EditionChoice),
+    /// The reporter string of the citation was one character off from this
+    /// one, and was matched by a
+    /// [`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer).
+    FuzzyReporter(&'a str),
+    /// How the cited case was decided, or what the cited opinion is, like
+    /// "per curiam", "en banc" or "plurality opinion".
+    Disposition(&'a str),
+    /// Judge who wrote the separate opinion cited, like "Scalia" in
+    /// "(Scalia, J., dissenting)".
+    Judge(&'a str),
+    /// Kind of separate opinion cited, like "dissenting" in
+    /// "(Scalia, J., dissenting)".
+    OpinionType(&'a str),
+    /// Index of the section of the document the citation is in. See
+    /// [`FindOptions::sections`](crate::find::FindOptions::sections).
+    DocumentSection(usize),
+    /// Title of the judge who wrote the separate opinion cited.
+    JudgeTitle(JudgeTitle),
+    /// Kind of separate opinion cited, parsed from
+    /// [`OpinionType`](Self::OpinionType).
+    OpinionRole(OpinionRole),
+    /// Application-specific metadata, as a key and a value. See
+    /// [`CitationSource::set_custom`].
+    Custom(&'a str, String),
 }
 
+/// Title of a judge, as abbreviated after their name in a parenthetical.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum JudgeTitle {
+    /// "J."
+    Justice,
+    /// "C.J." or "C. J."
+    ChiefJustice,
+    /// "JJ.", for an opinion written by more than one judge.
+    Justices,
+}
+
+impl JudgeTitle {
+    /// Parse a title like "J." or "C. J.".
+    pub fn parse(title: &str) -> Option<Self> {
+        match title.replace(' ', "").as_str() {
+            "J." => Some(JudgeTitle::Justice),
+            "C.J." => Some(JudgeTitle::ChiefJustice),
+            "JJ." => Some(JudgeTitle::Justices),
+            _ => None,
+        }
+    }
+
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            JudgeTitle::Justice => "J.",
+            JudgeTitle::ChiefJustice => "C.J.",
+            JudgeTitle::Justices => "JJ.",
+        }
+    }
+}
+
+/// Kind of separate opinion, like the "concurring in the judgment" in
+/// "(Breyer, J., concurring in the judgment)".
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum OpinionRole {
+    Concurring,
+    ConcurringInPart,
+    ConcurringInJudgment,
+    Dissenting,
+    DissentingInPart,
+    /// "concurring in part and dissenting in part"
+    ConcurringAndDissenting,
+}
+
+impl OpinionRole {
+    /// Parse the kind of opinion, like "dissenting" or "concurring in part
+    /// and dissenting in part". Opinions that neither concur nor dissent
+    /// aren't separate opinions.
+    pub fn parse(opinion_type: &str) -> Option<Self> {
+        let concurring = opinion_type.contains("concurring");
+        let dissenting = opinion_type.contains("dissenting");
+        let in_part = opinion_type.contains("in part");
+
+        Some(match (concurring, dissenting) {
+            (true, true) => OpinionRole::ConcurringAndDissenting,
+            (true, false) if opinion_type.contains("judgment") => OpinionRole::ConcurringInJudgment,
+            (true, false) if in_part => OpinionRole::ConcurringInPart,
+            (true, false) => OpinionRole::Concurring,
+            (false, true) if in_part => OpinionRole::DissentingInPart,
+            (false, true) => OpinionRole::Dissenting,
+            (false, false) => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpinionRole::Concurring => "concurring",
+            OpinionRole::ConcurringInPart => "concurring_in_part",
+            OpinionRole::ConcurringInJudgment => "concurring_in_judgment",
+            OpinionRole::Dissenting => "dissenting",
+            OpinionRole::DissentingInPart => "dissenting_in_part",
+            OpinionRole::ConcurringAndDissenting => "concurring_and_dissenting",
+        }
+    }
+}
+
+/// How the edition of a citation to a reporter was chosen, so
+/// disambiguation can be audited. See [`CitationSource::edition_choice`].
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum EditionChoice {
+    /// The reporter string is the abbreviation of exactly one edition, like
+    /// "U.S.".
+    ExactMatch,
+    /// The reporter string is a variation of exactly one edition, like
+    /// "U. S.".
+    Unambiguous,
+    /// The reporter string might refer to more than one edition, but only
+    /// one was published in the citation's year.
+    DateDisambiguated,
+    /// The reporter string might refer to more than one edition, and the
+    /// year, if any, doesn't narrow them down to one.
+    Ambiguous,
+}
+
+impl EditionChoice {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EditionChoice::ExactMatch => "exact_match",
+            EditionChoice::Unambiguous => "unambiguous",
+            EditionChoice::DateDisambiguated => "date_disambiguated",
+            EditionChoice::Ambiguous => "ambiguous",
+        }
+    }
+}
+
+/// Who wrote the separate opinion a citation is to, and what kind of
+/// opinion it is, like "(Breyer, J., concurring in the judgment)". See
+/// [`CitationSource::attribution`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Attribution<'a> {
+    pub surname: &'a str,
+    pub title: Option<JudgeTitle>,
+    pub role: OpinionRole,
+}
+
+/// Generate accessors for metadata holding a single string, like
+/// [`CitationSource::year`].
+macro_rules! metadata_accessors {
+    ($($(#[$doc:meta])* $name:ident => $variant:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(&self) -> Option<&'a str> {
+                self.metadata.iter().find_map(|m| match m {
+                    CitationMetadata::$variant(value) => Some(*value),
+                    _ => None,
+                })
+            }
+        )*
+    };
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CitationSource<'a> {
     pub token: Token<'a>,
     pub index: usize,
     pub span_start: Option<usize>,
     pub span_end: Option<usize>,
-    pub groups: HashMap<String, String>,
+    pub groups: Groups<'a>,
     pub metadata: HashSet<CitationMetadata<'a>>,
 }
 
+impl<'a> CitationSource<'a> {
+    pub fn new(token: Token<'a>, index: usize) -> Self {
+        let groups = token
+            .try_data()
+            .map(|data| data.groups.clone())
+            .unwrap_or_default();
+
+        Self {
+            token,
+            index,
+            span_start: None,
+            span_end: None,
+            groups,
+            metadata: HashSet::new(),
+        }
+    }
+
+    /// Record a piece of metadata, if it was found.
+    pub fn add_metadata(
+        &mut self,
+        metadata: fn(&'a str) -> CitationMetadata<'a>,
+        value: Option<&'a str>,
+    ) {
+        if let Some(value) = value {
+            self.metadata.insert(metadata(value));
+        }
+    }
+
+    /// Record where a piece of metadata is in the document, if it was found.
+    pub fn add_span(
+        &mut self,
+        metadata: fn(usize, usize) -> CitationMetadata<'a>,
+        span: Option<(usize, usize)>,
+    ) {
+        if let Some((start, end)) = span {
+            self.metadata.insert(metadata(start, end));
+        }
+    }
+
+    /// Start and stop offsets in source text for the matched text.
+    pub fn span(&self) -> (usize, usize) {
+        let data = self.token.try_data();
+
+        (
+            self.span_start
+                .or_else(|| data.map(|d| d.start))
+                .unwrap_or_default(),
+            self.span_end
+                .or_else(|| data.map(|d| d.end))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Start and stop offsets of just the text matched by the citation's
+    /// token, like "410 U.S. 113", without the pin cite or parentheticals
+    /// that [`span`](Self::span) takes in.
+    pub fn matched_span(&self) -> (usize, usize) {
+        self.token
+            .try_data()
+            .map(|data| (data.start, data.end))
+            .unwrap_or_else(|| self.span())
+    }
+
+    /// The text matched by the citation's token, like "410 U.S. 113".
+    pub fn matched_text(&self) -> &'a str {
+        match &self.token {
+            Token::Word(word) => word,
+            Token::Space => " ",
+            token => token.try_data().map_or("", |data| data.data),
+        }
+    }
+
+    /// The text of the whole citation, including the pin cite and
+    /// parentheticals, sliced from the text the citation was found in.
+    /// Returns `None` if `text` isn't that text and the span doesn't fit.
+    pub fn full_text<'t>(&self, text: &'t str) -> Option<&'t str> {
+        let (start, end) = self.span();
+        text.get(start..end)
+    }
+
+    /// Extractor data for citation tokens, like the editions that might
+    /// match the reporter string.
+    pub fn extra(&self) -> Option<&'a TokenExtractorExtra> {
+        match &self.token {
+            Token::Citation(data) => Some(data.extra),
+            _ => None,
+        }
+    }
+
+    /// The editions this citation might refer to, merged across the tokens
+    /// combined into its token, for citations to a reporter.
+    pub fn editions(&self) -> Option<&Editions<'a>> {
+        match &self.token {
+            Token::Citation(data) => Some(data.editions()),
+            _ => None,
+        }
+    }
+
+    /// The edition this citation refers to, if it can be narrowed down to
+    /// exactly one.
+    pub fn edition_guess(&self) -> Option<&'a ReporterEdition> {
+        let year = self.parsed_year().map(i32::from);
+        self.editions()
+            .and_then(|editions| helpers::guess_edition(editions, year))
+    }
+
+    /// Start and stop offsets of the value of a group in the document, if
+    /// it was matched in the citation's token.
+    pub fn group_span(&self, name: &str) -> Option<(usize, usize)> {
+        let data = self.token.try_data()?;
+        let (start, end) = helpers::subslice_span(data.data, self.groups.get(name)?)?;
+        Some((data.start + start, data.start + end))
+    }
+
+    /// The volume and reporter of the nominative reporter cited alongside the
+    /// official one, like ("4", "Wheat.") for "17 U.S. (4 Wheat.) 316".
+    pub fn nominative(&self) -> Option<(&str, &str)> {
+        Some((
+            self.groups.get("volume_nominative")?,
+            self.groups.get("reporter_nominative")?,
+        ))
+    }
+
+    /// The canonical form of an id citation, "Id." or "Ibid.", however its
+    /// token was written, like "id;" or "IBID". Returns `None` for other
+    /// citations.
+    pub fn normalized_id(&self) -> Option<&'static str> {
+        let Token::Id(data) = &self.token else {
+            return None;
+        };
+        let ibid = data
+            .data
+            .get(..4)
+            .is_some_and(|start| start.eq_ignore_ascii_case("ibid"));
+        Some(if ibid { "Ibid." } else { "Id." })
+    }
+
+    /// Whether the volume or page of the citation is a blank, like in
+    /// "___ U.S. ___", so it can't be told apart from other citations
+    /// without one of its parallel citations.
+    pub fn has_placeholder(&self) -> bool {
+        ["volume", "page"]
+            .into_iter()
+            .any(|name| self.groups.get(name).is_some_and(helpers::is_placeholder))
+    }
+
+    metadata_accessors! {
+        /// The parenthetical after the citation, if any.
+        parenthetical => Parenthetical;
+        /// The pin cite found in the citation's metadata, if any.
+        pin_cite => PinCite;
+        /// The year found in the citation's metadata, if any.
+        year => Year;
+        /// The court found in the citation's metadata, if any.
+        court => Court;
+        /// The plaintiff's name before a full case citation, if any.
+        plaintiff => Plaintiff;
+        /// The defendant's name before a full case citation, if any.
+        defendant => Defendant;
+        /// Text between the citation and its parenthetical, if any.
+        extra_text => Extra;
+        /// The word before a short form citation that might name the case it
+        /// refers to, if any.
+        antecedent_guess => AntecedentGuess;
+        /// The docket number of a slip opinion, if any.
+        docket_number => DocketNumber;
+        /// The part of the document an infra citation or cross reference
+        /// points to, if any.
+        reference => Reference;
+        /// The number of the footnote a supra citation points to, if any.
+        note => Note;
+        /// The publisher of the code a law is cited from, if any.
+        publisher => Publisher;
+        /// Whether the cited law changed since, like "repealed", if known.
+        status => Status;
+        /// The introductory signal, like "See also", that applies to the
+        /// citation, if any.
+        signal => Signal;
+        /// The short name given to the citation by "[hereinafter ...]", if
+        /// any.
+        hereinafter => Hereinafter;
+        /// The month of a dated citation, if any.
+        month => Month;
+        /// The day of a dated citation, if any.
+        day => Day;
+        /// The reporter string a fuzzy matched citation was matched to, if
+        /// the citation's own was misspelled.
+        fuzzy_reporter => FuzzyReporter;
+        /// How the cited case was decided, like "per curiam" or "en banc",
+        /// if its parenthetical says.
+        disposition => Disposition;
+        /// The judge who wrote the separate opinion cited, if any.
+        judge => Judge;
+        /// The kind of separate opinion cited, like "dissenting" or
+        /// "concurring in part", if any.
+        opinion_type => OpinionType;
+    }
+
+    /// The judge and kind of the separate opinion cited, if the citation is
+    /// to one, so citations can be attributed to specific opinions.
+    pub fn attribution(&self) -> Option<Attribution<'a>> {
+        let mut title = None;
+        let mut role = None;
+        for m in &self.metadata {
+            match m {
+                CitationMetadata::JudgeTitle(t) => title = Some(*t),
+                CitationMetadata::OpinionRole(r) => role = Some(*r),
+                _ => {}
+            }
+        }
+
+        Some(Attribution {
+            surname: self.judge()?,
+            title,
+            role: role?,
+        })
+    }
+
+    /// The year found in the citation's metadata, parsed and checked to be
+    /// plausible. See [`helpers::parse_year`].
+    pub fn parsed_year(&self) -> Option<u16> {
+        self.year().and_then(helpers::parse_year)
+    }
+
+    /// Application-specific metadata recorded with
+    /// [`set_custom`](Self::set_custom), if any.
+    pub fn custom(&self, key: &str) -> Option<&str> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::Custom(k, value) if *k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Record application-specific metadata, replacing any recorded before
+    /// under the same key.
+    pub fn set_custom(&mut self, key: &'a str, value: impl Into<String>) {
+        self.metadata
+            .retain(|m| !matches!(m, CitationMetadata::Custom(k, _) if *k == key));
+        self.metadata
+            .insert(CitationMetadata::Custom(key, value.into()));
+    }
+
+    /// Token index of the citation whose parenthetical this citation is
+    /// nested in, if any.
+    pub fn enclosing_citation(&self) -> Option<usize> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::EnclosingCitation(index) => Some(*index),
+            _ => None,
+        })
+    }
+
+    /// Start and stop offsets of the quoted text attributed to the citation,
+    /// if any.
+    pub fn quotation(&self) -> Option<(usize, usize)> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::Quotation(start, end) => Some((*start, *end)),
+            _ => None,
+        })
+    }
+
+    /// How the edition the citation refers to was chosen, for citations to
+    /// a reporter.
+    pub fn edition_choice(&self) -> Option<EditionChoice> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::EditionChoice(choice) => Some(*choice),
+            _ => None,
+        })
+    }
+
+    /// Whether the citation is inside quotation marks, like one quoted from
+    /// another opinion.
+    pub fn is_quoted(&self) -> bool {
+        self.metadata.contains(&CitationMetadata::Quoted)
+    }
+
+    /// Index of the section of the document the citation is in, if sections
+    /// were given. See [`FindOptions::sections`](crate::find::FindOptions::sections).
+    pub fn document_section(&self) -> Option<usize> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::DocumentSection(section) => Some(*section),
+            _ => None,
+        })
+    }
+
+    /// Start and stop offsets of the pin cite, if any.
+    pub fn pin_cite_span(&self) -> Option<(usize, usize)> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::PinCiteSpan(start, end) => Some((*start, *end)),
+            _ => None,
+        })
+    }
+
+    /// Start and stop offsets of the year, if any.
+    pub fn year_span(&self) -> Option<(usize, usize)> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::YearSpan(start, end) => Some((*start, *end)),
+            _ => None,
+        })
+    }
+
+    /// Start and stop offsets of the parenthetical, if any.
+    pub fn parenthetical_span(&self) -> Option<(usize, usize)> {
+        self.metadata.iter().find_map(|m| match m {
+            CitationMetadata::ParentheticalSpan(start, end) => Some((*start, *end)),
+            _ => None,
+        })
+    }
+
+    /// Move the citation's spans, including those of its metadata, by
+    /// `delta` bytes, like when it was found in a window of a larger text,
+    /// or text before it was edited. The token index is left as is.
+    pub fn shift(&mut self, delta: isize) {
+        let shift = |offset: usize| offset.saturating_add_signed(delta);
+
+        if let Some(data) = self.token.try_data_mut() {
+            data.start = shift(data.start);
+            data.end = shift(data.end);
+        }
+        self.span_start = self.span_start.map(shift);
+        self.span_end = self.span_end.map(shift);
+        self.metadata = self
+            .metadata
+            .drain()
+            .map(|m| match m {
+                CitationMetadata::Quotation(start, end) => {
+                    CitationMetadata::Quotation(shift(start), shift(end))
+                }
+                CitationMetadata::PinCiteSpan(start, end) => {
+                    CitationMetadata::PinCiteSpan(shift(start), shift(end))
+                }
+                CitationMetadata::YearSpan(start, end) => {
+                    CitationMetadata::YearSpan(shift(start), shift(end))
+                }
+                CitationMetadata::ParentheticalSpan(start, end) => {
+                    CitationMetadata::ParentheticalSpan(shift(start), shift(end))
+                }
+                m => m,
+            })
+            .collect();
+    }
+
+    /// Official reporter string from the edition guess, if possible.
+    pub fn corrected_reporter(&self) -> Option<&str> {
+        self.edition_guess()
+            .map(|edition| edition.short_name.as_str())
+            .or_else(|| self.groups.get("reporter"))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Citation<'a> {
     Resource {
         source: CitationSource<'a>,
         pin_cite: Option<&'a str>,
         year: Option<&'a str>,
     },
+    /// Citation of a statute or regulation, with the publisher, date and
+    /// status in the parenthetical after it. Only found by extractors
+    /// passed in with [`law`](crate::tokenizers::extractors::TokenExtractorExtra::law)
+    /// set, since the bundled extractors don't match laws.
     Law {
         source: CitationSource<'a>,
         publisher: Option<&'a str>,
         day: Option<&'a str>,
         month: Option<&'a str>,
+        year: Option<&'a str>,
+        /// Whether the law changed since, like "as amended" or "repealed".
+        status: Option<&'a str>,
     },
     Journal {
         source: CitationSource<'a>,
@@ -74,10 +635,15 @@ pub enum Citation<'a> {
     Adarand, 515 U.S. at 241
     515 U.S., at 241
     ```
+
+    In "515 U.S., at 241, 243" the page after "at" is `page`, "243" is the
+    `additional_pin_cite`, and the `pin_cite` is "241, 243".
     **/
     ShortCase {
         source: CitationSource<'a>,
         pin_cite: Option<&'a str>,
+        page: Option<&'a str>,
+        additional_pin_cite: Option<&'a str>,
         year: Option<&'a str>,
         court: Option<&'a str>,
         antecedent_guess: Option<&'a str>,
@@ -106,6 +672,55 @@ pub enum Citation<'a> {
         volume: Option<&'a str>,
     },
     /**
+    Convenience class which represents an 'infra' citation, i.e., a
+    reference forward to something that is below in the document, common in
+    law review articles.
+
+    Examples:
+    ```text
+    infra note 45
+    infra Part III.B
+    ```
+     **/
+    Infra {
+        source: CitationSource<'a>,
+        reference: Option<&'a str>,
+    },
+    /**
+    A reference to another part of the same document rather than to an
+    outside authority, common in briefs.
+
+    Examples:
+    ```text
+    see supra Part III
+    supra p. 14
+    see Section II.B
+    ```
+     **/
+    CrossReference {
+        source: CitationSource<'a>,
+        reference: &'a str,
+    },
+    /**
+    A citation to the record of a case, common in briefs. Only found when
+    [`TokenKinds::RECORD`](crate::tokenizers::models::TokenKinds::RECORD)
+    is extracted. The `document` and `page` groups of its source hold the
+    same values.
+
+    Examples:
+    ```text
+    R. at 45
+    J.A. 123
+    ECF No. 27 at 3
+    Ex. 14 at 2
+    ```
+     **/
+    Record {
+        source: CitationSource<'a>,
+        document: &'a str,
+        page: Option<&'a str>,
+    },
+    /**
     Convenience class which represents an 'id' or 'ibid' citation, i.e., a
     citation to the document referenced immediately prior. An 'id' citation is
     unlike a regular citation object since it has no knowledge of its reporter,
@@ -123,6 +738,25 @@ pub enum Citation<'a> {
         volume: Option<&'a str>,
     },
     /**
+    Convenience class which represents a slip opinion citation, i.e., a
+    citation by docket number to an opinion that hasn't been published in a
+    reporter yet.
+
+    Example:
+    ```text
+    No. 19-1392, slip op. at 12 (U.S. June 24, 2022)
+    ```
+    **/
+    SlipOpinion {
+        source: CitationSource<'a>,
+        docket_number: Option<&'a str>,
+        pin_cite: Option<&'a str>,
+        court: Option<&'a str>,
+        month: Option<&'a str>,
+        day: Option<&'a str>,
+        year: Option<&'a str>,
+    },
+    /**
     Convenience class which represents an unknown citation. A recognized
     citation should theoretically be parsed as a CaseCitation, FullLawCitation,
     or a FullJournalCitation. If it's something else, this class serves as
@@ -131,4 +765,238 @@ pub enum Citation<'a> {
     Unknown {
         source: CitationSource<'a>,
     },
+    /// Citation of a kind defined outside of eyecite, found by a custom
+    /// extractor using [`TokenFactories::Custom`](crate::tokenizers::models::TokenFactories::Custom).
+    Custom {
+        kind: &'static str,
+        source: CitationSource<'a>,
+    },
+}
+
+impl<'a> Citation<'a> {
+    /// The kind of citation this is, for filtering without matching on
+    /// every variant. See [`CitationIterator::filter_kind`].
+    pub fn kind(&self) -> CitationKind {
+        match self {
+            Citation::Resource { .. } => CitationKind::Resource,
+            Citation::Law { .. } => CitationKind::Law,
+            Citation::Journal { .. } => CitationKind::Journal,
+            Citation::Case { .. } => CitationKind::Case,
+            Citation::FullCase { .. } => CitationKind::FullCase,
+            Citation::ShortCase { .. } => CitationKind::ShortCase,
+            Citation::Supra { .. } => CitationKind::Supra,
+            Citation::Infra { .. } => CitationKind::Infra,
+            Citation::CrossReference { .. } => CitationKind::CrossReference,
+            Citation::Record { .. } => CitationKind::Record,
+            Citation::Id { .. } => CitationKind::Id,
+            Citation::SlipOpinion { .. } => CitationKind::SlipOpinion,
+            Citation::Unknown { .. } => CitationKind::Unknown,
+            Citation::Custom { .. } => CitationKind::Custom,
+        }
+    }
+
+    /// Whether this is a full citation, which the short form citations
+    /// after it may refer back to.
+    pub fn is_full(&self) -> bool {
+        matches!(
+            self,
+            Citation::FullCase { .. }
+                | Citation::Law { .. }
+                | Citation::Journal { .. }
+                | Citation::SlipOpinion { .. }
+        )
+    }
+
+    /// Whether this citation points to another part of the same document,
+    /// like infra citations and cross references, rather than to an outside
+    /// authority.
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            Citation::Infra { .. } | Citation::CrossReference { .. }
+        )
+    }
+
+    pub fn source(&self) -> &CitationSource<'a> {
+        match self {
+            Citation::Resource { source, .. }
+            | Citation::Law { source, .. }
+            | Citation::Journal { source, .. }
+            | Citation::Case { source, .. }
+            | Citation::FullCase { source, .. }
+            | Citation::ShortCase { source, .. }
+            | Citation::Supra { source, .. }
+            | Citation::Infra { source, .. }
+            | Citation::CrossReference { source, .. }
+            | Citation::Record { source, .. }
+            | Citation::Id { source, .. }
+            | Citation::SlipOpinion { source, .. }
+            | Citation::Unknown { source }
+            | Citation::Custom { source, .. } => source,
+        }
+    }
+
+    pub fn source_mut(&mut self) -> &mut CitationSource<'a> {
+        match self {
+            Citation::Resource { source, .. }
+            | Citation::Law { source, .. }
+            | Citation::Journal { source, .. }
+            | Citation::Case { source, .. }
+            | Citation::FullCase { source, .. }
+            | Citation::ShortCase { source, .. }
+            | Citation::Supra { source, .. }
+            | Citation::Infra { source, .. }
+            | Citation::CrossReference { source, .. }
+            | Citation::Record { source, .. }
+            | Citation::Id { source, .. }
+            | Citation::SlipOpinion { source, .. }
+            | Citation::Unknown { source }
+            | Citation::Custom { source, .. } => source,
+        }
+    }
+}
+
+/// Hashes the canonical key of the citation: where it was found and the
+/// groups matched by its regex. Metadata is left out, since it may not be
+/// hashable in a stable order.
+impl Hash for CitationSource<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.span().hash(state);
+        self.groups.hash(state);
+    }
+}
+
+impl Hash for Citation<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.kind().hash(state);
+        self.source().hash(state);
+    }
+}
+
+/// The kind of a [`Citation`], one for each variant.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum CitationKind {
+    Resource,
+    Law,
+    Journal,
+    Case,
+    FullCase,
+    ShortCase,
+    Supra,
+    Infra,
+    CrossReference,
+    Record,
+    Id,
+    SlipOpinion,
+    Unknown,
+    Custom,
+}
+
+/// Uniform access to what every citation has, so generic code like
+/// serializers, annotators and filters doesn't need to match on every
+/// variant of [`Citation`].
+pub trait CitationBase<'a> {
+    fn source(&self) -> &CitationSource<'a>;
+
+    fn kind(&self) -> CitationKind;
+
+    /// Start and stop offsets of the citation.
+    fn span(&self) -> (usize, usize) {
+        self.source().span()
+    }
+
+    /// Named groups matched by the citation's regex.
+    fn groups<'s>(&'s self) -> &'s Groups<'a>
+    where
+        'a: 's,
+    {
+        &self.source().groups
+    }
+
+    fn metadata<'s>(&'s self) -> &'s HashSet<CitationMetadata<'a>>
+    where
+        'a: 's,
+    {
+        &self.source().metadata
+    }
+}
+
+impl<'a> CitationBase<'a> for Citation<'a> {
+    fn source(&self) -> &CitationSource<'a> {
+        Citation::source(self)
+    }
+
+    fn kind(&self) -> CitationKind {
+        Citation::kind(self)
+    }
+}
+
+impl<'a, T: CitationBase<'a>> CitationBase<'a> for &T {
+    fn source(&self) -> &CitationSource<'a> {
+        T::source(self)
+    }
+
+    fn kind(&self) -> CitationKind {
+        T::kind(self)
+    }
+}
+
+/// Filter iterators of citations, or of references to them, by kind:
+/// `citations.iter().filter_kind(CitationKind::FullCase)`.
+pub trait CitationIterator<'a>: Iterator + Sized
+where
+    Self::Item: CitationBase<'a>,
+{
+    /// Keep the citations of one kind.
+    fn filter_kind(self, kind: CitationKind) -> impl Iterator<Item = Self::Item> {
+        self.filter(move |citation| citation.kind() == kind)
+    }
+
+    /// Keep the citations of any of the given kinds.
+    fn filter_kinds<'k>(self, kinds: &'k [CitationKind]) -> impl Iterator<Item = Self::Item> + 'k
+    where
+        Self: 'k,
+    {
+        self.filter(move |citation| kinds.contains(&citation.kind()))
+    }
+}
+
+impl<'a, I> CitationIterator<'a> for I
+where
+    I: Iterator,
+    I::Item: CitationBase<'a>,
+{
+}
+
+/// A citation found in a run of valid UTF-8 within a larger byte buffer.
+/// See [`get_citations_from_bytes`](crate::find::get_citations_from_bytes).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ByteCitation<'a> {
+    /// Offset of the run of text the citation was found in.
+    pub offset: usize,
+    pub citation: Citation<'a>,
+}
+
+impl<'a> ByteCitation<'a> {
+    /// Start and stop offsets of the citation in the original bytes.
+    pub fn span(&self) -> (usize, usize) {
+        let (start, end) = self.citation.source().span();
+        (self.offset + start, self.offset + end)
+    }
+}
+
+impl<'a> CitationBase<'a> for ByteCitation<'a> {
+    fn source(&self) -> &CitationSource<'a> {
+        self.citation.source()
+    }
+
+    fn kind(&self) -> CitationKind {
+        self.citation.kind()
+    }
+
+    /// Start and stop offsets of the citation in the original bytes.
+    fn span(&self) -> (usize, usize) {
+        ByteCitation::span(self)
+    }
 }
@@ -0,0 +1,208 @@
+/*!
+Associate citations found in footnotes with the place in the body text that
+refers to the footnote.
+
+Opinions often keep their footnote structure, either as bracketed markers,
+like "[fn12]", that appear once in the body and again at the start of the
+footnote, or as XML footnotes. A citation in a footnote is then far from the
+text it supports, so annotation and context extraction should point at the
+footnote's marker in the body instead.
+ */
+
+use crate::find::models::Citation;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref BRACKETED_REGEX: Regex =
+        Regex::new(r"\[fn(\w+)\]").expect("footnote regexes should compile");
+    static ref XML_MARKER_REGEX: Regex =
+        Regex::new(r"<footnotemark>(\w+)</footnotemark>").expect("footnote regexes should compile");
+    static ref XML_FOOTNOTE_REGEX: Regex =
+        Regex::new(r#"(?s)<footnote\b[^>]*\blabel="(\w+)"[^>]*>.*?</footnote>"#)
+            .expect("footnote regexes should compile");
+}
+
+/// How footnotes are marked up in a document.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FootnoteStyle {
+    /// "[fn12]" marks the footnote in the body, and again at the start of
+    /// the footnote itself. Footnotes run until the next footnote or the end
+    /// of the document.
+    Bracketed,
+    /// `<footnotemark>12</footnotemark>` marks the footnote in the body, and
+    /// the footnote is `<footnote label="12">...</footnote>`.
+    Xml,
+}
+
+/// A footnote, along with the marker referring to it from the body text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Footnote {
+    pub label: String,
+    /// Start and stop offsets of the marker in the body, if there is one.
+    pub marker: Option<(usize, usize)>,
+    /// Start and stop offsets of the footnote.
+    pub span: (usize, usize),
+}
+
+impl Footnote {
+    pub fn contains(&self, offset: usize) -> bool {
+        self.span.0 <= offset && offset < self.span.1
+    }
+}
+
+/// Find the footnotes of a document, in the order they appear.
+pub fn find_footnotes(text: &str, style: FootnoteStyle) -> Vec<Footnote> {
+    match style {
+        FootnoteStyle::Bracketed => find_bracketed_footnotes(text),
+        FootnoteStyle::Xml => find_xml_footnotes(text),
+    }
+}
+
+fn find_bracketed_footnotes(text: &str) -> Vec<Footnote> {
+    let mut occurrences: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    let mut labels = Vec::new();
+    for captures in BRACKETED_REGEX.captures_iter(text) {
+        let (m, Some(label)) = (captures.get_match(), captures.get(1)) else {
+            continue;
+        };
+        let label = label.as_str();
+        if !occurrences.contains_key(label) {
+            labels.push(label);
+        }
+        occurrences
+            .entry(label)
+            .or_default()
+            .push((m.start(), m.end()));
+    }
+
+    // The footnote starts at the last occurrence of its label. With just one
+    // occurrence, it is only a footnote if it starts a line; otherwise it is
+    // a marker without a footnote.
+    let mut footnotes: Vec<_> = labels
+        .into_iter()
+        .filter_map(|label| {
+            let spans = &occurrences[label];
+            let start = spans[spans.len() - 1];
+            let marker = (spans.len() > 1).then_some(spans[0]);
+            let starts_line = text[..start.0].trim_end_matches(' ').ends_with('\n');
+            (marker.is_some() || starts_line).then(|| Footnote {
+                label: label.to_string(),
+                marker,
+                span: (start.0, text.len()),
+            })
+        })
+        .collect();
+
+    footnotes.sort_by_key(|f| f.span.0);
+    for i in 1..footnotes.len() {
+        footnotes[i - 1].span.1 = footnotes[i].span.0;
+    }
+
+    footnotes
+}
+
+fn find_xml_footnotes(text: &str) -> Vec<Footnote> {
+    let mut markers: HashMap<&str, (usize, usize)> = HashMap::new();
+    for captures in XML_MARKER_REGEX.captures_iter(text) {
+        let (m, Some(label)) = (captures.get_match(), captures.get(1)) else {
+            continue;
+        };
+        let label = label.as_str();
+        markers.entry(label).or_insert((m.start(), m.end()));
+    }
+
+    XML_FOOTNOTE_REGEX
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let (m, label) = (captures.get_match(), captures.get(1)?.as_str());
+            Some(Footnote {
+                label: label.to_string(),
+                marker: markers.get(label).copied(),
+                span: (m.start(), m.end()),
+            })
+        })
+        .collect()
+}
+
+/// Where a citation is in a document.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CitationLocation<'f> {
+    /// Start and stop offsets of the citation itself.
+    pub span: (usize, usize),
+    /// The footnote the citation is in, if any.
+    pub footnote: Option<&'f Footnote>,
+}
+
+impl<'f> CitationLocation<'f> {
+    /// The place in the body text the citation belongs to: the marker of its
+    /// footnote, or the citation itself when it isn't in a footnote or the
+    /// footnote has no marker.
+    pub fn body_span(&self) -> (usize, usize) {
+        self.footnote
+            .and_then(|footnote| footnote.marker)
+            .unwrap_or(self.span)
+    }
+}
+
+/// Locate each citation relative to the footnotes of the document it was
+/// found in.
+pub fn locate_citations<'f>(
+    citations: &[Citation],
+    footnotes: &'f [Footnote],
+) -> Vec<CitationLocation<'f>> {
+    citations
+        .iter()
+        .map(|citation| {
+            let span = citation.source().span();
+            CitationLocation {
+                span,
+                footnote: footnotes.iter().find(|f| f.contains(span.0)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_footnotes, locate_citations, FootnoteStyle};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn bracketed_footnotes() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "The rule is settled.[fn1] See 1 U.S. 1.\n\
+            [fn1] Roe v. Wade, 410 U.S. 113 (1973).";
+        let marker = text.find("[fn1]").unwrap();
+
+        let footnotes = find_footnotes(text, FootnoteStyle::Bracketed);
+        assert_eq!(footnotes.len(), 1);
+        assert_eq!(footnotes[0].marker, Some((marker, marker + 5)));
+
+        let citations = get_citations(text, false, &tokenizer);
+        let locations = locate_citations(&citations, &footnotes);
+        let body_spans: Vec<_> = locations.iter().map(|l| l.body_span()).collect();
+        assert_eq!(
+            body_spans,
+            vec![citations[0].source().span(), (marker, marker + 5)]
+        );
+    }
+
+    #[test]
+    fn xml_footnotes() {
+        let text = "<p>Settled.<footnotemark>2</footnotemark></p>\
+            <footnote label=\"2\"><p>410 U.S. 113.</p></footnote>";
+
+        let footnotes = find_footnotes(text, FootnoteStyle::Xml);
+        assert_eq!(footnotes.len(), 1);
+        assert_eq!(footnotes[0].label, "2");
+        assert_eq!(
+            &text[footnotes[0].marker.unwrap().0..footnotes[0].marker.unwrap().1],
+            "<footnotemark>2</footnotemark>"
+        );
+        assert!(text[footnotes[0].span.0..footnotes[0].span.1].ends_with("</footnote>"));
+    }
+}
@@ -0,0 +1,164 @@
+/*!
+Build a citation graph for a document out of its resolved citations.
+
+Each resource the document cites is a node, in the order the resources are
+first cited. Edges point from the document, or from a resource whose
+parenthetical nests another citation, like "(quoting Smith v. Jones, ...)",
+to the cited resource, and carry every place the resource was cited along
+with the signal and pin cite used there.
+ */
+
+use crate::find::models::Citation;
+use crate::resolve::Resolutions;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// One place a resource is cited.
+#[derive(Debug, Clone, Copy)]
+pub struct Citing<'c, 'a> {
+    pub citation: &'c Citation<'a>,
+    /// Start and stop offsets of the citation.
+    pub span: (usize, usize),
+    /// Introductory signal, like "See also", if any.
+    pub signal: Option<&'a str>,
+    pub pin_cite: Option<&'a str>,
+}
+
+impl<'c, 'a> Citing<'c, 'a> {
+    fn new(citation: &'c Citation<'a>) -> Self {
+        let source = citation.source();
+        Self {
+            citation,
+            span: source.span(),
+            signal: source.signal(),
+            pin_cite: source.pin_cite(),
+        }
+    }
+}
+
+/// All the places one node cites another.
+#[derive(Debug, Clone)]
+pub struct CitationEdge<'c, 'a> {
+    /// Index of the citing node, or `None` for the document itself.
+    pub from: Option<usize>,
+    /// Index of the cited node.
+    pub to: usize,
+    /// Places the cited resource is cited, in document order.
+    pub citings: Vec<Citing<'c, 'a>>,
+}
+
+/// Resources cited by a document and the citations between them.
+#[derive(Debug, Clone)]
+pub struct CitationGraph<'c, 'a, R> {
+    /// Resources, in the order they are first cited.
+    pub nodes: Vec<R>,
+    /// Edges, in the order of their first citing.
+    pub edges: Vec<CitationEdge<'c, 'a>>,
+}
+
+impl<'c, 'a, R> CitationGraph<'c, 'a, R> {
+    /// Edges out of the document itself, leaving out citations nested in
+    /// parentheticals.
+    pub fn document_edges(&self) -> impl Iterator<Item = &CitationEdge<'c, 'a>> {
+        self.edges.iter().filter(|edge| edge.from.is_none())
+    }
+}
+
+/// Build the citation graph of a document from its citations, in the order
+/// they were found, and their resolutions, from
+/// [`resolve_citations`](crate::resolve::resolve_citations) or the like.
+/// Citations that weren't resolved are left out.
+pub fn citation_graph<'c, 'a, R: Clone + Eq + Hash>(
+    citations: &'c [Citation<'a>],
+    resolutions: &Resolutions<'c, 'a, R>,
+) -> CitationGraph<'c, 'a, R> {
+    // Token index of each resolved citation to its resource
+    let resources: HashMap<usize, &R> = resolutions
+        .iter()
+        .flat_map(|(resource, citations)| {
+            citations
+                .iter()
+                .map(move |citation| (citation.source().index, resource))
+        })
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut node_indices: HashMap<&R, usize> = HashMap::new();
+    let mut edges: Vec<CitationEdge> = Vec::new();
+    let mut edge_indices: HashMap<(Option<usize>, usize), usize> = HashMap::new();
+
+    for citation in citations {
+        let Some(&resource) = resources.get(&citation.source().index) else {
+            continue;
+        };
+        let to = *node_indices.entry(resource).or_insert_with(|| {
+            nodes.push(resource.clone());
+            nodes.len() - 1
+        });
+        let from = citation
+            .source()
+            .enclosing_citation()
+            .and_then(|index| resources.get(&index))
+            .map(|resource| node_indices[resource]);
+
+        let edge = *edge_indices.entry((from, to)).or_insert_with(|| {
+            edges.push(CitationEdge {
+                from,
+                to,
+                citings: Vec::new(),
+            });
+            edges.len() - 1
+        });
+        edges[edge].citings.push(Citing::new(citation));
+    }
+
+    CitationGraph { nodes, edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::citation_graph;
+    use crate::find::get_citations;
+    use crate::resolve::{resolve_citations, resolve_full_citation};
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn graph_of_document() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Foo v. Bar, 1 U.S. 1 (1800) (quoting Baz v. Qux, 2 U.S. 5 (1801)). \
+            Cf. Bar, 1 U.S., at 2. See also id. at 3.";
+        let citations = get_citations(text, false, &tokenizer);
+        let resolutions = resolve_citations(&citations);
+
+        let graph = citation_graph(&citations, &resolutions);
+        assert_eq!(
+            graph.nodes,
+            vec![
+                resolve_full_citation(&citations[0]),
+                resolve_full_citation(&citations[1]),
+            ]
+        );
+
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from, edge.to, edge.citings.len()))
+            .collect();
+        assert_eq!(edges, vec![(None, 0, 3), (Some(0), 1, 1)]);
+
+        let citings: Vec<_> = graph
+            .document_edges()
+            .flat_map(|edge| &edge.citings)
+            .map(|citing| (citing.signal, citing.pin_cite))
+            .collect();
+        assert_eq!(
+            citings,
+            vec![
+                (Some("See"), None),
+                (Some("Cf."), Some("2")),
+                (Some("See also"), Some("3")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1383 @@
+use crate::find::models::{Citation, CitationMetadata, EditionChoice, JudgeTitle, OpinionRole};
+use crate::regexes;
+use crate::sentences;
+use crate::tokenizers::extractors::{Editions, ReporterEdition};
+use crate::tokenizers::models::{GroupName, Token, TokenData};
+use chrono::Datelike;
+use lazy_static::lazy_static;
+use regex::{Captures, Match, Regex};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+
+/// Median case name length in the CL db is 28 (2016-02-26)
+pub const BACKWARD_SEEK: usize = 28;
+
+/// Maximum characters to scan using [`match_on_tokens`].
+/// If this is higher we have to do a little more work for each
+/// [`match_on_tokens`] call to prepare the text to be matched.
+pub const MAX_MATCH_CHARS: usize = 300;
+
+/// How far the metadata heuristics scan around a citation. Larger windows
+/// find more case names and parentheticals, at the cost of more false
+/// positives and more work per citation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ScanLimits {
+    /// How many words to look back for case names and antecedents.
+    pub backward_seek: usize,
+    /// How many characters to scan for metadata like parentheticals and
+    /// years.
+    pub max_match_chars: usize,
+}
+
+impl Default for ScanLimits {
+    /// The same limits as python's eyecite.
+    fn default() -> Self {
+        Self {
+            backward_seek: BACKWARD_SEEK,
+            max_match_chars: MAX_MATCH_CHARS,
+        }
+    }
+}
+
+/// Reporters that only publish opinions of the Supreme Court.
+const SCOTUS_REPORTERS: [&str; 11] = [
+    "U.S.", "S. Ct.", "L. Ed.", "U.S.L.W.", "Dall.", "Cranch", "Wheat.", "Pet.", "How.", "Black",
+    "Wall.",
+];
+
+/// If scanning forward, regex must match at start
+fn forward_regex(regex: &str) -> Regex {
+    cached_regex(&format!("(?x)^(?:{}\n)", regex)).expect("metadata regexes should compile")
+}
+
+/// If scanning backward, regex must match at end
+fn backward_regex(regex: &str) -> Regex {
+    cached_regex(&format!("(?x)(?:{}\n)$", regex)).expect("metadata regexes should compile")
+}
+
+thread_local! {
+    static COMPILED_REGEXES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Get a regex used to scan for metadata, compiling it only the first time
+/// it's asked for. Regexes built from patterns at runtime should come from
+/// here rather than `Regex::new`, so finding citations never compiles them
+/// again for each citation or document. Fails if the pattern doesn't
+/// compile.
+pub fn cached_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let cached = REGEX_CACHE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(pattern)
+        .cloned();
+    if let Some(regex) = cached {
+        return Ok(regex);
+    }
+
+    let regex = Regex::new(pattern)?;
+    COMPILED_REGEXES.with(|compiled| compiled.set(compiled.get() + 1));
+    Ok(REGEX_CACHE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(pattern.to_string())
+        .or_insert(regex)
+        .clone())
+}
+
+/// How many regexes [`cached_regex`] has compiled on this thread.
+pub fn compiled_regexes() -> usize {
+    COMPILED_REGEXES.with(Cell::get)
+}
+
+lazy_static! {
+    /// Regexes compiled by [`cached_regex`], by pattern. Cloning a `Regex`
+    /// shares the compiled program.
+    static ref REGEX_CACHE: RwLock<HashMap<String, Regex>> = RwLock::default();
+
+    static ref PIN_CITE_RE: Regex = forward_regex(regexes::PIN_CITE_REGEX);
+    static ref POST_FULL_CITATION_RE: Regex = forward_regex(regexes::POST_FULL_CITATION_REGEX);
+    static ref POST_SHORT_CITATION_RE: Regex = forward_regex(regexes::POST_SHORT_CITATION_REGEX);
+    static ref SHORT_CITE_ANTECEDENT_RE: Regex =
+        backward_regex(regexes::SHORT_CITE_ANTECEDENT_REGEX);
+    static ref SUPRA_ANTECEDENT_RE: Regex = backward_regex(regexes::SUPRA_ANTECEDENT_REGEX);
+    static ref HEREINAFTER_RE: Regex = forward_regex(regexes::HEREINAFTER_REGEX);
+    static ref INFRA_REFERENCE_RE: Regex = forward_regex(regexes::INFRA_REFERENCE_REGEX);
+    static ref SUPRA_NOTE_RE: Regex = forward_regex(regexes::SUPRA_NOTE_REGEX);
+    static ref SIGNAL_RE: Regex = forward_regex(regexes::SIGNAL_REGEX);
+    static ref SUBSEQUENT_HISTORY_RE: Regex = backward_regex(regexes::SUBSEQUENT_HISTORY_REGEX);
+    static ref YEAR_RE: Regex = forward_regex(regexes::YEAR_REGEX);
+    static ref SLIP_OPINION_DATE_RE: Regex = forward_regex(regexes::SLIP_OPINION_DATE_REGEX);
+    #[cfg(feature = "laws")]
+    static ref POST_LAW_CITATION_RE: Regex = forward_regex(regexes::POST_LAW_CITATION_REGEX);
+    static ref DISPOSITION_RE: Regex = forward_regex(regexes::DISPOSITION_REGEX);
+}
+
+/// The word tokens of a document, along with the offset each one starts at
+/// in the original text. Words are contiguous slices of the text, so metadata
+/// found by scanning them can be sliced straight out of the text.
+pub struct Document<'t, 'a> {
+    pub text: &'a str,
+    pub words: &'t [Token<'a>],
+    offsets: Vec<usize>,
+    sentence_starts: Vec<usize>,
+    pub limits: ScanLimits,
+}
+
+impl<'t, 'a> Document<'t, 'a> {
+    pub fn new(text: &'a str, words: &'t [Token<'a>]) -> Self {
+        let mut offsets = Vec::with_capacity(words.len() + 1);
+        let mut token_spans = Vec::new();
+        let mut offset = 0;
+
+        for word in words {
+            offsets.push(offset);
+            offset = match word {
+                Token::Word(word) => offset + word.len(),
+                Token::Space => offset + 1,
+                token => {
+                    token_spans.push((offset, token.end()));
+                    token.end()
+                }
+            };
+        }
+        offsets.push(offset);
+
+        // Periods in reporters, like "Cal. App.", don't end sentences
+        let mut token_spans = token_spans.into_iter().peekable();
+        let sentence_starts = sentences::sentence_starts(text)
+            .filter(|&start| {
+                while token_spans.next_if(|&(_, end)| end < start).is_some() {}
+                !matches!(token_spans.peek(), Some(&(s, e)) if s < start && start < e)
+            })
+            .collect();
+
+        Self {
+            text,
+            words,
+            offsets,
+            sentence_starts,
+            limits: ScanLimits::default(),
+        }
+    }
+
+    /// Scan around citations within the given limits instead of the defaults.
+    pub fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Offsets where sentences after the first start, in order.
+    pub fn sentence_starts(&self) -> &[usize] {
+        &self.sentence_starts
+    }
+
+    /// Offset where the sentence containing `offset` starts.
+    pub fn sentence_start(&self, offset: usize) -> usize {
+        let i = self
+            .sentence_starts
+            .partition_point(|&start| start <= offset);
+        i.checked_sub(1).map_or(0, |i| self.sentence_starts[i])
+    }
+
+    /// Offset where the sentence after the one containing `offset` starts,
+    /// or the end of the text.
+    pub fn sentence_end(&self, offset: usize) -> usize {
+        let i = self
+            .sentence_starts
+            .partition_point(|&start| start <= offset);
+        self.sentence_starts
+            .get(i)
+            .copied()
+            .unwrap_or(self.text.len())
+    }
+
+    /// Offset in the text where the word at `index` starts.
+    pub fn start_of(&self, index: usize) -> usize {
+        self.offsets[index.min(self.words.len())]
+    }
+
+    /// Text of the words from `start` up to, but not including, `end`.
+    pub fn text_between(&self, start: usize, end: usize) -> &'a str {
+        &self.text[self.start_of(start)..self.start_of(end)]
+    }
+}
+
+/// A regex match against a window of the document's text.
+pub struct TextMatch<'a> {
+    captures: Captures<'a>,
+    offset: usize,
+}
+
+impl<'a> TextMatch<'a> {
+    /// Text matched by the named group, if any.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.captures.name(name).map(|m| m.as_str())
+    }
+
+    /// Start and stop offsets in the document of the named group, if any.
+    pub fn span(&self, name: &str) -> Option<(usize, usize)> {
+        self.captures
+            .name(name)
+            .map(|m| (self.offset + m.start(), self.offset + m.end()))
+    }
+
+    /// The `parenthetical` group, cut down by [`process_parenthetical`],
+    /// along with its start and stop offsets in the document.
+    pub fn parenthetical(&self) -> (Option<&'a str>, Option<(usize, usize)>) {
+        matched_parenthetical(self.captures.name("parenthetical"), self.offset)
+    }
+}
+
+pub(crate) fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+pub(crate) fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Get the start and stop offsets of the text [`match_on_tokens`] would
+/// match against, within the document's [`ScanLimits`]. The window never
+/// extends past the sentence it starts (when scanning forward) or ends
+/// (when scanning backward) in.
+pub fn scan_window(
+    document: &Document,
+    index: usize,
+    prefix_start: Option<usize>,
+    strings_only: bool,
+    forward: bool,
+) -> (usize, usize) {
+    let is_stop = |token: &Token| match token {
+        Token::Word(_) | Token::Space => false,
+        Token::Paragraph(_) => true,
+        _ => strings_only,
+    };
+    let words = document.words;
+    let limits = document.limits;
+
+    if forward {
+        let start = prefix_start.unwrap_or_else(|| document.start_of(index));
+        let stop = (index..words.len())
+            .find(|&i| is_stop(&words[i]))
+            .map_or(document.text.len(), |i| document.start_of(i))
+            .min(document.sentence_end(start));
+        let stop = floor_char_boundary(document.text, stop.min(start + limits.max_match_chars));
+
+        (start, stop.max(start))
+    } else {
+        let stop = document.start_of(index);
+        let start = (0..index.min(words.len()))
+            .rev()
+            .find(|&i| is_stop(&words[i]))
+            .map_or(0, |i| document.start_of(i + 1))
+            .max(document.sentence_start(stop))
+            .max(document.start_of(index.saturating_sub(limits.backward_seek)));
+        let start = ceil_char_boundary(
+            document.text,
+            start.max(stop.saturating_sub(limits.max_match_chars)),
+        );
+
+        (start.min(stop), stop)
+    }
+}
+
+/// Scan forward or backward starting from the given index, up to the
+/// document's [`ScanLimits::max_match_chars`]. Return result of matching
+/// regex against token text.
+///
+/// If scanning forward, the words from `index` on are matched, and if
+/// `prefix_start` is provided the text starts at that offset instead. If
+/// scanning backward, the words before `index` are matched.
+/// If `strings_only` is true, stop matching at any non-string token;
+/// otherwise stop matching only at paragraph tokens.
+pub fn match_on_tokens<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    regex: &Regex,
+    prefix_start: Option<usize>,
+    strings_only: bool,
+    forward: bool,
+) -> Option<TextMatch<'a>> {
+    let (start, stop) = scan_window(document, index, prefix_start, strings_only, forward);
+
+    regex
+        .captures(&document.text[start..stop])
+        .map(|captures| TextMatch {
+            captures,
+            offset: start,
+        })
+}
+
+/// Lowest year a citation can plausibly be from.
+pub const LOWEST_VALID_YEAR: u16 = 1700;
+
+/// Parse a matched year string, rejecting anything that isn't all digits or
+/// isn't within [`LOWEST_VALID_YEAR`] and [`highest_valid_year`], like
+/// "19730" or "0197".
+pub fn parse_year(word: &str) -> Option<u16> {
+    if !word.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: u16 = word.parse().ok()?;
+
+    (i32::from(LOWEST_VALID_YEAR)..=highest_valid_year())
+        .contains(&i32::from(year))
+        .then_some(year)
+}
+
+/// Given a matched year string, look for a year within a reasonable
+/// range. See [`parse_year`].
+pub fn get_year(word: &str) -> Option<i32> {
+    parse_year(word).map(i32::from)
+}
+
+/// Highest valid year is this year + 1 because courts in December sometimes
+/// cite a case to be published in January.
+pub fn highest_valid_year() -> i32 {
+    chrono::Utc::now().year() + 1
+}
+
+/// The first group of a citation token's match that fails validation, if
+/// any: a volume that doesn't start with a digit, or an empty reporter or
+/// page. Such tokens are skipped rather than extracted, see
+/// [`ExtractionWarning::MalformedMatch`](crate::report::ExtractionWarning::MalformedMatch).
+pub fn malformed_group<'n>(data: &TokenData<'_>) -> Option<GroupName<'n>> {
+    let groups = &data.groups;
+
+    if groups.get(GroupName::Volume).is_some_and(|volume| {
+        !volume.starts_with(|c: char| c.is_ascii_digit()) && !is_placeholder(volume)
+    }) {
+        return Some(GroupName::Volume);
+    }
+
+    [GroupName::Reporter, GroupName::Page]
+        .into_iter()
+        .find(|&name| {
+            groups
+                .get(name)
+                .is_some_and(|value| value.trim().is_empty())
+        })
+}
+
+/// Whether a volume or page is a blank left to be filled in once it is
+/// known, like both of those in "___ U.S. ___".
+pub fn is_placeholder(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c == '_')
+}
+
+/// Strip spaces and commas from pin_cite.
+pub fn clean_pin_cite(pin_cite: &str) -> Option<&str> {
+    Some(pin_cite.trim_matches([',', ' '])).filter(|p| !p.is_empty())
+}
+
+/// Start and stop offsets of a value starting at `start`, if both are
+/// known.
+fn value_span(value: Option<&str>, start: Option<usize>) -> Option<(usize, usize)> {
+    value
+        .zip(start)
+        .map(|(value, start)| (start, start + value.len()))
+}
+
+/// A pin cite matched `offset` bytes into the document, cleaned up by
+/// [`clean_pin_cite`], along with its start and stop offsets.
+fn matched_pin_cite<'a>(
+    pin_cite: Option<Match<'a>>,
+    offset: usize,
+) -> (Option<&'a str>, Option<(usize, usize)>) {
+    let Some(pin_cite) = pin_cite else {
+        return (None, None);
+    };
+    let matched = pin_cite.as_str();
+    let cleaned = clean_pin_cite(matched);
+    let leading = matched.len() - matched.trim_start_matches([',', ' ']).len();
+
+    (
+        cleaned,
+        value_span(cleaned, Some(offset + pin_cite.start() + leading)),
+    )
+}
+
+/// A parenthetical matched `offset` bytes into the document, cut down by
+/// [`process_parenthetical`], along with its start and stop offsets.
+fn matched_parenthetical<'a>(
+    parenthetical: Option<Match<'a>>,
+    offset: usize,
+) -> (Option<&'a str>, Option<(usize, usize)>) {
+    let Some(parenthetical) = parenthetical else {
+        return (None, None);
+    };
+    // What's kept is always a prefix of what was matched
+    let processed = process_parenthetical(parenthetical.as_str());
+
+    (
+        processed,
+        value_span(processed, Some(offset + parenthetical.start())),
+    )
+}
+
+/// Exclude any additional parentheticals matched as well as year parentheticals
+///
+/// For example: 'something) (something else)' will be trimmed down
+/// to 'something' but 'something (clarifying something) or other' will be
+/// kept in full.
+pub fn process_parenthetical(matched_parenthetical: &str) -> Option<&str> {
+    let mut paren_balance = 0;
+
+    for (i, char) in matched_parenthetical.char_indices() {
+        if char == '(' {
+            // Nested parenthetical
+            paren_balance += 1;
+        } else if char == ')' {
+            paren_balance -= 1;
+        }
+
+        if paren_balance < 0 {
+            // End parenthetical reached
+            return Some(&matched_parenthetical[..i]).filter(|p| !p.is_empty());
+        }
+    }
+
+    if YEAR_RE.is_match(matched_parenthetical) {
+        return None;
+    }
+
+    Some(matched_parenthetical).filter(|p| !p.is_empty())
+}
+
+/// The pin cite and parenthetical found after a short form citation. See
+/// [`extract_pin_cite`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PinCite<'a> {
+    pub pin_cite: Option<&'a str>,
+    pub parenthetical: Option<&'a str>,
+    /// Offset in the document the citation's span should end at.
+    pub span_end: usize,
+    /// Start and stop offsets of the pin cite and the parenthetical.
+    pub pin_cite_span: Option<(usize, usize)>,
+    pub parenthetical_span: Option<(usize, usize)>,
+}
+
+/// Test whether text following token at index is a valid pin cite.
+/// Return pin cite text, the offset the citation's span should end at, and
+/// any parenthetical after the pin cite.
+/// If `prefix_start` is provided, use the text from that offset as the start
+/// of text to match.
+pub fn extract_pin_cite<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    prefix_start: Option<usize>,
+) -> PinCite<'a> {
+    let (start, stop) = scan_window(document, index + 1, prefix_start, true, true);
+    let text = &document.text[start..stop];
+
+    let pin_cite = PIN_CITE_RE
+        .captures(text)
+        .and_then(|captures| captures.name("pin_cite"));
+
+    let rest = pin_cite.map_or(0, |m| m.end());
+    let (parenthetical, parenthetical_span) = matched_parenthetical(
+        POST_SHORT_CITATION_RE
+            .captures(&text[rest..])
+            .and_then(|captures| captures.name("parenthetical")),
+        start + rest,
+    );
+    let span_end = pin_cite.map_or(start, |pin_cite| {
+        start + pin_cite.start() + pin_cite.as_str().trim_end_matches([',', ' ']).len()
+    });
+    let (pin_cite, pin_cite_span) = matched_pin_cite(pin_cite, start);
+
+    PinCite {
+        pin_cite,
+        parenthetical,
+        span_end,
+        pin_cite_span,
+        parenthetical_span,
+    }
+}
+
+/// Metadata found after a full citation. See [`regexes::POST_FULL_CITATION_REGEX`]
+/// for examples.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PostCitation<'a> {
+    pub pin_cite: Option<&'a str>,
+    pub extra: Option<&'a str>,
+    pub court: Option<&'a str>,
+    pub year: Option<&'a str>,
+    pub parenthetical: Option<&'a str>,
+    /// Start and stop offsets of the pin cite, year and parenthetical.
+    pub pin_cite_span: Option<(usize, usize)>,
+    pub year_span: Option<(usize, usize)>,
+    pub parenthetical_span: Option<(usize, usize)>,
+}
+
+/// Find any additional information after the base citation, including
+/// court, year, and possibly page range.
+pub fn extract_post_citation<'a>(document: &Document<'_, 'a>, index: usize) -> PostCitation<'a> {
+    let (start, stop) = scan_window(document, index + 1, None, false, true);
+    let text = &document.text[start..stop];
+
+    let pin_cite = PIN_CITE_RE
+        .captures(text)
+        .and_then(|captures| captures.name("pin_cite"));
+
+    // Prefer a pin cite followed by a year paren, then a year paren without
+    // a pin cite, and finally a pin cite on its own. Also keep where in the
+    // document the year paren was matched from.
+    let (pin_cite, post) = match pin_cite.and_then(|pin_cite| {
+        let rest = pin_cite.end();
+        Some((POST_FULL_CITATION_RE.captures(&text[rest..])?, start + rest))
+    }) {
+        Some(post) => (pin_cite, Some(post)),
+        None => match POST_FULL_CITATION_RE.captures(text) {
+            Some(post) => (None, Some((post, start))),
+            None => (pin_cite, None),
+        },
+    };
+
+    let (pin_cite, pin_cite_span) = matched_pin_cite(pin_cite, start);
+    let mut result = PostCitation {
+        pin_cite,
+        pin_cite_span,
+        ..Default::default()
+    };
+
+    if let Some((post, offset)) = post {
+        let group = |name| post.name(name).map(|m| m.as_str());
+
+        result.extra = group("extra")
+            .map(str::trim)
+            .filter(|extra| !extra.is_empty());
+        result.court = group("court")
+            .map(str::trim)
+            .filter(|court| !court.is_empty());
+        result.year = group("year");
+        result.year_span = value_span(result.year, post.name("year").map(|m| offset + m.start()));
+        (result.parenthetical, result.parenthetical_span) =
+            matched_parenthetical(post.name("parenthetical"), offset);
+    }
+
+    result
+}
+
+/// The court and date found after a slip opinion. See
+/// [`regexes::SLIP_OPINION_DATE_REGEX`] for examples.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SlipOpinionDate<'a> {
+    pub court: Option<&'a str>,
+    pub month: Option<&'a str>,
+    pub day: Option<&'a str>,
+    pub year: Option<&'a str>,
+    pub parenthetical: Option<&'a str>,
+    /// Offset in the document where the date parenthetical ends.
+    pub span_end: usize,
+    /// Start and stop offsets of the year and parenthetical.
+    pub year_span: Option<(usize, usize)>,
+    pub parenthetical_span: Option<(usize, usize)>,
+}
+
+/// Find the court and date parenthetical after a slip opinion, like
+/// "(U.S. June 24, 2022)".
+pub fn extract_slip_opinion_date<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> Option<SlipOpinionDate<'a>> {
+    let m = match_on_tokens(document, index + 1, &SLIP_OPINION_DATE_RE, None, true, true)?;
+    let (parenthetical, parenthetical_span) = m.parenthetical();
+
+    Some(SlipOpinionDate {
+        court: m.get("court").map(str::trim),
+        month: m.get("month"),
+        day: m.get("day"),
+        year: m.get("year"),
+        parenthetical,
+        span_end: m
+            .span("year")
+            .map_or(document.start_of(index + 1), |(_, end)| end + 1),
+        year_span: m.span("year"),
+        parenthetical_span,
+    })
+}
+
+/// The status, publisher and date found after a law citation. See
+/// [`regexes::POST_LAW_CITATION_REGEX`] for examples.
+#[cfg(feature = "laws")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PostLawCitation<'a> {
+    pub status: Option<&'a str>,
+    pub publisher: Option<&'a str>,
+    pub month: Option<&'a str>,
+    pub day: Option<&'a str>,
+    pub year: Option<&'a str>,
+    pub parenthetical: Option<&'a str>,
+    /// Offset in the document where the date parenthetical ends.
+    pub span_end: usize,
+    /// Start and stop offsets of the year and parenthetical.
+    pub year_span: Option<(usize, usize)>,
+    pub parenthetical_span: Option<(usize, usize)>,
+}
+
+/// Find the parenthetical after a law citation, like "(West 2019)" or
+/// "(repealed 1988)".
+#[cfg(feature = "laws")]
+pub fn extract_post_law_citation<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> Option<PostLawCitation<'a>> {
+    let m = match_on_tokens(document, index + 1, &POST_LAW_CITATION_RE, None, true, true)?;
+    let (parenthetical, parenthetical_span) = m.parenthetical();
+
+    Some(PostLawCitation {
+        status: m.get("status"),
+        publisher: m.get("publisher"),
+        month: m.get("month"),
+        day: m.get("day"),
+        year: m.get("year"),
+        parenthetical,
+        span_end: m
+            .span("year")
+            .map_or(document.start_of(index + 1), |(_, end)| end + 1),
+        year_span: m.span("year"),
+        parenthetical_span,
+    })
+}
+
+/// Scan backwards from reporter until you find v., in re,
+/// etc. If no known stop-token is found, no defendant name is stored.  In the
+/// future, this could be improved.
+///
+/// Returns the plaintiff and defendant, if found.
+pub fn extract_defendant<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> (Option<&'a str>, Option<&'a str>) {
+    let mut plaintiff = None;
+    let mut start_index = None;
+    let sentence_start = document.sentence_start(document.start_of(index));
+
+    let backward_seek = document.limits.backward_seek;
+    for i in (index.saturating_sub(backward_seek.saturating_sub(1))..index).rev() {
+        // Case names don't cross sentences
+        if document.start_of(i) < sentence_start {
+            break;
+        }
+
+        match &document.words[i] {
+            // Skip it
+            Token::Word(",") => continue,
+            Token::StopWord(data) => {
+                if data.groups.get(GroupName::StopWord) == Some("v") && i > 0 {
+                    plaintiff = Some(document.text_between(i.saturating_sub(2), i).trim())
+                        .filter(|p| !p.is_empty());
+                }
+                start_index = Some(i + 1);
+                break;
+            }
+            // String citation
+            Token::Word(word) if word.ends_with(';') => break,
+            _ => {}
+        }
+    }
+
+    let defendant = start_index
+        .map(|start| document.text_between(start, index).trim_matches([',', ' ']))
+        .filter(|d| !d.is_empty());
+
+    (plaintiff, defendant)
+}
+
+/// Guess the antecedent of a short form citation, like "Adarand" in
+/// "Adarand, 515 U.S., at 241".
+pub fn extract_short_cite_antecedent<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> Option<&'a str> {
+    match_on_tokens(
+        document,
+        index,
+        &SHORT_CITE_ANTECEDENT_RE,
+        None,
+        true,
+        false,
+    )
+    .and_then(|m| m.get("antecedent"))
+    .map(str::trim)
+}
+
+/// Guess the antecedent and volume of a supra citation, like "Adarand" and
+/// "515" in "Adarand, 515 supra, at 240".
+pub fn extract_supra_antecedent<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> (Option<&'a str>, Option<&'a str>) {
+    match match_on_tokens(document, index, &SUPRA_ANTECEDENT_RE, None, true, false) {
+        Some(m) => (
+            m.get("antecedent").or_else(|| m.get("bare_antecedent")),
+            m.get("volume").or_else(|| m.get("bare_volume")),
+        ),
+        None => (None, None),
+    }
+}
+
+/// Find what an infra or supra citation points to, like "note 45" in
+/// "infra note 45". Returns the reference and the offset where it ends.
+///
+/// References like "Part III" are cross reference tokens themselves, so
+/// scanning doesn't stop at them.
+pub fn extract_infra_reference<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+) -> Option<(&'a str, usize)> {
+    let m = match_on_tokens(document, index + 1, &INFRA_REFERENCE_RE, None, false, true)?;
+
+    Some((m.get("reference")?, m.span("reference")?.1))
+}
+
+/// Find the footnote number after the supra token at `index`, like "45" in
+/// "supra note 45".
+pub fn extract_supra_note<'a>(document: &Document<'_, 'a>, index: usize) -> Option<&'a str> {
+    match_on_tokens(document, index + 1, &SUPRA_NOTE_RE, None, false, true)?.get("note")
+}
+
+/// Whether an antecedent guess looks like part of a case name, rather than a
+/// signal or other lowercase word, like "see" in "see supra Part III".
+pub fn is_case_name_word(word: &str) -> bool {
+    word.starts_with(char::is_uppercase) && !SIGNAL_RE.is_match(&format!("{} ", word))
+}
+
+/// Find the short name a full citation is given for later references, like
+/// "[hereinafter Smith Report]".
+pub fn extract_hereinafter<'a>(document: &Document<'_, 'a>, index: usize) -> Option<&'a str> {
+    match_on_tokens(document, index + 1, &HEREINAFTER_RE, None, true, true)
+        .and_then(|m| m.get("hereinafter"))
+        .map(str::trim)
+}
+
+/// Find a short name given by an earlier "[hereinafter ...]" right before
+/// the token at `index`, like "Smith Report" in "Smith Report, supra, at 4".
+/// Short names are often more than one word, which the antecedent regexes
+/// can't capture.
+pub fn extract_hereinafter_antecedent<'a>(
+    document: &Document<'_, 'a>,
+    index: usize,
+    aliases: &[&'a str],
+) -> Option<&'a str> {
+    let before = document.text[..document.start_of(index)].trim_end_matches([',', ' ']);
+
+    aliases
+        .iter()
+        .find(|alias| before.ends_with(**alias))
+        .copied()
+}
+
+/// Record which citation each citation nested in a parenthetical belongs to,
+/// like "Smith" in "Roe, 410 U.S. 113 (1973) (quoting Smith, 1 U.S. 1)".
+///
+/// A citation is nested in the nearest earlier citation in the same sentence
+/// that has more parentheses opened than closed between the two.
+pub fn add_nested_citation_metadata(document: &Document<'_, '_>, citations: &mut [Citation<'_>]) {
+    let text = document.text;
+
+    for i in 0..citations.len() {
+        if matches!(citations[i], Citation::Unknown { .. }) {
+            continue;
+        }
+
+        let start = citations[i].source().span().0;
+        let sentence_start = document.sentence_start(start);
+        let mut depth = 0isize;
+        let mut scanned = start;
+        let mut enclosing = None;
+
+        for citation in citations[..i].iter().rev() {
+            let end = citation.source().token.end().min(scanned);
+            if end < sentence_start || start - end > document.limits.max_match_chars {
+                break;
+            }
+
+            for c in text[end..scanned].chars() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+            }
+            scanned = end;
+
+            if depth > 0 && !matches!(citation, Citation::Unknown { .. }) {
+                enclosing = Some(citation.source().index);
+                break;
+            }
+        }
+
+        if let Some(enclosing) = enclosing {
+            citations[i]
+                .source_mut()
+                .metadata
+                .insert(CitationMetadata::EnclosingCitation(enclosing));
+        }
+    }
+}
+
+/// Find the quotation ending at `end`, if the text before it ends with a
+/// closing quotation mark, and return the offsets of the quoted text.
+fn quotation_before(text: &str, end: usize, max_chars: usize) -> Option<(usize, usize)> {
+    let before = text[..end].trim_end();
+    let close = before.chars().next_back()?;
+    let open = match close {
+        '"' => '"',
+        '”' => '“',
+        '\'' => '\'',
+        '’' => '‘',
+        _ => return None,
+    };
+    let quote_end = before.len() - close.len_utf8();
+
+    // Straight quotes open at the start of a word, so apostrophes, like
+    // "court's", aren't mistaken for them.
+    let quote_start = before[..quote_end]
+        .match_indices(open)
+        .map(|(i, _)| i)
+        .rev()
+        .find(|&i| {
+            open != close
+                || before[..i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|c| c.is_whitespace() || c == '(' || c == '[')
+        })?
+        + open.len_utf8();
+
+    (quote_start < quote_end && quote_end - quote_start <= max_chars)
+        .then_some((quote_start, quote_end))
+}
+
+/// Start and stop offsets of the text inside each pair of quotation marks,
+/// nested quotations included, in the order they close.
+///
+/// Curly quotes pair up by kind. Straight quotes only open at the start of a
+/// word and close at the end of one, and a single quote only closes when it
+/// isn't followed by a letter, so apostrophes, like "court's" or "’90s",
+/// aren't mistaken for quotes. Quotations don't run across paragraphs.
+pub fn quotation_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    // Quote characters opened so far, along with where the quoted text starts
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut previous = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let word_start = previous.is_none_or(|p: char| p.is_whitespace() || "([\"“‘—".contains(p))
+            && next.is_some_and(|n| !n.is_whitespace());
+        let word_end = previous.is_some_and(|p| !p.is_whitespace())
+            && next.is_none_or(|n| !n.is_alphanumeric());
+        let opener = match c {
+            '”' => Some('“'),
+            '’' if word_end => Some('‘'),
+            '"' | '\'' if word_end => Some(c),
+            _ => None,
+        };
+
+        if let Some(position) = opener.and_then(|o| open.iter().rposition(|&(q, _)| q == o)) {
+            spans.push((open[position].1, i));
+            open.truncate(position);
+        } else if c == '“' || c == '‘' || ((c == '"' || c == '\'') && word_start) {
+            open.push((c, i + c.len_utf8()));
+        } else if c == '\n' && previous == Some('\n') {
+            open.clear();
+        }
+        previous = Some(c);
+    }
+
+    spans
+}
+
+/// Record which citations are inside quotation marks, like "in Smith, 1 U.S.
+/// 1, we held" quoted from another opinion. See [`quotation_spans`].
+pub fn add_quoted_metadata(document: &Document<'_, '_>, citations: &mut [Citation<'_>]) {
+    let quotations = quotation_spans(document.text);
+    if quotations.is_empty() {
+        return;
+    }
+
+    for citation in citations.iter_mut() {
+        let (start, end) = citation.source().matched_span();
+        if quotations
+            .iter()
+            .any(|&(quote_start, quote_end)| quote_start <= start && end <= quote_end)
+        {
+            citation
+                .source_mut()
+                .metadata
+                .insert(CitationMetadata::Quoted);
+        }
+    }
+}
+
+/// Record the disposition of each cited case, or the kind of opinion cited,
+/// from its parenthetical, like "per curiam" or "Scalia, J., dissenting".
+/// See [`regexes::DISPOSITION_REGEX`].
+pub fn add_disposition_metadata(citations: &mut [Citation<'_>]) {
+    for citation in citations.iter_mut() {
+        let source = citation.source_mut();
+        let Some(captures) = source
+            .parenthetical()
+            .and_then(|parenthetical| DISPOSITION_RE.captures(parenthetical))
+        else {
+            continue;
+        };
+        let group = |name: &str| captures.name(name).map(|m| m.as_str().trim_end());
+
+        source.add_metadata(CitationMetadata::Disposition, group("disposition"));
+        source.add_metadata(CitationMetadata::Judge, group("judge"));
+        source.add_metadata(CitationMetadata::OpinionType, group("opinion_type"));
+
+        let title = group("title").and_then(JudgeTitle::parse);
+        let role = group("opinion_type").and_then(OpinionRole::parse);
+        source
+            .metadata
+            .extend(title.map(CitationMetadata::JudgeTitle));
+        source
+            .metadata
+            .extend(role.map(CitationMetadata::OpinionRole));
+    }
+}
+
+/// Record which of the sections of the document, given as start and stop
+/// offsets, each citation starts in.
+pub fn add_section_metadata(sections: &[(usize, usize)], citations: &mut [Citation<'_>]) {
+    if sections.is_empty() {
+        return;
+    }
+
+    for citation in citations.iter_mut() {
+        let start = citation.source().span().0;
+        if let Some(section) = sections
+            .iter()
+            .position(|&(section_start, section_end)| section_start <= start && start < section_end)
+        {
+            citation
+                .source_mut()
+                .metadata
+                .insert(CitationMetadata::DocumentSection(section));
+        }
+    }
+}
+
+/// Start and stop offsets of `slice` in `text`, if it was sliced from it.
+pub fn subslice_span(text: &str, slice: &str) -> Option<(usize, usize)> {
+    let start = (slice.as_ptr() as usize).checked_sub(text.as_ptr() as usize)?;
+    let end = start + slice.len();
+    (end <= text.len()).then_some((start, end))
+}
+
+/// Record the quoted text each citation is given for, like "the statute is
+/// clear." in "'the statute is clear.' See Smith, 1 U.S. at 5".
+///
+/// The quotation has to end right before the sentence the citation starts,
+/// and only an introductory signal and a case name may come between them.
+pub fn add_quotation_metadata(document: &Document<'_, '_>, citations: &mut [Citation<'_>]) {
+    let text = document.text;
+
+    for citation in citations.iter_mut() {
+        if matches!(citation, Citation::Unknown { .. }) {
+            continue;
+        }
+
+        let start = citation.source().span().0;
+        let sentence_start = document.sentence_start(start);
+        let clause = &text[sentence_start..start];
+        if clause.contains(['"', '“', '”', ';'])
+            || clause.split_whitespace().count() > document.limits.backward_seek
+        {
+            continue;
+        }
+
+        if let Some((quote_start, quote_end)) =
+            quotation_before(text, sentence_start, document.limits.max_match_chars)
+        {
+            citation
+                .source_mut()
+                .metadata
+                .insert(CitationMetadata::Quotation(quote_start, quote_end));
+        }
+    }
+}
+
+/// Record which string cite each citation belongs to, along with the
+/// introductory signal that applies to it.
+///
+/// A string cite is the run of citations in the same sentence, like
+/// "See Smith, 1 U.S. 1 (1800); Jones, 2 U.S. 2 (1801).". Members are
+/// separated by semicolons, and each one carries the signal of the member
+/// before it unless it starts with its own, like "; see also Jones, ...".
+/// A citation given as the subsequent history of the one before it, like
+/// "rev'd on other grounds, 2 U.S. 2", has the history as its signal
+/// instead, without passing it on.
+pub fn add_string_cite_metadata<'a>(document: &Document<'_, 'a>, citations: &mut [Citation<'a>]) {
+    let text = document.text;
+    let mut previous_end = 0;
+    let mut string_cite = None;
+    let mut signal = None;
+
+    for citation in citations.iter_mut() {
+        // Citations nested in parentheticals aren't members of a string cite
+        if matches!(citation, Citation::Unknown { .. })
+            || citation.source().enclosing_citation().is_some()
+        {
+            continue;
+        }
+
+        let (start, end) = citation.source().span();
+        let between_start = previous_end.min(start);
+        let between = &text[between_start..start];
+        let sentence_start = sentences::sentence_starts(between).last();
+
+        // Where the text before this member starts, if it is a new member
+        let member_start = match (string_cite, sentence_start) {
+            (Some(_), None) => between.rfind(';').map(|i| i + 1),
+            (None, _) | (_, Some(_)) => {
+                string_cite = Some(citation.source().index);
+                signal = None;
+                Some(sentence_start.unwrap_or(0))
+            }
+        };
+
+        if let Some(member_start) = member_start {
+            let clause = &between[member_start..];
+            let clause = clause.trim_start();
+            let clause_start = start - clause.len();
+
+            if let Some(m) = SIGNAL_RE.captures(clause).and_then(|m| m.name("signal")) {
+                signal = text.get(clause_start + m.start()..clause_start + m.end());
+            }
+        }
+
+        let history = member_start
+            .is_none()
+            .then(|| SUBSEQUENT_HISTORY_RE.captures(between))
+            .flatten()
+            .and_then(|m| m.name("signal"))
+            .and_then(|m| text.get(between_start + m.start()..between_start + m.end()));
+
+        let source = citation.source_mut();
+        source.add_metadata(CitationMetadata::Signal, history.or(signal));
+        if let Some(string_cite) = string_cite {
+            source
+                .metadata
+                .insert(CitationMetadata::StringCite(string_cite));
+        }
+        previous_end = end;
+    }
+}
+
+/// Editions a citation might refer to. Uses exact matches if possible,
+/// otherwise tries variations, narrowed down by year when there's more than
+/// one candidate.
+pub fn candidate_editions<'a>(
+    editions: &Editions<'a>,
+    year: Option<i32>,
+) -> Vec<&'a ReporterEdition> {
+    let editions = if editions.exact.is_empty() {
+        &editions.variations
+    } else {
+        &editions.exact
+    };
+
+    match year {
+        // Attempt resolution by date
+        Some(year) if editions.len() > 1 => editions
+            .iter()
+            .copied()
+            .filter(|e| e.includes_year(year))
+            .collect(),
+        _ => editions.clone(),
+    }
+}
+
+/// Guess which edition a citation refers to, if there's only one candidate.
+pub fn guess_edition<'a>(
+    editions: &Editions<'a>,
+    year: Option<i32>,
+) -> Option<&'a ReporterEdition> {
+    match candidate_editions(editions, year).as_slice() {
+        [edition] => Some(edition),
+        _ => None,
+    }
+}
+
+/// How [`guess_edition`] chooses an edition for a citation, or why it
+/// can't.
+pub fn edition_choice(editions: &Editions, year: Option<i32>) -> EditionChoice {
+    match (editions.exact.as_slice(), editions.variations.as_slice()) {
+        ([_], _) => EditionChoice::ExactMatch,
+        ([], [_]) => EditionChoice::Unambiguous,
+        _ if guess_edition(editions, year).is_some() => EditionChoice::DateDisambiguated,
+        _ => EditionChoice::Ambiguous,
+    }
+}
+
+/// Record how the edition of each citation to a reporter was chosen. See
+/// [`edition_choice`].
+pub fn add_edition_choice_metadata(citations: &mut [Citation<'_>]) {
+    for citation in citations.iter_mut() {
+        let source = citation.source_mut();
+        let year = source.parsed_year().map(i32::from);
+        if let Some(editions) = source.editions() {
+            source
+                .metadata
+                .insert(CitationMetadata::EditionChoice(edition_choice(
+                    editions, year,
+                )));
+        }
+    }
+}
+
+/// Guess the court from the reporter. Only works for the Supreme Court,
+/// which lacks a court in the citation's parenthetical.
+pub fn guess_court(editions: &Editions) -> Option<&'static str> {
+    editions
+        .iter()
+        .any(|e| SCOTUS_REPORTERS.contains(&e.reporter.as_str()))
+        .then_some("scotus")
+}
+
+/// Return true if a citation might refer to more than one reporter and
+/// can't be narrowed down by date. Only citations to a reporter can be
+/// ambiguous.
+pub fn is_ambiguous(citation: &Citation) -> bool {
+    let source = citation.source();
+    source.extra().is_some() && source.edition_guess().is_none()
+}
+
+/// Filter out citations where there is more than one possible reporter.
+pub fn disambiguate_reporters(citations: Vec<Citation>) -> Vec<Citation> {
+    citations.into_iter().filter(|c| !is_ambiguous(c)).collect()
+}
+
+/// Keep citations where there is more than one possible reporter, but mark
+/// them as ambiguous and record every candidate edition in their metadata,
+/// so they can be disambiguated downstream.
+pub fn mark_ambiguous_reporters(mut citations: Vec<Citation>) -> Vec<Citation> {
+    for citation in citations.iter_mut().filter(|c| is_ambiguous(c)) {
+        let source = citation.source_mut();
+        let year = source.year().and_then(get_year);
+
+        if let Some(editions) = source.editions() {
+            for edition in candidate_editions(editions, year) {
+                source
+                    .metadata
+                    .insert(CitationMetadata::CandidateEdition(&edition.short_name));
+            }
+        }
+        source.metadata.insert(CitationMetadata::Ambiguous);
+    }
+
+    citations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cached_regex, compiled_regexes, highest_valid_year, parse_year, process_parenthetical,
+        quotation_spans, subslice_span,
+    };
+    use crate::find::models::{Attribution, EditionChoice, JudgeTitle, OpinionRole};
+    use crate::find::{get_citations, get_citations_with_options, Ambiguity, FindOptions};
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn parentheticals() {
+        assert_eq!(
+            process_parenthetical("overruling foo) (bar"),
+            Some("overruling foo")
+        );
+        assert_eq!(
+            process_parenthetical("something (clarifying something) or other"),
+            Some("something (clarifying something) or other")
+        );
+        assert_eq!(process_parenthetical("1999"), None);
+        assert_eq!(process_parenthetical(""), None);
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(parse_year("1973"), Some(1973));
+        assert_eq!(parse_year("1700"), Some(1700));
+        assert_eq!(parse_year("1699"), None);
+        assert_eq!(parse_year("19730"), None);
+        assert_eq!(parse_year("+1973"), None);
+        assert_eq!(parse_year(""), None);
+
+        let next_year = highest_valid_year();
+        assert_eq!(parse_year(&next_year.to_string()), Some(next_year as u16));
+        assert_eq!(parse_year(&(next_year + 1).to_string()), None);
+    }
+
+    #[test]
+    fn regexes_compiled_once() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 120 (1973) (holding so) \
+            [hereinafter Roe]; Roe, 410 U.S., at 115; Roe, supra, at 116; Id. \
+            See infra Part II. No. 19-1392, slip op. at 12 (U.S. June 1, 2020). \
+            42 U.S.C. § 1983 (2018).";
+
+        get_citations(text, false, &tokenizer);
+        let compiled = compiled_regexes();
+        for _ in 0..3 {
+            get_citations(text, false, &tokenizer);
+        }
+        assert_eq!(compiled_regexes(), compiled);
+
+        let pattern = r"(?x)^(?:regexes_compiled_once\n)";
+        assert_eq!(cached_regex(pattern).unwrap().as_str(), pattern);
+        cached_regex(pattern).unwrap();
+        assert!(cached_regex("(unclosed").is_err());
+        assert_eq!(compiled_regexes(), compiled + 1);
+    }
+
+    #[test]
+    fn metadata_spans() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113, 115 (1973) (holding that it is). Id. at 116. \
+            Roe, 410 U.S. at 117 (noting that it is).";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let slice = |span: Option<(usize, usize)>| span.map(|(start, end)| &text[start..end]);
+        let full = citations[0].source();
+        assert_eq!(slice(full.pin_cite_span()), Some("115"));
+        assert_eq!(slice(full.year_span()), Some("1973"));
+        assert_eq!(slice(full.parenthetical_span()), Some("holding that it is"));
+
+        let id = citations[1].source();
+        assert_eq!(slice(id.pin_cite_span()), id.pin_cite());
+        assert!(id
+            .pin_cite_span()
+            .is_some_and(|(start, _)| start > full.span().1));
+
+        let short = citations[2].source();
+        assert_eq!(slice(short.pin_cite_span()), Some("117"));
+        assert_eq!(slice(short.parenthetical_span()), Some("noting that it is"));
+
+        assert_eq!(subslice_span(text, &text[4..7]), Some((4, 7)));
+        assert_eq!(subslice_span(text, "Roe"), None);
+    }
+
+    #[test]
+    fn dispositions() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Smith v. Jones, 1 F.3d 1 (9th Cir. 1990) (en banc); \
+            Roe v. Wade, 410 U.S. 113, 120 (1973) (Rehnquist, J., dissenting); \
+            Doe v. Poe, 2 U.S. 2 (1800) (per curiam) (citing Roe); \
+            Moe v. Zoe, 3 U.S. 3 (1801) (Roberts, C. J., concurring in the judgment).";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let dispositions: Vec<_> = citations
+            .iter()
+            .map(|c| {
+                let source = c.source();
+                (source.disposition(), source.judge(), source.opinion_type())
+            })
+            .collect();
+        assert_eq!(
+            dispositions,
+            vec![
+                (Some("en banc"), None, None),
+                (None, Some("Rehnquist"), Some("dissenting")),
+                (Some("per curiam"), None, None),
+                (None, Some("Roberts"), Some("concurring in the judgment")),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributions() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U.S. 113 (1973) (Breyer, J., concurring in the judgment); \
+            Doe v. Poe, 2 U.S. 2 (1800) (Roberts, C. J., concurring in part and dissenting in part); \
+            Moe v. Zoe, 3 U.S. 3 (1801) (holding that x).";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let attributions: Vec<_> = citations.iter().map(|c| c.source().attribution()).collect();
+        assert_eq!(
+            attributions,
+            vec![
+                Some(Attribution {
+                    surname: "Breyer",
+                    title: Some(JudgeTitle::Justice),
+                    role: OpinionRole::ConcurringInJudgment,
+                }),
+                Some(Attribution {
+                    surname: "Roberts",
+                    title: Some(JudgeTitle::ChiefJustice),
+                    role: OpinionRole::ConcurringAndDissenting,
+                }),
+                None,
+            ]
+        );
+        assert_eq!(
+            OpinionRole::parse("dissenting in part"),
+            Some(OpinionRole::DissentingInPart)
+        );
+        assert_eq!(OpinionRole::parse("joining"), None);
+    }
+
+    #[test]
+    fn quoted_citations() {
+        let text = "He wrote: \"The court's view, 'in Smith, 1 U.S. 1, we held,' stands.\" \
+            See 2 U.S. 2. The ’90s saw “the rule of 3 U.S. 3.”";
+        let quoted = |(start, end): (usize, usize)| &text[start..end];
+        let spans: Vec<_> = quotation_spans(text).into_iter().map(quoted).collect();
+        assert_eq!(
+            spans,
+            vec![
+                "in Smith, 1 U.S. 1, we held,",
+                "The court's view, 'in Smith, 1 U.S. 1, we held,' stands.",
+                "the rule of 3 U.S. 3.",
+            ]
+        );
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations(text, false, &tokenizer);
+        let flags: Vec<_> = citations.iter().map(|c| c.source().is_quoted()).collect();
+        assert_eq!(flags, vec![true, false, true]);
+
+        let options = FindOptions {
+            exclude_quoted: true,
+            ..Default::default()
+        };
+        let citations = get_citations_with_options(text, &options, &tokenizer);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().groups["volume"], "2");
+    }
+
+    #[test]
+    fn edition_choices() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        // "W.2d" is a variation of both "Wis. 2d" and "Wash. 2d"
+        let text = "1 U.S. 1. 2 U. S. 2. 3 W.2d 3 (1854). 4 W.2d 4.";
+        let citations = get_citations(text, Ambiguity::Mark, &tokenizer);
+        let choices: Vec<_> = citations
+            .iter()
+            .map(|c| c.source().edition_choice())
+            .collect();
+        assert_eq!(
+            choices,
+            vec![
+                Some(EditionChoice::ExactMatch),
+                Some(EditionChoice::Unambiguous),
+                Some(EditionChoice::DateDisambiguated),
+                Some(EditionChoice::Ambiguous),
+            ]
+        );
+    }
+}
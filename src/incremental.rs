@@ -0,0 +1,217 @@
+/*!
+Update the citations of a document after a small edit, without finding the
+citations of the whole document again, for live citation linting in
+editors.
+
+[`update_citations`] widens the edit to the paragraphs around it, finds
+the citations of those paragraphs with
+[`get_citations_in_range`](crate::find::get_citations_in_range), and keeps
+the rest of the previous citations, moving those after the edit by how much
+longer or shorter the text got.
+
+Kept citations still borrow the previous text, which is the same as the new
+text around them, so it has to outlive the updated citations. Token indexes,
+and metadata pointing at them like
+[`StringCite`](crate::find::models::CitationMetadata::StringCite), aren't
+comparable across citations found by different runs.
+ */
+
+use crate::find::models::Citation;
+use crate::find::{get_citations_in_range, FindOptions};
+use crate::helpers;
+use crate::tokenizers::Tokenizer;
+use std::ops::Range;
+
+/// A replacement of part of a text.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TextEdit {
+    /// The bytes of the previous text that were replaced.
+    pub replaced: Range<usize>,
+    /// How many bytes long the text replacing them is.
+    pub inserted: usize,
+}
+
+impl TextEdit {
+    /// How many bytes longer the text got, or shorter if negative.
+    pub fn delta(&self) -> isize {
+        self.inserted as isize - self.replaced.len() as isize
+    }
+
+    /// Where the start of a span of the previous text is in the new text.
+    /// Spans starting in the replaced text start where the edit does.
+    fn map_start(&self, offset: usize) -> usize {
+        if offset < self.replaced.end {
+            offset.min(self.replaced.start)
+        } else {
+            offset.saturating_add_signed(self.delta())
+        }
+    }
+
+    /// Where the end of a span of the previous text is in the new text.
+    /// Spans ending in the replaced text end where the inserted text does.
+    fn map_end(&self, offset: usize) -> usize {
+        if offset <= self.replaced.start {
+            offset
+        } else {
+            offset
+                .max(self.replaced.end)
+                .saturating_add_signed(self.delta())
+        }
+    }
+}
+
+/// Update the citations found in a text after an edit, given the new text.
+///
+/// The paragraphs the edit touches, along with any previous citation
+/// overlapping them, are searched again. Previous citations before them are
+/// kept as they are, and those after them are moved by
+/// [`TextEdit::delta`]. The result is the same as finding the citations of
+/// the new text, as long as no citation's metadata reaches across a
+/// paragraph break.
+///
+/// An edit that doesn't fall on char boundaries of the new text is widened
+/// to the characters it cuts into.
+pub fn update_citations<'a>(
+    previous: Vec<Citation<'a>>,
+    edit: &TextEdit,
+    new_text: &'a str,
+    options: &FindOptions,
+    tokenizer: &'a dyn Tokenizer<'a>,
+) -> Vec<Citation<'a>> {
+    let edit_start = helpers::floor_char_boundary(new_text, edit.replaced.start);
+    let edit_end = helpers::ceil_char_boundary(
+        new_text,
+        edit.replaced
+            .start
+            .saturating_add(edit.inserted)
+            .min(new_text.len()),
+    );
+    let mut window = (
+        new_text[..edit_start].rfind("\n\n").map_or(0, |i| i + 2),
+        new_text[edit_end..]
+            .find("\n\n")
+            .map_or(new_text.len(), |i| edit_end + i),
+    );
+
+    // Spans of the previous citations in the new text
+    let spans: Vec<_> = previous
+        .iter()
+        .map(|citation| {
+            let (start, end) = citation.source().span();
+            (edit.map_start(start), edit.map_end(end))
+        })
+        .collect();
+
+    // Widen the window to take in every citation it overlaps, until it
+    // doesn't overlap any more
+    loop {
+        let widened = spans
+            .iter()
+            .filter(|&&(start, end)| start < window.1 && end > window.0)
+            .fold(window, |(low, high), &(start, end)| {
+                (low.min(start), high.max(end))
+            });
+        if widened == window {
+            break;
+        }
+        window = widened;
+    }
+
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for (mut citation, (start, end)) in previous.into_iter().zip(spans) {
+        if end <= window.0 {
+            before.push(citation);
+        } else if start >= window.1 {
+            citation.source_mut().shift(edit.delta());
+            after.push(citation);
+        }
+    }
+
+    before.extend(get_citations_in_range(
+        new_text,
+        window.0..window.1,
+        options,
+        tokenizer,
+    ));
+    before.extend(after);
+    before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{update_citations, TextEdit};
+    use crate::find::models::Citation;
+    use crate::find::{get_citations, FindOptions};
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn update_after_edits() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 1 U.S. 1 (1800).\n\nRoe v. Wade, 410 U.S. 113 (1973).\n\n\
+            Id. at 115. See 2 U.S. 2, 5.";
+        let previous = get_citations(text, false, &tokenizer);
+
+        // Replace "410 U.S. 113" with "505 U.S. 833, 840"
+        let start = text.find("410").unwrap();
+        let new_text = text.replacen("410 U.S. 113", "505 U.S. 833, 840", 1);
+        let edit = TextEdit {
+            replaced: start..start + "410 U.S. 113".len(),
+            inserted: "505 U.S. 833, 840".len(),
+        };
+        assert_eq!(edit.delta(), 5);
+
+        let updated = update_citations(
+            previous,
+            &edit,
+            &new_text,
+            &FindOptions::default(),
+            &tokenizer,
+        );
+        let expected = get_citations(&new_text, false, &tokenizer);
+        let spans = |citations: &[Citation]| -> Vec<_> {
+            citations.iter().map(|c| c.source().span()).collect()
+        };
+        assert_eq!(spans(&updated), spans(&expected));
+        assert_eq!(updated[1].source().groups["volume"], "505");
+        assert_eq!(updated[1].source().pin_cite(), Some("840"));
+        assert_eq!(
+            updated[3].source().pin_cite_span(),
+            expected[3].source().pin_cite_span()
+        );
+
+        // Deleting a whole citation drops it
+        let start = new_text.find("See 2").unwrap();
+        let end = new_text.len() - 1;
+        let shorter = format!("{}{}", &new_text[..start], &new_text[end..]);
+        let edit = TextEdit {
+            replaced: start..end,
+            inserted: 0,
+        };
+        let updated = update_citations(
+            updated,
+            &edit,
+            &shorter,
+            &FindOptions::default(),
+            &tokenizer,
+        );
+        assert_eq!(
+            spans(&updated),
+            spans(&get_citations(&shorter, false, &tokenizer))
+        );
+
+        // Offsets inside a character are widened to it
+        let text = "Café. See 1 U.S. 1.";
+        let previous = get_citations(text, false, &tokenizer);
+        let edit = TextEdit {
+            replaced: 4..5,
+            inserted: 1,
+        };
+        let updated = update_citations(previous, &edit, text, &FindOptions::default(), &tokenizer);
+        assert_eq!(
+            spans(&updated),
+            spans(&get_citations(text, false, &tokenizer))
+        );
+    }
+}
@@ -0,0 +1,158 @@
+/*!
+Label the words of a text with where citations are, in the BIO scheme
+named entity recognition models are trained on, so eyecite's extraction can
+generate silver labels for legal text.
+
+Words come from the word token stream of a
+[`Tokenizer`](crate::tokenizers::Tokenizer), without spaces. Words, and the
+tokens eyecite matched, like "410 U.S. 113", are split at whitespace, like
+line breaks, so every label is for a whitespace separated word. The first
+word of a citation is labeled `B-` and the rest `I-`, followed by the
+citation's kind, like `B-FullCase`, and words outside of citations are
+labeled `O`.
+ */
+
+use crate::find::models::{Citation, CitationBase, CitationKind};
+use crate::helpers::subslice_span;
+use crate::tokenizers::models::Token;
+use std::fmt;
+
+/// Where a word is relative to a citation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BioTag {
+    /// The first word of a citation.
+    Begin,
+    /// A word of a citation after the first.
+    Inside,
+    /// A word outside of any citation.
+    Outside,
+}
+
+/// The label of a word: where it is relative to a citation, and the kind
+/// of that citation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Label {
+    pub tag: BioTag,
+    /// The kind of citation the word is in, or None outside of citations.
+    pub kind: Option<CitationKind>,
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.tag, self.kind) {
+            (BioTag::Begin, Some(kind)) => write!(f, "B-{kind:?}"),
+            (BioTag::Inside, Some(kind)) => write!(f, "I-{kind:?}"),
+            _ => f.write_str("O"),
+        }
+    }
+}
+
+/// A word of the text along with its label.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct LabeledWord<'a> {
+    pub text: &'a str,
+    /// Start and stop offsets of the word in the text.
+    pub span: (usize, usize),
+    pub label: Label,
+}
+
+/// Label the words of a text, given its word token stream, like the first
+/// vector returned by [`Tokenizer::tokenize`](crate::tokenizers::Tokenizer::tokenize),
+/// and the citations found in it.
+///
+/// Words are labeled by the first citation whose
+/// [`span`](crate::find::models::CitationSource::span) they start in, so
+/// citations nested in the parenthetical of another are labeled as part of
+/// it. Unknown citations, which are section markers, aren't labeled.
+pub fn bio_labels<'a>(
+    text: &'a str,
+    words: &[Token<'a>],
+    citations: &[Citation],
+) -> Vec<LabeledWord<'a>> {
+    let spans: Vec<_> = citations
+        .iter()
+        .filter(|citation| citation.kind() != CitationKind::Unknown)
+        .map(|citation| (citation.source().span(), citation.kind()))
+        .collect();
+    let mut labeled = Vec::new();
+    // Index of the citation the previous word was in
+    let mut previous = None;
+
+    let pieces = words.iter().flat_map(|token| match token {
+        Token::Word(word) => word.split_whitespace().collect::<Vec<_>>(),
+        token => token
+            .try_data()
+            .map(|data| data.data.split_whitespace().collect())
+            .unwrap_or_default(),
+    });
+    for word in pieces {
+        let Some(span) = subslice_span(text, word) else {
+            continue;
+        };
+        let citation = spans
+            .iter()
+            .position(|&((start, end), _)| start <= span.0 && span.0 < end);
+
+        let label = match citation {
+            Some(i) => Label {
+                tag: if previous == Some(i) {
+                    BioTag::Inside
+                } else {
+                    BioTag::Begin
+                },
+                kind: Some(spans[i].1),
+            },
+            None => Label {
+                tag: BioTag::Outside,
+                kind: None,
+            },
+        };
+        previous = citation;
+        labeled.push(LabeledWord {
+            text: word,
+            span,
+            label,
+        });
+    }
+
+    labeled
+}
+
+/// Format labeled words in the CoNLL style most NER tooling reads: one word
+/// and its label per line, separated by a tab.
+pub fn to_conll(labeled: &[LabeledWord]) -> String {
+    labeled
+        .iter()
+        .map(|word| format!("{}\t{}\n", word.text, word.label))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bio_labels, to_conll};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, Tokenizer};
+
+    #[test]
+    fn labels() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See 410 U.S. 113. Id. at 115.";
+        let (words, _) = tokenizer.tokenize(text);
+        let citations = get_citations(text, false, &tokenizer);
+
+        let labeled = bio_labels(text, &words, &citations);
+        assert_eq!(
+            to_conll(&labeled),
+            "See\tO\n\
+            410\tB-FullCase\n\
+            U.S.\tI-FullCase\n\
+            113\tI-FullCase\n\
+            .\tO\n\
+            Id.\tB-Id\n\
+            at\tI-Id\n\
+            115.\tI-Id\n"
+        );
+        assert_eq!(labeled[1].span, (4, 7));
+    }
+}
@@ -1,10 +1,51 @@
+/*!
+A port of the Free Law Project's eyecite, which finds legal citations in
+text.
+
+# Panics
+
+Finding citations, and working with the citations found, doesn't panic on
+any text: malformed matches are skipped, and spans are sliced with checks.
+Library code can't `unwrap`, and a test runs the public functions over
+generated text. Extractors and tokenizers built from regexes that are
+passed in return an error if they don't compile. Only eyecite's own
+constant regexes are expected to compile, which every test run would
+catch.
+ */
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
+
 extern crate core;
 
 use thiserror::Error;
 
+pub mod annotate;
+pub mod bluebook;
+pub mod catalog;
+pub mod clean;
+pub mod confidence;
+pub mod diagnostics;
+pub mod diff;
+pub mod explain;
 pub mod find;
+pub mod footnotes;
+pub mod graph;
+pub mod helpers;
+pub mod incremental;
+pub mod labels;
+pub mod limits;
+pub mod names;
+pub mod offsets;
+pub mod paragraphs;
 pub mod regexes;
+pub mod report;
+pub mod resolve;
+pub mod sentences;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod style;
+pub mod suggest;
 pub mod tokenizers;
+pub mod validate;
 
 #[derive(Error, Debug)]
 pub enum EyeciteError {
@@ -18,13 +59,126 @@ pub enum EyeciteError {
         #[from]
         source: regex::Error,
     },
+    #[error("Input is {len} bytes long, more than the limit of {max}")]
+    InputTooLong { len: usize, max: usize },
+    #[error("Found more than the limit of {max} citations")]
+    TooManyCitations { max: usize },
+    #[error("An extractor found more than the limit of {max} matches")]
+    TooManyMatches { max: usize },
+    #[error("Extraction didn't finish before its deadline")]
+    DeadlineExceeded,
+    #[error("Regex has no reporter group followed by a page group: {regex}")]
+    MissingReporterPage { regex: String },
+    #[error("Error building the built-in extractors: {report}")]
+    Init {
+        report: tokenizers::extractors::InitReport,
+    },
+    #[error("Regex uses {feature}, which hyperscan doesn't support: {regex}")]
+    UnsupportedRegexFeature {
+        feature: &'static str,
+        regex: String,
+    },
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::clean::join_line_breaks;
+    use crate::find::{
+        get_citations, get_citations_from_bytes, get_citations_with_report, Ambiguity, FindOptions,
+    };
+    use crate::footnotes::{find_footnotes, FootnoteStyle};
+    use crate::graph::citation_graph;
+    use crate::resolve::resolve_citations;
+    use crate::style::CitationStyle;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::models::TokenKinds;
+    use crate::tokenizers::Ahocorasick;
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    /// Generate text from pieces of citations, with a fixed seed so
+    /// failures can be reproduced.
+    fn generated_texts(count: usize) -> Vec<String> {
+        const PIECES: [&str; 34] = [
+            "1",
+            "410",
+            "U.S.",
+            "F.",
+            "Supp.",
+            "2d",
+            " ",
+            " ",
+            ", ",
+            ".",
+            "(",
+            ")",
+            "[",
+            "]",
+            "\n",
+            "§",
+            "¶",
+            "Id.",
+            "supra",
+            "infra",
+            "at",
+            "v.",
+            "See",
+            "Roe",
+            "é",
+            "ñ",
+            "—",
+            "\"",
+            "[fn1]",
+            "No. 19-1392, slip op.",
+            "(1973)",
+            "R. at",
+            "-",
+            "١١٥",
+        ];
+
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..count)
+            .map(|_| {
+                let len = next() % 40;
+                (0..len)
+                    .map(|_| PIECES[(next() % PIECES.len() as u64) as usize])
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_panics() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let options = FindOptions {
+            ambiguous: Ambiguity::Mark,
+            kinds: TokenKinds::default() | TokenKinds::RECORD,
+            ..Default::default()
+        };
+
+        for text in generated_texts(2000) {
+            let citations = get_citations(&text, true, &tokenizer);
+            let resolutions = resolve_citations(&citations);
+            citation_graph(&citations, &resolutions);
+            for citation in &citations {
+                citation.source().full_text(&text);
+                citation.source().corrected_citation_in(CitationStyle::Alwd);
+            }
+
+            get_citations_with_report(&text, &options, &tokenizer);
+            get_citations_from_bytes(text.as_bytes(), &options, &tokenizer);
+            get_citations(&join_line_breaks(&text).text, false, &tokenizer);
+            find_footnotes(&text, FootnoteStyle::Bracketed);
+        }
+    }
 }
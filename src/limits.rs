@@ -0,0 +1,94 @@
+/*!
+Limits on the work done extracting citations from untrusted input.
+
+Extraction is linear in the size of the input, but documents can be
+arbitrarily large or packed with citation-like text. These limits make
+[`try_get_citations`](crate::find::try_get_citations) give up with an error
+instead of running unbounded.
+ */
+
+use crate::EyeciteError;
+use std::convert::Infallible;
+use std::time::Instant;
+
+/// Limits enforced by [`try_get_citations`](crate::find::try_get_citations).
+/// Every limit is off by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InputLimits {
+    /// Longest input, in bytes, to extract citations from.
+    pub max_input_len: Option<usize>,
+    /// Most citations to extract from one input.
+    pub max_citations: Option<usize>,
+    /// Most matches a single extractor may find in one input.
+    pub max_matches_per_extractor: Option<usize>,
+    /// Time by which extraction must be done. It is checked between matches
+    /// and citations, so extraction may overrun it slightly.
+    pub deadline: Option<Instant>,
+}
+
+impl InputLimits {
+    pub fn check_input_len(&self, len: usize) -> Result<(), EyeciteError> {
+        match self.max_input_len {
+            Some(max) if len > max => Err(EyeciteError::InputTooLong { len, max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_citations(&self, count: usize) -> Result<(), EyeciteError> {
+        match self.max_citations {
+            Some(max) if count > max => Err(EyeciteError::TooManyCitations { max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_matches(&self, count: usize) -> Result<(), EyeciteError> {
+        match self.max_matches_per_extractor {
+            Some(max) if count > max => Err(EyeciteError::TooManyMatches { max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_deadline(&self) -> Result<(), EyeciteError> {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => Err(EyeciteError::DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The limits checked while finding citations in tokens. Finding citations
+/// without limits checks [`Unlimited`], which can't fail, so there is no
+/// error to throw away.
+pub(crate) trait FindLimits {
+    type Error;
+
+    fn check_deadline(&self) -> Result<(), Self::Error>;
+
+    fn check_citations(&self, count: usize) -> Result<(), Self::Error>;
+}
+
+impl FindLimits for InputLimits {
+    type Error = EyeciteError;
+
+    fn check_deadline(&self) -> Result<(), EyeciteError> {
+        InputLimits::check_deadline(self)
+    }
+
+    fn check_citations(&self, count: usize) -> Result<(), EyeciteError> {
+        InputLimits::check_citations(self, count)
+    }
+}
+
+pub(crate) struct Unlimited;
+
+impl FindLimits for Unlimited {
+    type Error = Infallible;
+
+    fn check_deadline(&self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn check_citations(&self, _count: usize) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+/*!
+Normalize case names, so that antecedent guesses like "Smith" or
+"United States ex rel. Smith" can be compared against the parties of full
+citations. Resolution uses these to match short case and supra citations,
+so applications doing their own matching can stay consistent with it.
+ */
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    /// The common abbreviations, built once and shared by everything that
+    /// doesn't bring its own.
+    pub(crate) static ref DEFAULT_ABBREVIATIONS: Abbreviations = Abbreviations::default();
+    /// Procedural phrases before the name of the party that matters, like
+    /// "In re" or "United States ex rel."
+    static ref PROCEDURAL_PREFIX_RE: Regex = Regex::new(
+        r"(?ix)^(?:
+            in\s+re|
+            ex\s+parte|
+            (?:in\s+the\s+)?matter\s+of|
+            (?:united\s+states|u\.\s?s\.|state|people|commonwealth)\s+ex\s+rel\.?
+        )\s+"
+    )
+    .expect("name regexes should compile");
+    static ref ET_AL_RE: Regex =
+        Regex::new(r"(?i),?\s+et\s+al\.?").expect("name regexes should compile");
+    static ref UNITED_STATES_RE: Regex =
+        Regex::new(r"\bU\.\s?S\.(?:\s?A\.)?").expect("name regexes should compile");
+}
+
+/// Common abbreviations of words in party names, from the Bluebook's
+/// abbreviations for case names.
+const COMMON_ABBREVIATIONS: [(&str, &str); 28] = [
+    ("Ass'n", "Association"),
+    ("Bd.", "Board"),
+    ("Bros.", "Brothers"),
+    ("Co.", "Company"),
+    ("Comm'n", "Commission"),
+    ("Comm'r", "Commissioner"),
+    ("Corp.", "Corporation"),
+    ("Dep't", "Department"),
+    ("Dist.", "District"),
+    ("Educ.", "Education"),
+    ("Emps.", "Employees"),
+    ("Fed.", "Federal"),
+    ("Gov't", "Government"),
+    ("Hosp.", "Hospital"),
+    ("Inc.", "Incorporated"),
+    ("Ins.", "Insurance"),
+    ("Int'l", "International"),
+    ("Ltd.", "Limited"),
+    ("Mfg.", "Manufacturing"),
+    ("Mut.", "Mutual"),
+    ("Nat'l", "National"),
+    ("R.R.", "Railroad"),
+    ("Ry.", "Railway"),
+    ("Sch.", "School"),
+    ("Servs.", "Services"),
+    ("Twp.", "Township"),
+    ("Univ.", "University"),
+    ("Util.", "Utility"),
+];
+
+/// Abbreviations of words in party names mapped to what they stand for,
+/// like "Ins." to "Insurance", so that "Acme Ins." and "Acme Insurance"
+/// name the same party.
+///
+/// The default table holds common abbreviations; start from
+/// [`Abbreviations::empty`] to use only your own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Abbreviations {
+    expansions: HashMap<String, String>,
+}
+
+impl Default for Abbreviations {
+    fn default() -> Self {
+        let mut abbreviations = Self::empty();
+        for (abbreviation, expansion) in COMMON_ABBREVIATIONS {
+            abbreviations.insert(abbreviation, expansion);
+        }
+
+        abbreviations
+    }
+}
+
+impl Abbreviations {
+    pub fn empty() -> Self {
+        Self {
+            expansions: HashMap::new(),
+        }
+    }
+
+    /// Lookups ignore case and the kind of apostrophe used.
+    fn key(abbreviation: &str) -> String {
+        abbreviation.replace('’', "'").to_lowercase()
+    }
+
+    /// Add an abbreviation, or replace its expansion.
+    pub fn insert(&mut self, abbreviation: &str, expansion: &str) {
+        self.expansions
+            .insert(Self::key(abbreviation), expansion.to_string());
+    }
+
+    pub fn remove(&mut self, abbreviation: &str) -> Option<String> {
+        self.expansions.remove(&Self::key(abbreviation))
+    }
+
+    pub fn get(&self, abbreviation: &str) -> Option<&str> {
+        self.expansions
+            .get(&Self::key(abbreviation))
+            .map(String::as_str)
+    }
+
+    /// Replace each abbreviated word in a name with its expansion, like
+    /// "Bd. of Educ." with "Board of Education".
+    pub fn expand(&self, name: &str) -> String {
+        name.split_whitespace()
+            .map(|word| {
+                let abbreviation = word.trim_end_matches([',', ';', ':']);
+                match self.get(abbreviation) {
+                    Some(expansion) => format!("{}{}", expansion, &word[abbreviation.len()..]),
+                    None => word.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Normalize a case name, or part of one, for comparison:
+///
+/// * procedural phrases like "In re", "Ex parte" and "United States ex rel."
+///   are stripped from the start,
+/// * "et al." is dropped,
+/// * "U.S." is spelled out as "United States",
+/// * whitespace is collapsed, surrounding punctuation is trimmed, and the
+///   result is lowercased.
+///
+/// For example, "United States ex rel. Smith, et al." becomes "smith".
+pub fn normalize_case_name(name: &str) -> String {
+    let name = name.trim();
+    let name = PROCEDURAL_PREFIX_RE.replace(name, "");
+    let name = ET_AL_RE.replace_all(&name, "");
+    let name = UNITED_STATES_RE.replace_all(&name, "United States");
+
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
+/// Whether an antecedent guess, like "Adarand," refers to a party with the
+/// given name, i.e. its normalized form appears in the party's. Common
+/// abbreviations are expanded first, see [`antecedent_matches_with`].
+pub fn antecedent_matches(antecedent_guess: &str, party: &str) -> bool {
+    antecedent_matches_with(antecedent_guess, party, &DEFAULT_ABBREVIATIONS)
+}
+
+/// Like [`antecedent_matches`], expanding the given abbreviations in both
+/// names before comparing them.
+pub fn antecedent_matches_with(
+    antecedent_guess: &str,
+    party: &str,
+    abbreviations: &Abbreviations,
+) -> bool {
+    let antecedent_guess = normalize_case_name(&abbreviations.expand(antecedent_guess));
+
+    !antecedent_guess.is_empty()
+        && normalize_case_name(&abbreviations.expand(party)).contains(&antecedent_guess)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{antecedent_matches, antecedent_matches_with, normalize_case_name, Abbreviations};
+
+    #[test]
+    fn normalize_case_names() {
+        for (name, expected) in [
+            ("In re Gault", "gault"),
+            ("Ex parte  Milligan", "milligan"),
+            ("United States ex rel. Smith, et al.", "smith"),
+            ("U.S. v. Nixon", "united states v. nixon"),
+            ("Adarand,", "adarand"),
+            ("", ""),
+        ] {
+            assert_eq!(normalize_case_name(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn match_antecedents() {
+        assert!(antecedent_matches("Bar,", "Bar Corp."));
+        assert!(antecedent_matches("U.S.", "United States"));
+        assert!(antecedent_matches("in re Smith", "Smith et al."));
+        assert!(!antecedent_matches(",", "Bar"));
+        assert!(!antecedent_matches("Baz", "Bar"));
+    }
+
+    #[test]
+    fn expand_abbreviations() {
+        let mut abbreviations = Abbreviations::default();
+        assert_eq!(
+            abbreviations.expand("Bd. of Educ., Dep’t of Acme Ins. Co."),
+            "Board of Education, Department of Acme Insurance Company"
+        );
+        assert!(antecedent_matches("Acme Ins.", "Acme Insurance Co."));
+
+        abbreviations.insert("Amalg.", "Amalgamated");
+        abbreviations.remove("Ins.");
+        assert!(antecedent_matches_with(
+            "Amalg. Workers",
+            "Amalgamated Workers Union",
+            &abbreviations
+        ));
+        assert!(!antecedent_matches_with(
+            "Acme Ins.",
+            "Acme Insurance",
+            &abbreviations
+        ));
+        assert!(!antecedent_matches_with(
+            "Amalg.",
+            "Amalgamated",
+            &Abbreviations::empty()
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+/*!
+Conversion of spans from byte offsets into the UTF-8 text, which is what
+[`CitationSource::span`](crate::find::models::CitationSource::span) returns,
+into the char or UTF-16 code unit offsets that Python, JavaScript and most
+editor APIs use.
+ */
+
+/// How many bytes of text each checkpoint in an [`OffsetIndex`] covers.
+const BLOCK_SIZE: usize = 64;
+
+/// Precomputed index for converting byte offsets into a text to char or
+/// UTF-16 offsets.
+///
+/// Building the index is a single O(n) pass over the text. Each conversion
+/// after that only has to count within one block of [`BLOCK_SIZE`] bytes,
+/// so converting the spans of every citation in a document stays cheap.
+#[derive(Debug, Clone)]
+pub struct OffsetIndex<'a> {
+    text: &'a str,
+    /// Chars starting before each block.
+    chars: Vec<usize>,
+    /// UTF-16 code units of the chars starting before each block.
+    utf16: Vec<usize>,
+}
+
+/// Return true if the byte is the first byte of a char.
+fn starts_char(byte: u8) -> bool {
+    (byte as i8) >= -0x40
+}
+
+/// Number of UTF-16 code units for the char starting with this byte. Only
+/// chars encoded with four bytes in UTF-8 need a surrogate pair.
+fn utf16_units(byte: u8) -> usize {
+    match byte {
+        0xF0.. => 2,
+        _ if starts_char(byte) => 1,
+        _ => 0,
+    }
+}
+
+impl<'a> OffsetIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let blocks = text.len() / BLOCK_SIZE + 2;
+        let mut chars = Vec::with_capacity(blocks);
+        let mut utf16 = Vec::with_capacity(blocks);
+        let (mut char_count, mut utf16_count) = (0, 0);
+
+        for block in text.as_bytes().chunks(BLOCK_SIZE) {
+            chars.push(char_count);
+            utf16.push(utf16_count);
+            char_count += block.iter().filter(|&&b| starts_char(b)).count();
+            utf16_count += block.iter().map(|&b| utf16_units(b)).sum::<usize>();
+        }
+        // for offsets at the very end of the text
+        chars.push(char_count);
+        utf16.push(utf16_count);
+
+        Self { text, chars, utf16 }
+    }
+
+    /// The bytes from the start of `offset`'s block up to `offset`.
+    fn block_prefix(&self, offset: usize) -> &[u8] {
+        let offset = offset.min(self.text.len());
+        &self.text.as_bytes()[offset - offset % BLOCK_SIZE..offset]
+    }
+
+    /// Convert a byte offset to a char offset. Offsets past the end of the
+    /// text are clamped to it.
+    pub fn to_char(&self, offset: usize) -> usize {
+        let block = offset.min(self.text.len()) / BLOCK_SIZE;
+        let prefix = self.block_prefix(offset);
+
+        self.chars[block] + prefix.iter().filter(|&&b| starts_char(b)).count()
+    }
+
+    /// Convert a byte offset to a UTF-16 code unit offset. Offsets past the
+    /// end of the text are clamped to it.
+    pub fn to_utf16(&self, offset: usize) -> usize {
+        let block = offset.min(self.text.len()) / BLOCK_SIZE;
+        let prefix = self.block_prefix(offset);
+
+        self.utf16[block] + prefix.iter().map(|&b| utf16_units(b)).sum::<usize>()
+    }
+
+    /// Convert a span of byte offsets to char offsets.
+    pub fn char_span(&self, (start, end): (usize, usize)) -> (usize, usize) {
+        (self.to_char(start), self.to_char(end))
+    }
+
+    /// Convert a span of byte offsets to UTF-16 code unit offsets.
+    pub fn utf16_span(&self, (start, end): (usize, usize)) -> (usize, usize) {
+        (self.to_utf16(start), self.to_utf16(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OffsetIndex;
+
+    #[test]
+    fn convert_offsets() {
+        let text = "Adarand Constructors, Inc. v. Peña 🙂, ".repeat(5) + "515 U.S. 200";
+        let index = OffsetIndex::new(&text);
+
+        for (byte, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            let prefix = &text[..byte];
+            assert_eq!(index.to_char(byte), prefix.chars().count());
+            assert_eq!(index.to_utf16(byte), prefix.encode_utf16().count());
+        }
+
+        let start = text.find("515").unwrap();
+        let span = index.utf16_span((start, text.len()));
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        assert_eq!(
+            String::from_utf16(&utf16[span.0..span.1]).unwrap(),
+            "515 U.S. 200"
+        );
+    }
+}
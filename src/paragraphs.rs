@@ -0,0 +1,184 @@
+/*!
+Group citations by the paragraph, and optionally the sentence, they appear
+in, so document summarizers can list the authorities of each paragraph
+without finding the structure of the document again.
+
+Paragraphs are separated by the [`Token::Paragraph`] tokens of the word
+token stream, which are line breaks, and sentences are found the same way
+as while finding citations, so periods in reporters, like "Cal. App.",
+don't split them.
+ */
+
+use crate::find::models::Citation;
+use crate::helpers::Document;
+use crate::tokenizers::models::Token;
+
+/// A paragraph of a document, along with the citations in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paragraph<'c, 'a> {
+    /// Start and stop offsets of the paragraph, without surrounding
+    /// whitespace.
+    pub span: (usize, usize),
+    pub text: &'a str,
+    /// Citations starting in the paragraph, in the order they were found.
+    pub citations: Vec<&'c Citation<'a>>,
+    /// Sentences of the paragraph, if they were asked for.
+    pub sentences: Vec<Sentence<'c, 'a>>,
+}
+
+/// A sentence of a paragraph, along with the citations in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sentence<'c, 'a> {
+    /// Start and stop offsets of the sentence, without surrounding
+    /// whitespace.
+    pub span: (usize, usize),
+    pub text: &'a str,
+    /// Citations starting in the sentence, in the order they were found.
+    pub citations: Vec<&'c Citation<'a>>,
+}
+
+/// The span without the whitespace around it, or None if it is blank.
+fn trim(text: &str, (start, end): (usize, usize)) -> Option<(usize, usize)> {
+    let slice = &text[start..end];
+    let trimmed = slice.trim_start();
+    let start = start + slice.len() - trimmed.len();
+    let trimmed = trimmed.trim_end();
+    (!trimmed.is_empty()).then_some((start, start + trimmed.len()))
+}
+
+/// Citations whose [`span`](crate::find::models::CitationSource::span)
+/// starts within `span`.
+fn within<'c, 'a>(
+    citations: &'c [Citation<'a>],
+    (start, end): (usize, usize),
+) -> Vec<&'c Citation<'a>> {
+    citations
+        .iter()
+        .filter(|citation| (start..end).contains(&citation.source().span().0))
+        .collect()
+}
+
+/// Split a paragraph into sentences, given where sentences start in the
+/// whole document.
+fn split_sentences<'c, 'a>(
+    text: &'a str,
+    (start, end): (usize, usize),
+    sentence_starts: &[usize],
+    citations: &[&'c Citation<'a>],
+) -> Vec<Sentence<'c, 'a>> {
+    let starts = sentence_starts
+        .iter()
+        .copied()
+        .filter(|&offset| start < offset && offset < end);
+    let ends = starts.clone().chain(std::iter::once(end));
+
+    std::iter::once(start)
+        .chain(starts)
+        .zip(ends)
+        .filter_map(|span| trim(text, span))
+        .map(|span| Sentence {
+            span,
+            text: &text[span.0..span.1],
+            citations: citations
+                .iter()
+                .copied()
+                .filter(|citation| (span.0..span.1).contains(&citation.source().span().0))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Group the citations found in a text by paragraph, given its word token
+/// stream, like the first vector returned by
+/// [`Tokenizer::tokenize`](crate::tokenizers::Tokenizer::tokenize), and the
+/// citations found in it. Blank paragraphs are left out, and paragraphs
+/// without citations are kept, so paragraphs can be numbered. If
+/// `sentences` is true, each paragraph is also split into sentences.
+pub fn group_by_paragraph<'c, 'a>(
+    text: &'a str,
+    words: &[Token<'a>],
+    citations: &'c [Citation<'a>],
+    sentences: bool,
+) -> Vec<Paragraph<'c, 'a>> {
+    let document = sentences.then(|| Document::new(text, words));
+    let breaks = words
+        .iter()
+        .filter(|token| matches!(token, Token::Paragraph(_)))
+        .map(|token| (token.start(), token.end()))
+        .chain(std::iter::once((text.len(), text.len())));
+
+    let mut start = 0;
+    let mut paragraphs = Vec::new();
+    for (break_start, break_end) in breaks {
+        let span = (start, break_start);
+        start = break_end;
+        let Some(span) = trim(text, span) else {
+            continue;
+        };
+
+        let citations = within(citations, span);
+        let sentences = document
+            .as_ref()
+            .map(|document| split_sentences(text, span, document.sentence_starts(), &citations))
+            .unwrap_or_default();
+        paragraphs.push(Paragraph {
+            span,
+            text: &text[span.0..span.1],
+            citations,
+            sentences,
+        });
+    }
+
+    paragraphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::group_by_paragraph;
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, Tokenizer};
+
+    #[test]
+    fn paragraphs() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973). It was decided.\n\n\
+            Nothing here.\n\
+            See 1 Cal. App. 4th 1 (1991). Id. at 2.";
+        let (words, _) = tokenizer.tokenize(text);
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+
+        let paragraphs = group_by_paragraph(text, &words, &citations, false);
+        let counts: Vec<_> = paragraphs.iter().map(|p| p.citations.len()).collect();
+        assert_eq!(counts, vec![1, 0, 2]);
+        assert_eq!(paragraphs[1].text, "Nothing here.");
+        assert!(paragraphs[0].sentences.is_empty());
+
+        let paragraphs = group_by_paragraph(text, &words, &citations, true);
+        let sentences: Vec<Vec<_>> = paragraphs
+            .iter()
+            .map(|p| {
+                p.sentences
+                    .iter()
+                    .map(|s| (s.text, s.citations.len()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            sentences,
+            vec![
+                vec![
+                    ("Roe v. Wade, 410 U.S. 113 (1973).", 1),
+                    ("It was decided.", 0)
+                ],
+                vec![("Nothing here.", 0)],
+                vec![("See 1 Cal. App. 4th 1 (1991).", 1), ("Id. at 2.", 1)],
+            ]
+        );
+        assert!(std::ptr::eq(
+            paragraphs[2].sentences[1].citations[0],
+            &citations[2]
+        ));
+    }
+}
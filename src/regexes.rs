@@ -1,9 +1,30 @@
 /*!
 # *** Tokenizer regexes: ***
 Regexes used from tokenizers.py
+
+# Building custom extractors
+
+The helpers that wrap and rewrite the reporter regexes are public, so custom
+extractors can be built from the same pieces:
+
+- [`nonalphanum_boundaries_re`] and [`space_boundaries_re`] wrap a regex so
+  it only matches between boundaries, with the match in group 1, which is
+  what [`TokenExtractor`](crate::tokenizers::extractors::TokenExtractor)
+  takes as the token.
+- [`strip_punctuation_re`] allows punctuation around a word, like "supra,".
+- [`short_cite_re`] and [`nominative_cite_re`] rewrite a full citation regex
+  into the short form, like "1 U.S., at 5", and the form with a nominative
+  reporter, like "17 U.S. (4 Wheat.) 316".
+
+Each has a `try_` variant that checks the regex it returns compiles, and,
+for the rewrites, that there was a reporter and page to rewrite.
  */
 
+use crate::EyeciteError;
 use const_format::formatcp;
+use lazy_static::lazy_static;
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
 use reporters_db::regexes::ResolvedRegex;
 
 /**!
@@ -34,6 +55,41 @@ pub const PAGE_NUMBER_REGEX: &str = formatcp!(r"(?:\d+|{})", ROMAN_NUMERAL_REGEX
 
 pub const PAGE_REGEX: &str = formatcp!("(?P<page>{})", PAGE_NUMBER_REGEX);
 
+/// Paragraph number regex for looseleaf reporters, which number paragraphs
+/// instead of pages and may use a thousands separator. E.g. "12,345"
+pub const PARAGRAPH_PAGE_REGEX: &str = r"(?P<page>\d{1,3}(?:,\d{3})+|\d+)";
+
+/// Page regex for editions paginated by two numbers joined by a hyphen,
+/// like the "12-345" in "2 Tax Ct. Rep. (CCH) 12-345".
+pub const HYPHENATED_PAGE_REGEX: &str = r"(?P<page>\d+-\d+)";
+
+/// Citation to a Supreme Court opinion whose volume of the United States
+/// Reports isn't published yet, like "___ U.S. ___". It is usually followed
+/// by a parallel citation to the Supreme Court Reporter, like
+/// "___ U.S. ___, 141 S. Ct. 1163".
+pub const PLACEHOLDER_CITE_REGEX: &str =
+    r"(?P<volume>_{2,}) (?P<reporter>U\.S\.),? (?P<page>_{2,})";
+
+/// Shape of a full citation to any reporter, like "1 SO.2d 5", for finding
+/// citations whose reporter string is misspelled. See
+/// [`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer).
+pub const FUZZY_CITATION_REGEX: &str =
+    r"\b(?P<volume>\d{1,4}) (?P<reporter>[A-Za-z][A-Za-z0-9.,'& ]{2,30}?),? (?P<page>\d{1,6})\b";
+
+/// A volume and reporter without a page, like "410 U.S. (1973)", followed
+/// by a parenthetical, the end of a string cite or the end of the text. The
+/// reporter is only a candidate; see [`Suggester`](crate::suggest::Suggester).
+pub const MISSING_PAGE_REGEX: &str =
+    r"\b(?P<volume>\d{1,4}) (?P<reporter>[A-Za-z][A-Za-z0-9.' ]{0,30}?\.),?(?: ?\(|;|\s*$)";
+
+/// Volume regex for reporters that number opinions by year instead of by
+/// volume, like the "2020" in "T.C. Memo. 2020-123".
+pub const YEAR_VOLUME_REGEX: &str = r"(?P<volume>1[6-9]\d{2}|20\d{2})";
+
+/// Volume regex for reporters that are often cited without a volume, like
+/// "Bankr. L. Rep. (CCH) ¶ 12,345". When missing there is no volume group.
+pub const OPTIONAL_VOLUME_REGEX: &str = r"(?:(?P<volume>\d+) )?";
+
 /// Wrap regex with space or end of string.
 macro_rules! space_boundaries_re {
     ($regex:expr) => {
@@ -53,13 +109,65 @@ macro_rules! strip_punctuation_re {
 }
 
 /// Regex for IdToken
-pub const ID_REGEX: &str = space_boundaries_re!(r"id\.,?|ibid\.");
+pub const ID_REGEX: &str = space_boundaries_re!(r"(?P<id>id\.,?|ibid\.)");
+
+/// Regexes for sloppy forms of "id." and "ibid." that OCR and careless
+/// drafting produce, which [`ID_REGEX`] doesn't match. Each has an `id`
+/// group holding the token. Add them to extractors with
+/// [`FilteredExtractors::with_id_variants`](crate::tokenizers::extractors::FilteredExtractors::with_id_variants).
+pub const ID_VARIANT_REGEXES: [&str; 3] = [
+    // "Id;" and "Id:", with the period misread or mistyped
+    space_boundaries_re!(r"(?P<id>id[;:])"),
+    // "id at 5", without the period. "Id" is also a word, so only before a
+    // pin cite.
+    r"(?:^|\s)(?P<id>id),?\ at\ \d",
+    // "IBID" and "ibid,", without the period
+    space_boundaries_re!(r"(?P<id>ibid,?)"),
+];
+
+/// Regex for a bare pin cite at the start of a sentence, like "At 745, the
+/// Court ...", which refers to the citation immediately before it like an
+/// IdToken.
+pub const AT_PIN_CITE_REGEX: &str =
+    r#"(?:^|[.!?]["'”’)\]]*\s+)(?P<id>At (?P<pin_cite>\d+(?:[-–]\d+)?))[,:]"#;
 
 /// Regex for SupraToken
-pub const SUPRA_REGEX: &str = space_boundaries_re!(strip_punctuation_re!("supra"));
+pub const SUPRA_REGEX: &str =
+    space_boundaries_re!(formatcp!("(?P<supra>{})", strip_punctuation_re!("supra")));
+
+/// Regex for InfraToken
+pub const INFRA_REGEX: &str =
+    space_boundaries_re!(formatcp!("(?P<infra>{})", strip_punctuation_re!("infra")));
+
+/// Regex for CrossReferenceToken, a bare reference to another part of the
+/// document like "Part II.A" or "Section III". Only roman numerals are
+/// matched, so statutes like "Section 1983" aren't.
+pub const CROSS_REFERENCE_REGEX: &str =
+    r"\b((?:Parts?|Sections?) (?P<reference>[IVX]+(?:\.[A-Z0-9]+)*))\b";
+
+/// Regex for RecordToken, a citation to the record of a case, like
+/// "R. at 45", "J.A. 123", "ECF No. 27 at 3" or "Ex. 14 at 2". Numbered
+/// documents may be cited without a page, but bare labels like "R." need
+/// one, so those are captured as `bare_document` and `bare_page`.
+pub const RECORD_REGEX: &str = r"(?x)
+    (?:^|[^\w.])
+    (
+        (?:
+            (?P<document>
+                (?:ECF|Dkt\.|Doc\.)\ No\.\ \d+|
+                Exh?\.\ [\w\-]*\d[\w\-]*
+            )
+            (?:,?\ (?:at\ )?(?P<page>\d+(?:[-–]\d+)?))?
+        |
+            (?P<bare_document>R\.|J\.A\.|S\.A\.|Tr\.)
+            ,?\ (?:at\ )?(?P<bare_page>\d+(?:[-–]\d+)?)
+        )
+    )
+    \b
+";
 
 /// Regex for ParagraphToken
-pub const PARAGRAPH_REGEX: &str = r"(\n)";
+pub const PARAGRAPH_REGEX: &str = r"(?P<paragraph>\n)";
 
 /// Wrap regex with punctuation pattern.
 macro_rules! join_with {
@@ -105,37 +213,222 @@ pub const STOP_WORDS: [&str; 11] = [
     "dismissed",
 ];
 
-/// Regex for StopWordToken
+/// Regex for the multi-word stop words of StopWordToken, escaped.
+pub const STOP_PHRASES_JOINED: &str = join_with!(
+    "|",
+    [
+        r"see also",
+        r"see generally",
+        r"but see",
+        r"but cf\.",
+        r"cert\. denied",
+        r"cert\. granted",
+        r"reh'g denied",
+        r"rev'd on other grounds",
+        r"aff'd on other grounds",
+        r"vacated on other grounds",
+        r"overruled on other grounds",
+    ]
+);
+
+/// Signals and subsequent history of more than one word, like "see also"
+/// and "rev'd on other grounds", which are stop words as a whole.
+pub const STOP_PHRASES: [&str; 11] = [
+    "see also",
+    "see generally",
+    "but see",
+    "but cf.",
+    "cert. denied",
+    "cert. granted",
+    "reh'g denied",
+    "rev'd on other grounds",
+    "aff'd on other grounds",
+    "vacated on other grounds",
+    "overruled on other grounds",
+];
+
+/// Regex for StopWordToken. Phrases come first, so "see also" isn't only
+/// matched as "see".
 pub const STOP_WORD_REGEX: &str = space_boundaries_re!(strip_punctuation_re!(formatcp!(
-    r"(?P<stop_word>{})",
+    r"(?P<stop_word>{}|{})",
+    STOP_PHRASES_JOINED,
     STOP_WORDS_JOINED
 )));
 
 /// Regex for SectionToken
-pub const SECTION_REGEX: &str = r"(\S*§\S*)";
+pub const SECTION_REGEX: &str = r"(?P<section>\S*§\S*)";
+
+/// Regex for SlipOpinionToken, like "No. 19-1392, slip op. at 12". The date
+/// parenthetical that follows is captured by [`SLIP_OPINION_DATE_REGEX`].
+pub const SLIP_OPINION_REGEX: &str =
+    r"(No\. (?P<docket_number>[^,\s]+),? slip op\.(?:,? at (?P<pin_cite>\d+(?:[-–]\d+)?))?)";
+
+/// A character that isn't a letter or a digit in any script, so accented
+/// letters, like the "ñ" in "Peña", don't end a word.
+pub const NONALPHANUM_REGEX: &str = r"[^\p{L}\p{N}]";
 
 /// Regex to match punctuation around volume numbers and stopwords.
 /// This could potentially be more precise.
-pub const PUNCTUATION_REGEX: &str = r"[^\sa-zA-Z0-9]*";
+pub const PUNCTUATION_REGEX: &str = r"[^\s\p{L}\p{N}]*";
 
-/// Wrap regex to require non-alphanumeric characters on left and right.
+/// Wrap regex to require non-alphanumeric characters, see
+/// [`NONALPHANUM_REGEX`], on left and right.
 pub fn nonalphanum_boundaries_re(regex: &ResolvedRegex) -> ResolvedRegex {
     ResolvedRegex::of(format!(
-        r"(?:^|[^a-zA-Z0-9])({})(?:[^a-zA-Z0-9]|$)",
+        r"(?:^|{NONALPHANUM_REGEX})({})(?:{NONALPHANUM_REGEX}|$)",
         regex.value()
     ))
 }
 
+/// Like [`nonalphanum_boundaries_re`], checking that the result compiles.
+pub fn try_nonalphanum_boundaries_re(regex: &ResolvedRegex) -> Result<ResolvedRegex, EyeciteError> {
+    checked(nonalphanum_boundaries_re(regex))
+}
+
+/// Wrap regex to require whitespace or the edge of the text on left and
+/// right. The same as `space_boundaries_re!`, which builds the constant
+/// regexes of this module.
+pub fn space_boundaries_re(regex: &str) -> ResolvedRegex {
+    ResolvedRegex::of(format!(r"(?:^|\s)({})(?:\s|$)", regex))
+}
+
+/// Like [`space_boundaries_re`], checking that the result compiles.
+pub fn try_space_boundaries_re(regex: &str) -> Result<ResolvedRegex, EyeciteError> {
+    checked(space_boundaries_re(regex))
+}
+
+/// Allow any punctuation, see [`PUNCTUATION_REGEX`], around a regex. The
+/// same as `strip_punctuation_re!`.
+pub fn strip_punctuation_re(regex: &str) -> ResolvedRegex {
+    ResolvedRegex::of(format!("{PUNCTUATION_REGEX}{regex}{PUNCTUATION_REGEX}"))
+}
+
+/// Like [`strip_punctuation_re`], checking that the result compiles.
+pub fn try_strip_punctuation_re(regex: &str) -> Result<ResolvedRegex, EyeciteError> {
+    checked(strip_punctuation_re(regex))
+}
+
+/// Check that a regex built by one of the helpers compiles.
+fn checked(regex: ResolvedRegex) -> Result<ResolvedRegex, EyeciteError> {
+    regex::Regex::new(regex.value())?;
+    Ok(regex)
+}
+
+/// Check that a rewrite of `regex` changed something and compiles.
+fn checked_rewrite(regex: &str, rewritten: ResolvedRegex) -> Result<ResolvedRegex, EyeciteError> {
+    if rewritten.value() == regex {
+        return Err(EyeciteError::MissingReporterPage {
+            regex: regex.to_string(),
+        });
+    }
+    checked(rewritten)
+}
+
 /// Convert a full citation regex into a short citation regex.
 ///
 /// Currently this just means we turn
 /// > '(?P<reporter>...),? (?P<page>...'
 /// to
 /// > '(?P<reporter>...),? at (?P<page>...'
+///
+/// Extractors built from `reporters_db` rewrite the templates with
+/// [`short_cite_template`] instead, before they're resolved.
+pub fn short_cite_re(regex: &str) -> ResolvedRegex {
+    replace_reporter_page(regex, r"$1,? at $2")
+}
+
+lazy_static! {
+    /// A reporter and page next to each other in a regex template.
+    static ref TEMPLATE_REPORTER_PAGE_RE: Regex =
+        Regex::new(r"(\$\{?reporter\}?)(?:,\?)? (\$\{?page\w*\}?)")
+            .expect("template regexes should compile");
+    /// A reference to a full citation variable in a regex template.
+    static ref TEMPLATE_FULL_CITE_RE: Regex =
+        Regex::new(r"\$\{?full_cite(\w*)\}?").expect("template regexes should compile");
+}
+
+/// Convert a full citation regex template from `reporters_db`, like
+/// "$volume $reporter,? $page", into a short citation template, like
+/// "$volume $reporter,? at $page", before its variables are resolved.
+///
+/// References to full citation variables, like "$full_cite_year_page",
+/// become references to the matching short citation variable, like
+/// "$short_cite_year_page", if `has_short_variable` says it exists. Returns
+/// None if the template has no short form, like paragraph citations.
+///
+/// Unlike [`short_cite_re`], this works on the templates, which are short
+/// and use the same variables throughout, rather than on resolved regexes
+/// whose groups could be written any number of ways.
+pub fn short_cite_template(
+    template: &str,
+    has_short_variable: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let short = TEMPLATE_REPORTER_PAGE_RE.replace_all(template, "$1,? at $2");
+    let short = TEMPLATE_FULL_CITE_RE.replace_all(&short, |captures: &regex::Captures| {
+        let name = format!("short_cite{}", &captures[1]);
+        if has_short_variable(&name) {
+            format!("${name}")
+        } else {
+            captures[0].to_string()
+        }
+    });
+
+    (short != template).then(|| short.into_owned())
+}
+
+/// Like [`short_cite_re`], failing if the regex has no reporter group
+/// followed by a page group to rewrite, or the result doesn't compile.
+pub fn try_short_cite_re(regex: &str) -> Result<ResolvedRegex, EyeciteError> {
+    checked_rewrite(regex, short_cite_re(regex))
+}
+
+/// A nominative reporter in parentheses between the official reporter and
+/// page, like the "(4 Wheat.)" in "17 U.S. (4 Wheat.) 316".
+pub const NOMINATIVE_REGEX: &str =
+    r"\((?P<volume_nominative>\d{1,3}) (?P<reporter_nominative>[A-Z][A-Za-z.&' ]*[A-Za-z.])\),? ";
+
+/// Convert a full citation regex into one for citations that also give the
+/// nominative reporter, by inserting [`NOMINATIVE_REGEX`] between the
+/// reporter and page groups.
+pub fn nominative_cite_re(regex: &str) -> ResolvedRegex {
+    replace_reporter_page(regex, &format!("$1,? {}$2", NOMINATIVE_REGEX))
+}
+
+/// Like [`nominative_cite_re`], failing if the regex has no reporter group
+/// followed by a page group to rewrite, or the result doesn't compile.
+pub fn try_nominative_cite_re(regex: &str) -> Result<ResolvedRegex, EyeciteError> {
+    checked_rewrite(regex, nominative_cite_re(regex))
+}
+
+/// Regex matching a reporter abbreviation with its periods optional and
+/// optional spaces after them, so "U.S." also matches "U. S.", "U.S" and
+/// "US", and "F. Supp." matches "F.Supp.". Spaces that don't follow a period
+/// are still required.
+pub fn spacing_tolerant_reporter(reporter: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = reporter.chars().peekable();
+    let mut after_period = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                regex.push_str(r"\.?");
+                if chars.peek().is_some_and(|next| *next != ' ') {
+                    regex.push_str(" ?");
+                }
+            }
+            ' ' if after_period => regex.push_str(" ?"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        after_period = c == '.';
+    }
+
+    regex
+}
 
 // clippy doesn't like '\ ' but i think its ok since it is set to ignore whitespace
 #[allow(clippy::invalid_regex)]
-pub fn short_cite_re(regex: &str) -> ResolvedRegex {
+fn replace_reporter_page(regex: &str, replacement: &str) -> ResolvedRegex {
     let replaced = regex::RegexBuilder::new(
         r#"# reporter group:
             (
@@ -149,8 +442,812 @@ pub fn short_cite_re(regex: &str) -> ResolvedRegex {
     )
     .ignore_whitespace(true)
     .build()
-    .unwrap()
-    .replace_all(regex, r"$1,? at $2");
+    .expect("reporter page regex should compile")
+    .replace_all(regex, replacement);
 
     ResolvedRegex::of(replaced.to_string())
 }
+
+/// Strings, one of which appears in every match of a regex, like
+/// `[" U.S. "]` for `(?P<volume>\d+) (?P<reporter>U\.S\.) (?P<page>\d+)`.
+///
+/// Used to derive trigger strings for extractors that don't have any, so a
+/// prefiltering tokenizer still runs them when they might match. Returns
+/// None if the regex can't be parsed or no literal is required, like for
+/// `\d+`.
+pub fn required_literals(regex: &str) -> Option<Vec<String>> {
+    let mut strings = match literals_of(&regex_syntax::parse(regex).ok()?)? {
+        Literals::Exact(strings) | Literals::Required(strings) => strings,
+    };
+
+    if strings.iter().any(String::is_empty) {
+        return None;
+    }
+    let mut seen = std::collections::HashSet::new();
+    strings.retain(|s| seen.insert(s.clone()));
+
+    Some(strings)
+}
+
+/// Most strings to build when combining the literals of a concatenation.
+const MAX_LITERALS: usize = 64;
+
+/// What a regex requires of its matches.
+enum Literals {
+    /// Every match is one of these strings.
+    Exact(Vec<String>),
+    /// Every match contains one of these strings.
+    Required(Vec<String>),
+}
+
+/// Of two sets of required strings, keep the one whose shortest string is
+/// longest, since it will be found the least often.
+fn better_literals(a: Option<Vec<String>>, b: Option<Vec<String>>) -> Option<Vec<String>> {
+    let score = |strings: &Option<Vec<String>>| {
+        strings
+            .as_ref()
+            .and_then(|strings| strings.iter().map(|s| s.chars().count()).min())
+            .unwrap_or_default()
+    };
+
+    if score(&b) > score(&a) {
+        b
+    } else {
+        a
+    }
+}
+
+fn literals_of(hir: &Hir) -> Option<Literals> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Some(Literals::Exact(vec![String::new()])),
+        HirKind::Literal(literal) => {
+            let literal = std::str::from_utf8(&literal.0).ok()?;
+            Some(Literals::Exact(vec![literal.to_string()]))
+        }
+        HirKind::Class(_) => None,
+        HirKind::Capture(capture) => literals_of(&capture.sub),
+        HirKind::Repetition(repetition) => match literals_of(&repetition.sub)? {
+            Literals::Exact(strings) if repetition.max == Some(1) => {
+                let mut strings = strings;
+                if repetition.min == 0 {
+                    strings.push(String::new());
+                }
+                Some(Literals::Exact(strings))
+            }
+            Literals::Exact(strings) | Literals::Required(strings) if repetition.min > 0 => {
+                Some(Literals::Required(strings))
+            }
+            _ => None,
+        },
+        // Runs of parts with exact strings combine into longer strings, and
+        // any part's required strings are required by the whole
+        HirKind::Concat(parts) => {
+            let mut best = None;
+            let mut run = vec![String::new()];
+            let mut exact = true;
+
+            for part in parts {
+                match literals_of(part) {
+                    Some(Literals::Exact(strings)) if run.len() * strings.len() <= MAX_LITERALS => {
+                        run = run
+                            .iter()
+                            .flat_map(|prefix| strings.iter().map(move |s| format!("{prefix}{s}")))
+                            .collect();
+                    }
+                    Some(Literals::Exact(strings)) => {
+                        best = better_literals(best, Some(run));
+                        run = strings;
+                        exact = false;
+                    }
+                    other => {
+                        best = better_literals(best, Some(run));
+                        if let Some(Literals::Required(strings)) = other {
+                            best = better_literals(best, Some(strings));
+                        }
+                        run = vec![String::new()];
+                        exact = false;
+                    }
+                }
+            }
+
+            if exact {
+                return Some(Literals::Exact(run));
+            }
+            better_literals(best, Some(run)).map(Literals::Required)
+        }
+        // Any branch could match, so one of each branch's strings is needed
+        HirKind::Alternation(branches) => {
+            let mut strings = Vec::new();
+            let mut exact = true;
+            for branch in branches {
+                match literals_of(branch)? {
+                    Literals::Exact(branch) => strings.extend(branch),
+                    Literals::Required(branch) => {
+                        strings.extend(branch);
+                        exact = false;
+                    }
+                }
+            }
+
+            Some(if exact {
+                Literals::Exact(strings)
+            } else {
+                Literals::Required(strings)
+            })
+        }
+    }
+}
+
+/// Which regex engines the regexes of extractors have to work with.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum RegexMode {
+    /// Anything the regex crate supports.
+    #[default]
+    Rust,
+    /// Only features hyperscan also supports, so a hyperscan tokenizer can
+    /// use the same extractors. See [`check_hyperscan_compatible`].
+    Hyperscan,
+}
+
+impl RegexMode {
+    /// Check that a regex only uses features this mode allows.
+    pub fn check(self, regex: &str) -> Result<(), EyeciteError> {
+        match self {
+            RegexMode::Rust => Ok(()),
+            RegexMode::Hyperscan => check_hyperscan_compatible(regex),
+        }
+    }
+}
+
+/// Check that a regex only uses features both the regex crate and
+/// hyperscan support.
+///
+/// Neither supports lookarounds or backreferences, but hyperscan also
+/// rejects PCRE features the regex crate would fail to parse anyway, like
+/// atomic groups, and doesn't know the regex crate's extensions, like the
+/// `U` flag, `\<` word boundaries and set operations in classes. Comments in
+/// verbose regexes are checked too, so they shouldn't mention these.
+pub fn check_hyperscan_compatible(regex: &str) -> Result<(), EyeciteError> {
+    let unsupported = |feature| {
+        Err(EyeciteError::UnsupportedRegexFeature {
+            feature,
+            regex: regex.to_string(),
+        })
+    };
+
+    let mut chars = regex.chars().peekable();
+    let mut in_class = false;
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('1'..='9' | 'k' | 'g') => return unsupported("a backreference"),
+                Some('G' | 'K') => return unsupported("a PCRE assertion"),
+                Some('<' | '>') => return unsupported("a word boundary extension"),
+                Some('b' | 'B') if chars.peek() == Some(&'{') => {
+                    return unsupported("a word boundary extension")
+                }
+                _ => {}
+            },
+            '[' if in_class && chars.peek() != Some(&':') => return unsupported("a nested class"),
+            '[' => in_class = true,
+            ']' if in_class && previous != Some('[') && previous != Some('^') => in_class = false,
+            '&' | '-' | '~' if in_class && chars.peek() == Some(&c) && previous != Some('[') => {
+                return unsupported("a class set operation")
+            }
+            '(' if !in_class && chars.peek() == Some(&'?') => {
+                chars.next();
+                let group: String = chars.clone().take(2).collect();
+                if group.starts_with(['=', '!']) || group == "<=" || group == "<!" {
+                    return unsupported("a lookaround");
+                } else if group.starts_with('>') {
+                    return unsupported("an atomic group");
+                } else if group.starts_with('(') {
+                    return unsupported("a conditional");
+                }
+                let flags: String = chars
+                    .clone()
+                    .take_while(|c| c.is_ascii_alphabetic() || *c == '-')
+                    .collect();
+                if flags.contains(['U', 'R', 'u']) && !group.starts_with('P') {
+                    return unsupported("a flag of the regex crate");
+                }
+            }
+            '+' if !in_class && matches!(previous, Some('*' | '+' | '?' | '}')) => {
+                return unsupported("a possessive quantifier")
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+
+    Ok(())
+}
+
+/// A string matched by a regex, along with the text of its named groups.
+/// See [`example_match`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExampleMatch {
+    pub text: String,
+    pub groups: Vec<(String, String)>,
+}
+
+/// Characters tried, in order, to stand in for a class in an example.
+const EXAMPLE_CHARS: [char; 5] = ['1', 'a', 'A', ' ', '.'];
+
+/// Build a short string the regex matches, like "1 U.S. 1" for
+/// `(?P<volume>\d+) (?P<reporter>U\.S\.) (?P<page>\d+)`, by taking the first
+/// branch of every alternation and the fewest repetitions allowed. Returns
+/// None if the regex can't be parsed or only matches the empty string.
+pub fn example_match(regex: &str) -> Option<ExampleMatch> {
+    let mut text = Vec::new();
+    let mut spans = Vec::new();
+    example_of(&regex_syntax::parse(regex).ok()?, &mut text, &mut spans);
+
+    let text = String::from_utf8(text).ok()?;
+    let groups = spans
+        .into_iter()
+        .map(|(name, start, end)| Some((name, text.get(start..end)?.to_string())))
+        .collect::<Option<_>>()?;
+
+    (!text.is_empty()).then_some(ExampleMatch { text, groups })
+}
+
+fn example_of(hir: &Hir, text: &mut Vec<u8>, groups: &mut Vec<(String, usize, usize)>) {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Literal(literal) => text.extend_from_slice(&literal.0),
+        HirKind::Class(Class::Unicode(class)) => {
+            let ranges = class.ranges();
+            let c = EXAMPLE_CHARS
+                .into_iter()
+                .find(|c| ranges.iter().any(|r| (r.start()..=r.end()).contains(c)))
+                .or_else(|| ranges.first().map(|r| r.start()));
+            if let Some(c) = c {
+                text.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            let ranges = class.ranges();
+            let b = EXAMPLE_CHARS
+                .into_iter()
+                .map(|c| c as u8)
+                .find(|b| ranges.iter().any(|r| (r.start()..=r.end()).contains(b)))
+                .or_else(|| ranges.first().map(|r| r.start()));
+            text.extend(b);
+        }
+        HirKind::Capture(capture) => {
+            let start = text.len();
+            example_of(&capture.sub, text, groups);
+            if let Some(name) = &capture.name {
+                groups.push((name.to_string(), start, text.len()));
+            }
+        }
+        HirKind::Repetition(repetition) => {
+            for _ in 0..repetition.min {
+                example_of(&repetition.sub, text, groups);
+            }
+        }
+        HirKind::Concat(parts) => {
+            for part in parts {
+                example_of(part, text, groups);
+            }
+        }
+        HirKind::Alternation(branches) => {
+            if let Some(branch) = branches.first() {
+                example_of(branch, text, groups);
+            }
+        }
+    }
+}
+
+// *** Metadata regexes: ***
+// Regexes used to scan forward or backward from a citation token. NOTE:
+// * Regexes are written in verbose mode. Intentional spaces must be escaped.
+// * In many regexes order matters: options separated by "|" are
+//   tested left to right, so more specific (typically longer) have to come
+//   before less specific.
+// * The regex crate supports neither lookaround nor reusing a group name, so
+//   a few of these are split up compared to python's eyecite and are stitched
+//   back together in `crate::helpers`.
+
+/// Parenthetical regex:
+/// Capture a parenthetical after a cite, like " (overruling Foo)"
+pub const PARENTHETICAL_REGEX: &str = r"
+    (?:
+        # optional space, opening paren
+        \ ?\(
+            # capture until last end paren, we'll trim off extra afterwards
+            (?P<parenthetical>.*)
+           \)
+    )?
+";
+
+/// Disposition regex:
+/// Match the disposition of a cited case, or the kind of opinion cited, at
+/// the start of its parenthetical, like "per curiam", "en banc" or
+/// "Scalia, J., dissenting".
+pub const DISPOSITION_REGEX: &str = r"
+    (?i:
+        (?P<disposition>per\ curiam|en\ banc|plurality\ opinion|mem(?:orandum)?\.)
+    )
+    |
+    # a separate opinion, like 'Breyer, J., concurring in the judgment':
+    (?P<judge>\p{Lu}[\p{L}'’-]+(?:\ \p{Lu}[\p{L}'’-]+)*)
+    ,\ (?P<title>C\.\ ?J\.|JJ?\.)
+    ,?\ (?P<opinion_type>(?:concurring|dissenting)\b[^()]*)
+";
+
+pub const MONTH_REGEX: &str = r"
+    (?P<month>
+        Jan\.|Feb\.|Mar\.|Apr\.|May|June|
+        July|Aug\.|Sept\.|Oct\.|Nov\.|Dec\.
+    )
+";
+
+pub const YEAR_REGEX: &str = r"
+    (?:
+        (?P<year>
+            \d{4}
+        )
+        # Year is occasionally a range, like 1993-94 or 2005-06.
+        # For now we ignore the end of the range:
+        (?:-\d{2})?
+    )
+";
+
+/// Pin cite regex:
+/// A pin cite is the part of a citation used to specify a particular section of
+/// the referenced document. These may have prefixes, may include paragraph,
+/// page, or line references, and may have multiple ranges specified.
+/// For some examples see
+/// <https://github.com/freelawproject/courtlistener/issues/1344#issuecomment-662994948>
+pub const PIN_CITE_TOKEN_REGEX: &str = r"
+    # optional label (longest to shortest):
+    (?:
+        (?:
+            (?:&\ )?note|       # note, & note
+            (?:&\ )?nn?\.?|     # n., nn., & nn.
+            (?:&\ )?fn?\.?|     # fn., & fn.
+            ¶{1,2}|             # ¶
+            §{1,2}|             # §
+            \*{1,4}|            # *
+            pg\.?|              # pg.
+            pp?\.?              # p., pp.
+        )\ ?  # optional space after label
+    )?
+    (?:
+        # page:paragraph cite, like 123:24-25 or 123:24-124:25:
+        \d+:\d+(?:-\d+(?::\d+)?)?|
+        # page range, like 12 or 12-13:
+        \d+(?:-\d+)?
+    )
+";
+
+/// Pin cite regex. Python's eyecite uses a lookahead to require that the pin
+/// cite is followed by ending punctuation, so it doesn't capture the start of
+/// the next citation. Here the terminator is consumed instead, so only the
+/// span of the `pin_cite` group should be used by callers.
+pub const PIN_CITE_REGEX: &str = formatcp!(
+    r"
+    (?P<pin_cite>
+        # optional comma, space, at before pin cite
+        ,?\ ?(?:at\ )?
+        # first mandatory page number
+        {PIN_CITE_TOKEN_REGEX}
+        # optional additional page numbers
+        (?:,\ ?{PIN_CITE_TOKEN_REGEX})*
+    )
+    # pin cite must be followed by one of these so it doesn't capture
+    # start of next citation
+    (?:
+        [,.;)\]\\]|  # ending punctuation
+        \ ?[(\[]|    # space and start of parens
+        $            # end of text
+    )
+",
+    PIN_CITE_TOKEN_REGEX = PIN_CITE_TOKEN_REGEX
+);
+
+/// Slip opinion date regex:
+/// Capture the court and full date after a slip opinion, as well as any
+/// parenthetical comment. For example given the citation
+/// "No. 19-1392, slip op. at 12" with the following text:
+/// ```text
+/// No. 19-1392, slip op. at 12 (U.S. June 24, 2022) (plurality opinion)
+/// ```
+/// we want to capture:
+/// ```text
+/// court = U.S.
+/// month = June
+/// day = 24
+/// year = 2022
+/// parenthetical = plurality opinion
+/// ```
+pub const SLIP_OPINION_DATE_REGEX: &str = formatcp!(
+    r"
+    \ ?\(
+        # optional court before the date:
+        (?:(?P<court>[^)]+?)\ )?
+        {MONTH_REGEX}\ (?P<day>\d{{1,2}}),\ {YEAR_REGEX}
+    \)
+    # optional parenthetical comment:
+    {PARENTHETICAL_REGEX}
+",
+    MONTH_REGEX = MONTH_REGEX,
+    YEAR_REGEX = YEAR_REGEX,
+    PARENTHETICAL_REGEX = PARENTHETICAL_REGEX
+);
+
+/// Post law citation regex:
+/// Capture the status, publisher and date parenthetical after a statute or
+/// regulation. For example, given the citation "Cal. Civ. Code § 1714" with
+/// the following text:
+/// ```text
+/// Cal. Civ. Code § 1714 (West Jan. 1, 2019) (duty of care)
+/// ```
+/// we want to capture:
+/// ```text
+/// publisher = West
+/// month = Jan.
+/// day = 1
+/// year = 2019
+/// parenthetical = duty of care
+/// ```
+/// Other parentheticals captured are like "(as amended 2003)",
+/// "(repealed 1988)" and "(Supp. V 2017)".
+pub const POST_LAW_CITATION_REGEX: &str = formatcp!(
+    r"
+    \ ?\(
+        # optional status of the law:
+        (?:(?P<status>as\ amended|amended|repealed|superseded|renumbered|expired)\ )?
+        # optional publisher or supplement:
+        (?:(?P<publisher>[A-Z][\w.\ ]*?[\w.])\ )?
+        # optional month and day:
+        (?:{MONTH_REGEX}\ (?:(?P<day>\d{{1,2}}),\ )?)?
+        {YEAR_REGEX}
+    \)
+    # optional parenthetical comment:
+    {PARENTHETICAL_REGEX}
+",
+    MONTH_REGEX = MONTH_REGEX,
+    YEAR_REGEX = YEAR_REGEX,
+    PARENTHETICAL_REGEX = PARENTHETICAL_REGEX
+);
+
+/// Short cite antecedent regex:
+/// What case does a short cite refer to? For now, we just capture the previous
+/// word optionally followed by a comma. Example: Adarand, 515 U.S. at 241.
+pub const SHORT_CITE_ANTECEDENT_REGEX: &str = r"
+    (?P<antecedent>[\w\-.]+),?
+    \   # final space
+";
+
+/// Hereinafter regex:
+/// Capture a short name given to a full citation for later references, like
+/// "Smith Report" in "Smith, Report on Things 1 (1990) [hereinafter Smith
+/// Report]". It has to come before the end of the clause.
+pub const HEREINAFTER_REGEX: &str = r"
+    [^\[;]*?
+    \[hereinafter\ (?P<hereinafter>[^\]]+)\]
+";
+
+/// Supra note regex:
+/// Capture the footnote a supra citation points to, like "45" in
+/// "Smith, supra note 45, at 12", as academic documents cite.
+pub const SUPRA_NOTE_REGEX: &str = r"
+    ,?\ ?
+    # label:
+    (?:notes?|nn?\.)
+    \ ?
+    (?P<note>\d+)
+";
+
+/// Infra reference regex:
+/// Capture the part of a document an infra citation points forward to, like
+/// "note 45" in "infra note 45" or "Part III.B" in "infra Part III.B".
+pub const INFRA_REFERENCE_REGEX: &str = r"
+    ,?\ ?
+    (?P<reference>
+        # label:
+        (?:
+            text\ accompanying\ notes?|
+            notes?|nn?\.|
+            Parts?|
+            Sections?|§§?|
+            pp?\.
+        )
+        \ ?
+        # number, without trailing punctuation:
+        [\w.]*\w
+        # optional range, like 45-47:
+        (?:\ ?[-–]\ ?[\w.]*\w)?
+    )
+";
+
+/// Signal regex:
+/// Capture the introductory signal at the start of a citation clause, like
+/// "See also" in "See also Smith, 1 U.S. 1 (1800)". Longer signals come
+/// first, so "See also" isn't captured as "See".
+pub const SIGNAL_REGEX: &str = r"
+    (?P<signal>
+        (?i:
+            see,\ e\.g\.|see\ also|see\ generally|see|
+            but\ see,\ e\.g\.|but\ see|but\ cf\.|
+            cf\.|compare|contra|accord|e\.g\.
+        )
+    )
+    ,?\   # final space
+";
+
+/// Subsequent history regex:
+/// Capture the subsequent history right before a citation, like "rev'd on
+/// other grounds" in "Smith, 1 U.S. 1 (1800), rev'd on other grounds, 2 U.S.
+/// 2 (1801)", which is the signal of that citation alone.
+pub const SUBSEQUENT_HISTORY_REGEX: &str = r"
+    \b
+    (?P<signal>
+        (?i:
+            cert\.\ denied|cert\.\ granted|cert\.\ dismissed|
+            reh'g\ denied|
+            (?:rev'd|aff'd|vacated|overruled|modified)\ on\ other\ grounds|
+            rev'd|aff'd|vacated|overruled|modified
+        )
+    )
+    ,?\ ?  # final comma and space
+";
+
+/// Supra cite antecedent regex:
+/// What case does a short cite refer to? For now, we just capture the previous
+/// word optionally followed by a comma. Example: Adarand, supra.
+/// If the previous word is a digit, we capture both that (to store as a volume)
+/// and the word before it (to store as antecedent).
+///
+/// Group names can't be repeated, so the bare forms are captured as
+/// `bare_volume` and `bare_antecedent`.
+pub const SUPRA_ANTECEDENT_REGEX: &str = r"
+    (?:
+        (?P<antecedent>[\w\-.]+),?\ (?P<volume>\d+)|
+        (?P<bare_volume>\d+)|
+        (?P<bare_antecedent>[\w\-.]+),?
+    )
+    \   # final space
+";
+
+/// Post full citation regex:
+/// Capture metadata after a full cite and its (optional) pin cite. For example
+/// given the citation "1 U.S. 1" with the following text:
+/// ```text
+/// 1 U.S. 1, 4-5, 2 S. Ct. 2, 6-7 (4th Cir. 2012) (overruling foo)
+/// ```
+/// we want to capture:
+/// ```text
+/// pin_cite = 4-5
+/// extra = 2 S. Ct. 2, 6-7
+/// court = 4th Cir.
+/// year = 2012
+/// parenthetical = overruling foo
+/// ```
+/// The pin cite is matched first with [`PIN_CITE_REGEX`], and this regex is
+/// matched against whatever follows it.
+pub const POST_FULL_CITATION_REGEX: &str = formatcp!(
+    r"
+    # content before year paren:
+    ,?\ ?
+    (?P<extra>[^(]*)
+    # content within year paren:
+    \(
+        # optional court before the year:
+        (?:(?P<court>[^)]+)\ )?
+        {YEAR_REGEX}
+    \)
+    # optional parenthetical comment:
+    {PARENTHETICAL_REGEX}
+",
+    YEAR_REGEX = YEAR_REGEX,
+    PARENTHETICAL_REGEX = PARENTHETICAL_REGEX
+);
+
+/// Post short-form citation regex:
+/// Capture the parenthetical after a short, id, or supra citation and its
+/// (optional) pin cite. For example, given the citation
+/// 'asdf, 1 U.S., at 3 (overruling xyz)', [`PIN_CITE_REGEX`] will capture
+/// `3` and this will capture `overruling xyz`.
+pub const POST_SHORT_CITATION_REGEX: &str = formatcp!(
+    r"
+    \ ?
+    # optional parenthetical comment:
+    {PARENTHETICAL_REGEX}
+",
+    PARENTHETICAL_REGEX = PARENTHETICAL_REGEX
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_hyperscan_compatible, example_match, nonalphanum_boundaries_re, required_literals,
+        short_cite_template, space_boundaries_re, spacing_tolerant_reporter, strip_punctuation_re,
+        try_nonalphanum_boundaries_re, try_short_cite_re, try_space_boundaries_re, PARAGRAPH_REGEX,
+        SUPRA_REGEX,
+    };
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::EyeciteError;
+    use reporters_db::regexes::ResolvedRegex;
+
+    #[test]
+    fn derive_required_literals() {
+        let regex = nonalphanum_boundaries_re(&ResolvedRegex::of(
+            r"(?P<volume>\d+) (?P<reporter>U\.S\.|U\. S\.) (?P<page>\d+)".into(),
+        ));
+        assert_eq!(
+            required_literals(regex.value()),
+            Some(vec![" U.S. ".to_string(), " U. S. ".to_string()])
+        );
+        assert_eq!(
+            required_literals(PARAGRAPH_REGEX),
+            Some(vec!["\n".to_string()])
+        );
+        assert_eq!(required_literals(r"(?P<volume>\d+)"), None);
+    }
+
+    #[test]
+    fn unicode_boundaries() {
+        let regex = nonalphanum_boundaries_re(&ResolvedRegex::of(
+            r"(?P<volume>\d+) (?P<reporter>U\.S\.),? (?P<page>\d+)".into(),
+        ));
+        let regex = regex::Regex::new(regex.value()).unwrap();
+        let matched = |text| regex.captures(text).map(|c| c.get(1).unwrap().as_str());
+
+        assert_eq!(matched("Peña v. Muñoz, 1 U.S. 1 (1990)"), Some("1 U.S. 1"));
+        assert_eq!(matched("Émile, 1 U.S. 1é"), None);
+        assert_eq!(matched("Ñ1 U.S. 1"), None);
+        assert_eq!(matched("«1 U.S. 1»"), Some("1 U.S. 1"));
+    }
+
+    #[test]
+    fn example_matches() {
+        let example =
+            example_match(r"(?P<volume>\d+) (?P<reporter>U\.S\.|U\. S\.),? (?P<page>\d+|[ivx]+)")
+                .unwrap();
+        assert_eq!(example.text, "1 U.S. 1");
+        assert_eq!(
+            example.groups,
+            vec![
+                ("volume".to_string(), "1".to_string()),
+                ("reporter".to_string(), "U.S.".to_string()),
+                ("page".to_string(), "1".to_string()),
+            ]
+        );
+
+        let boundaries = nonalphanum_boundaries_re(&ResolvedRegex::of(r"[A-Z]\.".into()));
+        assert_eq!(example_match(boundaries.value()).unwrap().text, "A. ");
+        assert_eq!(example_match(r"a?"), None);
+    }
+
+    /// Regexes of the built-in extractors for the United States Reports,
+    /// full, nominative, short and placeholder, so changes to how
+    /// extractors are built from `reporters_db` show up as snapshot diffs.
+    #[test]
+    fn reporter_regexes() {
+        let snapshot: Vec<_> = EXTRACTORS
+            .iter()
+            .filter(|extractor| {
+                let editions = &extractor.extra.exact_editions;
+                editions.iter().any(|edition| edition.short_name == "U.S.")
+            })
+            .map(|extractor| {
+                let kind = if extractor.extra.short {
+                    "short:"
+                } else {
+                    "full: "
+                };
+                format!("{kind} {}", extractor.regex.value())
+            })
+            .collect();
+        insta::assert_snapshot!(snapshot.join("\n"));
+    }
+
+    #[test]
+    fn regex_toolkit() {
+        // the functions build the same regexes as the macros
+        let supra = format!("(?P<supra>{})", strip_punctuation_re("supra").value());
+        assert_eq!(space_boundaries_re(&supra).value(), SUPRA_REGEX);
+
+        let full = r"(?P<volume>\d+) (?P<reporter>Foo\.),? (?P<page>\d+)";
+        let short = try_short_cite_re(full).unwrap();
+        assert_eq!(
+            short.value(),
+            r"(?P<volume>\d+) (?P<reporter>Foo\.),? at (?P<page>\d+)"
+        );
+        assert!(matches!(
+            try_short_cite_re(r"(?P<reporter>Foo\.) ¶ (?P<page>\d+)"),
+            Err(EyeciteError::MissingReporterPage { .. })
+        ));
+
+        assert!(try_nonalphanum_boundaries_re(&short).is_ok());
+        assert!(matches!(
+            try_space_boundaries_re("(unclosed"),
+            Err(EyeciteError::RegexError { .. })
+        ));
+    }
+
+    #[test]
+    fn short_cite_templates() {
+        let has_short = |name: &str| name == "short_cite";
+
+        assert_eq!(
+            short_cite_template("$volume $reporter,? $page", has_short).as_deref(),
+            Some("$volume $reporter,? at $page")
+        );
+        assert_eq!(
+            short_cite_template("$volume ${reporter} $page_with_roman_numerals", has_short)
+                .as_deref(),
+            Some("$volume ${reporter},? at $page_with_roman_numerals")
+        );
+        assert_eq!(
+            short_cite_template("$full_cite", has_short).as_deref(),
+            Some("$short_cite")
+        );
+        // paragraph cites and unknown variables have no short form
+        assert_eq!(
+            short_cite_template("$volume_optional$reporter,? ¶ ?$page_paragraph", has_short),
+            None
+        );
+        assert_eq!(short_cite_template("$full_cite_paragraph", has_short), None);
+    }
+
+    #[test]
+    fn hyperscan_compatible() {
+        for regex in [
+            r"(?P<volume>\d+) (?P<reporter>U\.S\.),? (?P<page>\d+)",
+            r"(?i)\bid\.,?",
+            r"[^a-zA-Z0-9\-]",
+            r"[[:alpha:]]+",
+        ] {
+            assert!(check_hyperscan_compatible(regex).is_ok(), "{regex}");
+        }
+
+        for (regex, feature) in [
+            (r"foo(?=bar)", "a lookaround"),
+            (r"(?<!foo)bar", "a lookaround"),
+            (r"(a)\1", "a backreference"),
+            (r"(?>a+)", "an atomic group"),
+            (r"a++", "a possessive quantifier"),
+            (r"(?U)a+", "a flag of the regex crate"),
+            (r"\<word\>", "a word boundary extension"),
+            (r"[a-z[aeiou]]", "a nested class"),
+            (r"[a-z--b]", "a class set operation"),
+        ] {
+            match check_hyperscan_compatible(regex) {
+                Err(EyeciteError::UnsupportedRegexFeature { feature: f, .. }) => {
+                    assert_eq!(f, feature, "{regex}")
+                }
+                _ => panic!("expected {regex} to be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn spacing_tolerant_reporters() {
+        assert_eq!(spacing_tolerant_reporter("U.S."), r"U\.? ?S\.?");
+        assert_eq!(
+            spacing_tolerant_reporter("F. Supp. 2d"),
+            r"F\.? ?Supp\.? ?2d"
+        );
+        assert_eq!(spacing_tolerant_reporter("S. Ct."), r"S\.? ?Ct\.?");
+
+        let regex = regex::Regex::new(&format!("^{}$", spacing_tolerant_reporter("U.S."))).unwrap();
+        for reporter in ["U.S.", "U. S.", "U.S", "US", "U. S"] {
+            assert!(regex.is_match(reporter), "{reporter}");
+        }
+        assert!(!regex.is_match("U..S."));
+
+        let regex = regex::Regex::new(&format!(
+            "^{}$",
+            spacing_tolerant_reporter("Tax Ct. Rep. (CCH)")
+        ))
+        .unwrap();
+        assert!(regex.is_match("Tax Ct.Rep (CCH)"));
+        assert!(!regex.is_match("TaxCt. Rep. (CCH)"));
+    }
+}
@@ -0,0 +1,188 @@
+/*!
+Non-fatal problems found while extracting citations from a document.
+
+None of these stop extraction, but tracking them over time shows how well
+extraction works on a corpus, and which documents deserve a closer look.
+See [`get_citations_with_report`](crate::find::get_citations_with_report).
+ */
+
+use crate::find::models::Citation;
+use crate::helpers;
+use crate::resolve::resolve_citations;
+use crate::tokenizers::models::Token;
+use crate::validate::{validate_volumes, VolumeLimits, VolumeWarning};
+use std::collections::HashSet;
+
+/// A problem found while extracting citations. Indexes refer to the list of
+/// citations the report was made for.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExtractionWarning {
+    /// The year of a citation isn't a plausible year, like "(3020)".
+    MalformedYear {
+        index: usize,
+        span: (usize, usize),
+        year: String,
+    },
+    /// The volume of a citation is implausible for its reporter.
+    ImplausibleVolume(VolumeWarning),
+    /// A short case, supra or id citation couldn't be resolved to an earlier
+    /// full citation.
+    UnresolvedShortForm { index: usize, span: (usize, usize) },
+    /// Two extractors matched exactly the same text, and the tokens couldn't
+    /// be merged, so only the first was kept.
+    MergeConflict { span: (usize, usize) },
+    /// A match was skipped because it overlaps an earlier one.
+    SkippedOverlap { span: (usize, usize) },
+    /// A citation match was skipped because one of its groups is malformed,
+    /// like a volume that isn't a number. See
+    /// [`helpers::malformed_group`].
+    MalformedMatch { span: (usize, usize), group: String },
+}
+
+/// Warnings found while extracting citations from a document.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ExtractionReport {
+    pub warnings: Vec<ExtractionWarning>,
+}
+
+impl ExtractionReport {
+    /// Check extracted citations for malformed years, implausible volumes
+    /// and short forms that can't be resolved.
+    pub fn new(citations: &[Citation], limits: &VolumeLimits) -> Self {
+        let mut report = Self::default();
+
+        for (index, citation) in citations.iter().enumerate() {
+            let source = citation.source();
+            if let Some(year) = source.year() {
+                if helpers::get_year(year).is_none() {
+                    report.warnings.push(ExtractionWarning::MalformedYear {
+                        index,
+                        span: source.span(),
+                        year: year.to_string(),
+                    });
+                }
+            }
+        }
+
+        report.warnings.extend(
+            validate_volumes(citations, limits)
+                .into_iter()
+                .map(ExtractionWarning::ImplausibleVolume),
+        );
+
+        let resolved: HashSet<_> = resolve_citations(citations)
+            .into_values()
+            .flatten()
+            .map(|citation| citation.source().index)
+            .collect();
+        for (index, citation) in citations.iter().enumerate() {
+            let is_short_form = matches!(
+                citation,
+                Citation::ShortCase { .. } | Citation::Supra { .. } | Citation::Id { .. }
+            );
+            if is_short_form && !resolved.contains(&citation.source().index) {
+                report
+                    .warnings
+                    .push(ExtractionWarning::UnresolvedShortForm {
+                        index,
+                        span: citation.source().span(),
+                    });
+            }
+        }
+
+        report
+    }
+
+    /// Record tokens the tokenizer skipped, given the spans of the tokens it
+    /// kept. See [`TokenBuffers::skipped`](crate::tokenizers::TokenBuffers::skipped).
+    pub fn add_skipped_tokens(&mut self, skipped: &[Token], kept: &[(usize, usize)]) {
+        for token in skipped {
+            let span = (token.start(), token.end());
+            self.warnings.push(if kept.contains(&span) {
+                ExtractionWarning::MergeConflict { span }
+            } else {
+                ExtractionWarning::SkippedOverlap { span }
+            });
+        }
+    }
+
+    /// Record citation tokens skipped because a group of their match is
+    /// malformed.
+    pub fn add_malformed_matches<'t, 'a: 't>(
+        &mut self,
+        tokens: impl IntoIterator<Item = &'t Token<'a>>,
+    ) {
+        for token in tokens {
+            if let Token::Citation(data) = token {
+                if let Some(group) = helpers::malformed_group(data) {
+                    self.warnings.push(ExtractionWarning::MalformedMatch {
+                        span: (data.start, data.end),
+                        group: group.as_str().to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtractionWarning;
+    use crate::find::{get_citations_with_report, FindOptions};
+    use crate::tokenizers::extractors::{TokenExtractor, EXTRACTORS};
+    use crate::tokenizers::models::TokenFactories;
+    use crate::tokenizers::Ahocorasick;
+    use reporters_db::regexes::ResolvedRegex;
+
+    #[test]
+    fn extraction_warnings() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (3020). Id. at 2. Baz, supra, at 3. 0 F. 1.";
+        let (citations, report) =
+            get_citations_with_report(text, &FindOptions::default(), &tokenizer);
+        assert_eq!(citations.len(), 4);
+
+        let kinds: Vec<_> = report
+            .warnings
+            .iter()
+            .filter_map(|w| match w {
+                ExtractionWarning::MalformedYear { index, .. } => Some(("year", *index)),
+                ExtractionWarning::ImplausibleVolume(w) => Some(("volume", w.index)),
+                ExtractionWarning::UnresolvedShortForm { index, .. } => Some(("short", *index)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kinds, vec![("year", 0), ("volume", 3), ("short", 2)]);
+    }
+
+    #[test]
+    fn malformed_matches() {
+        let extractors = vec![TokenExtractor::try_new(
+            ResolvedRegex::of(r"((?P<volume>\w+) Rep\. (?P<page>\d+))".into()),
+            TokenFactories::Citation,
+            false,
+            vec!["Rep.".into()].into_iter().collect(),
+            Default::default(),
+        )
+        .unwrap()];
+        let tokenizer = Ahocorasick::new(&extractors).unwrap();
+        let text = "See 1 Rep. 5 and XL Rep. 7.";
+
+        let (citations, report) =
+            get_citations_with_report(text, &FindOptions::default(), &tokenizer);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source().groups["volume"], "1");
+
+        let start = text.find("XL").unwrap();
+        assert!(report
+            .warnings
+            .contains(&ExtractionWarning::MalformedMatch {
+                span: (start, start + "XL Rep. 7".len()),
+                group: "volume".to_string(),
+            }));
+    }
+}
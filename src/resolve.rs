@@ -0,0 +1,961 @@
+/*!
+Resolve citations to the resources they refer to, grouping each full citation
+with the short case, supra and id citations that refer back to it.
+ */
+
+use crate::find::models::Citation;
+use crate::footnotes::Footnote;
+use crate::names::{antecedent_matches_with, Abbreviations, DEFAULT_ABBREVIATIONS};
+use crate::tokenizers::models::GroupName;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Skip id. citations that imply a page length longer than this,
+/// such as "1 U.S. 1. Id. at 200.":
+pub const MAX_OPINION_PAGE_COUNT: u32 = 150;
+
+/// Thin resource representing an object to which a citation can be resolved.
+///
+/// Resources are the same if their full citations are semantically
+/// equivalent, i.e. have the same volume, reporter, page and so on. Pin cites
+/// point into a resource, so they aren't part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Resource {
+    pub groups: BTreeMap<String, String>,
+}
+
+/// Full citations along with the resources they were resolved to, in the
+/// order they were found.
+pub type ResolvedFullCites<'c, 'a, R> = Vec<(&'c Citation<'a>, R)>;
+
+/// Resources mapped to the citations that refer to them.
+pub type Resolutions<'c, 'a, R> = HashMap<R, Vec<&'c Citation<'a>>>;
+
+/// By default, resolve full citations to a generic (but reference-unique)
+/// [`Resource`]. Custom resolution logic passed to
+/// [`resolve_citations_with`] may still want to fall back to this, to allow
+/// normal clustering of citations it doesn't know about.
+pub fn resolve_full_citation(full_citation: &Citation) -> Resource {
+    Resource {
+        groups: full_citation
+            .source()
+            .groups
+            .iter()
+            .filter(|(name, _)| *name != GroupName::PinCite)
+            .map(|(name, value)| (name.as_str().to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+/// Like [`resolve_full_citation`], but with the reporter corrected to its
+/// official abbreviation, so the same resource cited with different reporter
+/// variations, like "U. S." and "U.S.", in different documents is the same
+/// resource. [`resolve_corpus`] uses this by default.
+pub fn resolve_canonical_citation(full_citation: &Citation) -> Resource {
+    let mut resource = resolve_full_citation(full_citation);
+    if let Some(reporter) = full_citation.source().corrected_reporter() {
+        resource
+            .groups
+            .insert("reporter".to_string(), reporter.to_string());
+    }
+    resource
+}
+
+/// Only keep a resource if it is the only one among the candidates.
+fn only_resource<'r, R: Eq>(resources: impl IntoIterator<Item = &'r R>) -> Option<&'r R> {
+    let mut resources = resources.into_iter();
+    let first = resources.next()?;
+
+    resources.all(|r| r == first).then_some(first)
+}
+
+fn _filter_by_matching_antecedent<R: Clone + Eq>(
+    resolved_full_cites: &[(&Citation, R)],
+    section: Option<usize>,
+    antecedent_guess: &str,
+    abbreviations: &Abbreviations,
+) -> Option<R> {
+    // A short name given by "[hereinafter ...]" refers to exactly one
+    // citation
+    if let Some((_, resource)) = resolved_full_cites.iter().rev().find(|(citation, _)| {
+        in_section(citation, section) && citation.source().hereinafter() == Some(antecedent_guess)
+    }) {
+        return Some(resource.clone());
+    }
+
+    let matches = resolved_full_cites
+        .iter()
+        .filter(|(citation, _)| in_section(citation, section))
+        .filter_map(|(citation, resource)| match citation {
+            Citation::FullCase {
+                plaintiff,
+                defendant,
+                ..
+            } => (matches!(defendant, Some(d) if antecedent_matches_with(antecedent_guess, d, abbreviations))
+                || matches!(plaintiff, Some(p) if antecedent_matches_with(antecedent_guess, p, abbreviations)))
+            .then_some(resource),
+            _ => None,
+        });
+
+    // Remove duplicates and only accept if one candidate remains
+    only_resource(matches).cloned()
+}
+
+/// Return true if an id citation has a pin cite that can't be correct for
+/// the given full citation.
+fn _has_invalid_pin_cite(full_cite: &Citation, pin_cite: Option<&str>) -> bool {
+    // if no pin cite, we're fine
+    let pin_cite = match pin_cite {
+        Some(pin_cite) => pin_cite,
+        None => return false,
+    };
+
+    // if full cite has no page (such as a statute), we don't know what to
+    // check, so assume we're fine
+    let page: u32 = match full_cite.source().groups.get("page").map(|p| p.parse()) {
+        Some(Ok(page)) => page,
+        _ => return false,
+    };
+
+    // parse short cite pin
+    let pin_cite = pin_cite.strip_prefix("at ").unwrap_or(pin_cite);
+    let digits = pin_cite
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(pin_cite, |end| &pin_cite[..end]);
+
+    match digits.parse::<u32>() {
+        // check page range
+        Ok(pin_cite) => pin_cite < page || pin_cite > page + MAX_OPINION_PAGE_COUNT,
+        // If pin cite doesn't start with a digit, assume it is invalid.
+        // This is hopefully a conservative rule -- it will err for valid pin
+        // cites like "Id. at *10", but successfully filter invalid pin cites
+        // like "1 U.S. 1. ... Id. at ¶ 10".
+        Err(_) => true,
+    }
+}
+
+/// Try to match short case citations by checking whether their reporter and
+/// volume number matches those of any of the previously resolved full
+/// citations. If there are multiple possible matches, try to refine by also
+/// checking whether their antecedent_guess appears in either the defendant
+/// or plaintiff field of any of the previously resolved full citations.
+fn _resolve_shortcase_citation<R: Clone + Eq>(
+    short_citation: &Citation,
+    resolved_full_cites: &[(&Citation, R)],
+    abbreviations: &Abbreviations,
+) -> Option<R> {
+    let short = short_citation.source();
+    let section = short.document_section();
+    let candidates: Vec<_> = resolved_full_cites
+        .iter()
+        .filter(|(full_citation, _)| {
+            let full = full_citation.source();
+            matches!(full_citation, Citation::FullCase { .. })
+                && in_section(full_citation, section)
+                && short.corrected_reporter() == full.corrected_reporter()
+                && short.groups.get("volume") == full.groups.get("volume")
+        })
+        .cloned()
+        .collect();
+
+    // Remove duplicates and only accept if one candidate remains
+    if let Some(resource) = only_resource(candidates.iter().map(|(_, r)| r)) {
+        return Some(resource.clone());
+    }
+
+    // Otherwise, if there is an antecedent guess, try to refine further
+    match short_citation {
+        Citation::ShortCase {
+            antecedent_guess: Some(antecedent_guess),
+            ..
+        } => _filter_by_matching_antecedent(&candidates, None, antecedent_guess, abbreviations),
+        // Otherwise, nothing left to try
+        _ => None,
+    }
+}
+
+/// What the footnote a "supra note 45" citation points to cites. See
+/// [`resolve_citations_with_notes`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum NoteTarget<R> {
+    /// Start and stop offsets of the footnote, which refers to the resource
+    /// of the first full citation in it.
+    Span(usize, usize),
+    /// The resource the footnote refers to, when it is already known.
+    Resource(R),
+}
+
+/// Targets of footnotes found with
+/// [`find_footnotes`](crate::footnotes::find_footnotes), by label.
+pub fn footnote_targets<R>(footnotes: &[Footnote]) -> HashMap<String, NoteTarget<R>> {
+    footnotes
+        .iter()
+        .map(|footnote| {
+            let (start, end) = footnote.span;
+            (footnote.label.clone(), NoteTarget::Span(start, end))
+        })
+        .collect()
+}
+
+/// Try to resolve a supra citation pointing to a footnote, like "Smith,
+/// supra note 45", to what the footnote refers to.
+fn _resolve_supra_note<R: Clone>(
+    supra_citation: &Citation,
+    resolved_full_cites: &[(&Citation, R)],
+    notes: &HashMap<String, NoteTarget<R>>,
+) -> Option<R> {
+    match notes.get(supra_citation.source().note()?)? {
+        NoteTarget::Resource(resource) => Some(resource.clone()),
+        NoteTarget::Span(start, end) => resolved_full_cites
+            .iter()
+            .find(|(full_citation, _)| (*start..*end).contains(&full_citation.source().span().0))
+            .map(|(_, resource)| resource.clone()),
+    }
+}
+
+/// Try to resolve supra citations by checking whether their antecedent_guess
+/// appears in either the defendant or plaintiff field of any of the
+/// previously resolved full citations.
+fn _resolve_supra_citation<R: Clone + Eq>(
+    supra_citation: &Citation,
+    resolved_full_cites: &[(&Citation, R)],
+    abbreviations: &Abbreviations,
+) -> Option<R> {
+    match supra_citation {
+        Citation::Supra {
+            antecedent_guess: Some(antecedent_guess),
+            ..
+        } => _filter_by_matching_antecedent(
+            resolved_full_cites,
+            supra_citation.source().document_section(),
+            antecedent_guess,
+            abbreviations,
+        ),
+        // If no guess, can't do anything
+        _ => None,
+    }
+}
+
+/// Whether a short form citation in a section of the document, if it is in
+/// one, can refer to a full citation: only those in the same section. See
+/// [`FindOptions::sections`](crate::find::FindOptions::sections).
+fn in_section(full_citation: &Citation, section: Option<usize>) -> bool {
+    section.is_none() || full_citation.source().document_section() == section
+}
+
+/// Resolve id citations to the resource of the previously resolved
+/// citation.
+fn _resolve_id_citation<R: Clone + Eq + Hash>(
+    id_citation: &Citation,
+    last_resolution: Option<&R>,
+    resolutions: &Resolutions<R>,
+) -> Option<R> {
+    // if last resolution failed, id. cite should also fail
+    let last_resolution = last_resolution?;
+
+    // filter out citations based on pin cite
+    let pin_cite = match id_citation {
+        Citation::Id { pin_cite, .. } => *pin_cite,
+        _ => None,
+    };
+    let full_cite = resolutions.get(last_resolution)?.first()?;
+    if _has_invalid_pin_cite(full_cite, pin_cite) {
+        return None;
+    }
+
+    Some(last_resolution.clone())
+}
+
+/// Most bytes between a citation and a parallel citation to the same case,
+/// like the ", " in "___ U.S. ___, 141 S. Ct. 1163".
+const MAX_PARALLEL_GAP: usize = 2;
+
+/// For each full citation with a placeholder, like "___ U.S. ___", the
+/// index of the parallel citation right next to it that names the same case,
+/// like "141 S. Ct. 1163" in "___ U.S. ___, 141 S. Ct. 1163". Citations with
+/// placeholders can't be told apart from each other, so they are resolved
+/// to the resource of their parallel citation instead.
+fn placeholder_parallels(citations: &[Citation]) -> HashMap<usize, usize> {
+    let mut parallels = HashMap::new();
+    let mut run: Vec<usize> = Vec::new();
+
+    // Runs of full citations, each right after the one before it
+    for i in 0..=citations.len() {
+        let adjacent = citations.get(i).is_some_and(|citation| {
+            citation.is_full()
+                && run.last().is_some_and(|&previous| {
+                    let end = citations[previous].source().matched_span().1;
+                    let start = citation.source().matched_span().0;
+                    start >= end && start - end <= MAX_PARALLEL_GAP
+                })
+        });
+        if adjacent {
+            run.push(i);
+            continue;
+        }
+
+        if let Some(&parallel) = run
+            .iter()
+            .find(|&&j| !citations[j].source().has_placeholder())
+        {
+            for &j in &run {
+                if citations[j].source().has_placeholder() {
+                    parallels.insert(j, parallel);
+                }
+            }
+        }
+        run.clear();
+        if citations.get(i).is_some_and(Citation::is_full) {
+            run.push(i);
+        }
+    }
+
+    parallels
+}
+
+/// Resolve a list of citations to [`Resource`]s. See
+/// [`resolve_citations_with`].
+pub fn resolve_citations<'c, 'a>(citations: &'c [Citation<'a>]) -> Resolutions<'c, 'a, Resource> {
+    resolve_citations_with(citations, resolve_full_citation)
+}
+
+/**
+Resolve a list of citations to their associated resources by matching
+each type of citation (full, short case, supra and id citations) to a
+"resource". A resource could be a document, a URL, a database entry, etc. --
+anything that can be compared and hashed. By default, eyecite uses an
+extremely thin [`Resource`] that simply serves as a conceptual way to group
+citations with the same references together.
+
+This function assumes that the given list of citations is ordered in the
+order that they were extracted from the text (i.e., assumes that supra
+citations and id citations can only refer to previous references).
+
+Full citations are resolved with `resolve_full_citation`; other citations are
+resolved using simple heuristics to narrow down the set of possible
+resolutions. If a citation cannot be definitively resolved to a resource, it
+is dropped and not resolved.
+
+Citations found with [`FindOptions::sections`](crate::find::FindOptions::sections)
+are only resolved to citations in the same section of the document, so an
+id citation at the start of a dissent doesn't refer to the last citation of
+the majority opinion.
+
+Full citations with a placeholder, like "___ U.S. ___", resolve to the
+resource of the parallel citation right next to them, like
+"141 S. Ct. 1163" in "___ U.S. ___, 141 S. Ct. 1163", and aren't resolved
+without one.
+
+Args:
+    citations:
+        A list of citations, returned from calling
+        [`get_citations`](crate::find::get_citations).
+    resolve_full_citation:
+        A function that resolves full citations to resources.
+
+Returns:
+    A map from resources to the citations that refer to them.
+ */
+pub fn resolve_citations_with<'c, 'a, R: Clone + Eq + Hash>(
+    citations: &'c [Citation<'a>],
+    resolve_full_citation: impl FnMut(&Citation<'a>) -> R,
+) -> Resolutions<'c, 'a, R> {
+    resolve_citations_with_abbreviations(citations, resolve_full_citation, &DEFAULT_ABBREVIATIONS)
+}
+
+/// Like [`resolve_citations_with`], expanding the given party-name
+/// abbreviations instead of the common ones when matching the antecedents
+/// of short case and supra citations to case names.
+pub fn resolve_citations_with_abbreviations<'c, 'a, R: Clone + Eq + Hash>(
+    citations: &'c [Citation<'a>],
+    resolve_full_citation: impl FnMut(&Citation<'a>) -> R,
+    abbreviations: &Abbreviations,
+) -> Resolutions<'c, 'a, R> {
+    resolve_citations_with_notes(
+        citations,
+        resolve_full_citation,
+        abbreviations,
+        &HashMap::new(),
+    )
+}
+
+/// Like [`resolve_citations_with_abbreviations`], resolving supra citations
+/// pointing to a footnote, like "Smith, supra note 45", to what the
+/// footnote with that number in `notes` refers to: its first full citation,
+/// or a known resource. Supra citations to footnotes that aren't in `notes`
+/// are resolved by their antecedent, as usual.
+pub fn resolve_citations_with_notes<'c, 'a, R: Clone + Eq + Hash>(
+    citations: &'c [Citation<'a>],
+    mut resolve_full_citation: impl FnMut(&Citation<'a>) -> R,
+    abbreviations: &Abbreviations,
+    notes: &HashMap<String, NoteTarget<R>>,
+) -> Resolutions<'c, 'a, R> {
+    // All citation resolutions
+    let mut resolutions: Resolutions<R> = HashMap::new();
+
+    // Full citations and their resolved resources
+    let mut resolved_full_cites: ResolvedFullCites<R> = Vec::new();
+
+    // The resource of the most recently resolved citation, if any
+    let mut last_resolution: Option<R> = None;
+
+    let parallels = placeholder_parallels(citations);
+
+    // Iterate over each citation and attempt to resolve it to a resource
+    for (i, citation) in citations.iter().enumerate() {
+        // Id citations don't refer back across sections of the document
+        if i > 0
+            && citation.source().document_section() != citations[i - 1].source().document_section()
+        {
+            last_resolution = None;
+        }
+
+        let resolution = match citation {
+            // If the citation is a full citation, try to resolve it
+            citation if citation.is_full() => {
+                let resolution = match parallels.get(&i) {
+                    Some(&parallel) => Some(resolve_full_citation(&citations[parallel])),
+                    None if citation.source().has_placeholder() => None,
+                    None => Some(resolve_full_citation(citation)),
+                };
+                if let Some(resolution) = &resolution {
+                    resolved_full_cites.push((citation, resolution.clone()));
+                }
+                resolution
+            }
+
+            // If the citation is a short case citation, try to resolve it
+            Citation::ShortCase { .. } => {
+                _resolve_shortcase_citation(citation, &resolved_full_cites, abbreviations)
+            }
+
+            // If the citation is a supra citation, try to resolve it
+            Citation::Supra { .. } => _resolve_supra_note(citation, &resolved_full_cites, notes)
+                .or_else(|| _resolve_supra_citation(citation, &resolved_full_cites, abbreviations)),
+
+            // If the citation is an id citation, try to resolve it
+            Citation::Id { .. } => {
+                _resolve_id_citation(citation, last_resolution.as_ref(), &resolutions)
+            }
+
+            // If the citation is to an unknown document, ignore for now
+            _ => None,
+        };
+
+        if let Some(resolution) = &resolution {
+            // Record the citation in the appropriate list
+            resolutions
+                .entry(resolution.clone())
+                .or_default()
+                .push(citation);
+        }
+        last_resolution = resolution;
+    }
+
+    resolutions
+}
+
+/// Stable ID of a citation within its document: its position among the
+/// document's citations ordered by span. See [`citation_ids`].
+pub type CitationId = usize;
+
+/// The ID of each citation of a document, in the same order as the
+/// citations. Citations are numbered in the order of their spans, so
+/// finding citations in the same text always gives them the same IDs.
+pub fn citation_ids(citations: &[Citation]) -> Vec<CitationId> {
+    let mut order: Vec<_> = (0..citations.len()).collect();
+    order.sort_by_key(|&i| citations[i].source().span());
+
+    let mut ids = vec![0; citations.len()];
+    for (id, i) in order.into_iter().enumerate() {
+        ids[i] = id;
+    }
+    ids
+}
+
+/// The ID of the full citation each short case, supra and id citation was
+/// resolved to refer back to, in the same order as the citations: the first
+/// full citation of its resource, preferring one without a placeholder.
+/// Full citations, and citations that weren't resolved, have none.
+pub fn antecedent_ids<'c, 'a, R>(
+    citations: &'c [Citation<'a>],
+    resolutions: &Resolutions<'c, 'a, R>,
+) -> Vec<Option<CitationId>> {
+    let ids = citation_ids(citations);
+    let indices: HashMap<*const Citation<'a>, usize> = citations
+        .iter()
+        .enumerate()
+        .map(|(i, citation)| (std::ptr::from_ref(citation), i))
+        .collect();
+    let mut antecedents = vec![None; citations.len()];
+
+    for group in resolutions.values() {
+        let group: Vec<_> = group
+            .iter()
+            .filter_map(|&citation| indices.get(&std::ptr::from_ref(citation)).copied())
+            .collect();
+        let full = group
+            .iter()
+            .find(|&&i| citations[i].is_full() && !citations[i].source().has_placeholder())
+            .or_else(|| group.iter().find(|&&i| citations[i].is_full()));
+        let Some(&full) = full else {
+            continue;
+        };
+        for &i in &group {
+            if !citations[i].is_full() {
+                antecedents[i] = Some(ids[full]);
+            }
+        }
+    }
+
+    antecedents
+}
+
+/// Like [`resolve_citations`], but resolves citations on multiple threads.
+#[cfg(feature = "parallel")]
+pub fn par_resolve_citations<'c, 'a>(
+    citations: &'c [Citation<'a>],
+) -> Resolutions<'c, 'a, Resource> {
+    par_resolve_citations_with(citations, resolve_full_citation)
+}
+
+/// Like [`resolve_citations_with`], but resolves full, short case and supra
+/// citations on multiple threads, which helps with opinions citing thousands
+/// of cases. Each short case or supra citation only depends on the full
+/// citations before it, so they can be resolved independently; only id
+/// citations, which depend on the citation before them, are resolved in
+/// order. The result is the same as [`resolve_citations_with`].
+#[cfg(feature = "parallel")]
+pub fn par_resolve_citations_with<'c, 'a, R: Clone + Eq + Hash + Send + Sync>(
+    citations: &'c [Citation<'a>],
+    resolve_full_citation: impl Fn(&Citation<'a>) -> R + Sync + Send,
+) -> Resolutions<'c, 'a, R> {
+    par_resolve_citations_with_abbreviations(
+        citations,
+        resolve_full_citation,
+        &DEFAULT_ABBREVIATIONS,
+    )
+}
+
+/// Like [`resolve_citations_with_abbreviations`], on multiple threads. See
+/// [`par_resolve_citations_with`].
+#[cfg(feature = "parallel")]
+pub fn par_resolve_citations_with_abbreviations<'c, 'a, R: Clone + Eq + Hash + Send + Sync>(
+    citations: &'c [Citation<'a>],
+    resolve_full_citation: impl Fn(&Citation<'a>) -> R + Sync + Send,
+    abbreviations: &Abbreviations,
+) -> Resolutions<'c, 'a, R> {
+    par_resolve_citations_with_notes(
+        citations,
+        resolve_full_citation,
+        abbreviations,
+        &HashMap::new(),
+    )
+}
+
+/// Like [`resolve_citations_with_notes`], on multiple threads. See
+/// [`par_resolve_citations_with`].
+#[cfg(feature = "parallel")]
+pub fn par_resolve_citations_with_notes<'c, 'a, R: Clone + Eq + Hash + Send + Sync>(
+    citations: &'c [Citation<'a>],
+    resolve_full_citation: impl Fn(&Citation<'a>) -> R + Sync + Send,
+    abbreviations: &Abbreviations,
+    notes: &HashMap<String, NoteTarget<R>>,
+) -> Resolutions<'c, 'a, R> {
+    let parallels = placeholder_parallels(citations);
+    let mut full_resolutions: Vec<Option<R>> = citations
+        .par_iter()
+        .enumerate()
+        .map(|(i, citation)| match parallels.get(&i) {
+            _ if !citation.is_full() => None,
+            Some(&parallel) => Some(resolve_full_citation(&citations[parallel])),
+            None if citation.source().has_placeholder() => None,
+            None => Some(resolve_full_citation(citation)),
+        })
+        .collect();
+
+    // Full citations and their resolved resources, along with how many of
+    // them come before each citation
+    let resolved_full_cites: ResolvedFullCites<R> = citations
+        .iter()
+        .zip(&full_resolutions)
+        .filter_map(|(citation, resolution)| Some((citation, resolution.clone()?)))
+        .collect();
+    let preceding_full_cites: Vec<usize> = full_resolutions
+        .iter()
+        .scan(0, |count, resolution| {
+            let preceding = *count;
+            *count += usize::from(resolution.is_some());
+            Some(preceding)
+        })
+        .collect();
+
+    let mut short_resolutions: Vec<Option<R>> = citations
+        .par_iter()
+        .enumerate()
+        .map(|(i, citation)| {
+            let preceding = &resolved_full_cites[..preceding_full_cites[i]];
+            match citation {
+                citation if citation.is_full() => None,
+                Citation::ShortCase { .. } => {
+                    _resolve_shortcase_citation(citation, preceding, abbreviations)
+                }
+                Citation::Supra { .. } => _resolve_supra_note(citation, preceding, notes)
+                    .or_else(|| _resolve_supra_citation(citation, preceding, abbreviations)),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let mut resolutions: Resolutions<R> = HashMap::new();
+    let mut last_resolution: Option<R> = None;
+
+    for (i, citation) in citations.iter().enumerate() {
+        if i > 0
+            && citation.source().document_section() != citations[i - 1].source().document_section()
+        {
+            last_resolution = None;
+        }
+
+        let resolution = match citation {
+            citation if citation.is_full() => full_resolutions[i].take(),
+            Citation::Id { .. } => {
+                _resolve_id_citation(citation, last_resolution.as_ref(), &resolutions)
+            }
+            _ => short_resolutions[i].take(),
+        };
+
+        if let Some(resolution) = &resolution {
+            resolutions
+                .entry(resolution.clone())
+                .or_default()
+                .push(citation);
+        }
+        last_resolution = resolution;
+    }
+
+    resolutions
+}
+
+/// The citations of one document that refer to a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentOccurrences<D> {
+    pub document: D,
+    /// Start and stop offsets of each citation, in the order they were found.
+    pub spans: Vec<(usize, usize)>,
+}
+
+/// A resource cited somewhere in a corpus, and where it was cited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusResource<D, R> {
+    pub resource: R,
+    /// Documents citing the resource, in the order they were resolved.
+    pub documents: Vec<DocumentOccurrences<D>>,
+}
+
+/// Resources cited across a corpus of documents, deduplicated, in the order
+/// they were first cited. Returned by [`resolve_corpus`].
+#[derive(Debug, Clone)]
+pub struct CorpusResolutions<D, R> {
+    pub resources: Vec<CorpusResource<D, R>>,
+    indexes: HashMap<R, usize>,
+}
+
+impl<D, R: Eq + Hash> CorpusResolutions<D, R> {
+    /// Where a resource was cited, if anywhere.
+    pub fn get(&self, resource: &R) -> Option<&CorpusResource<D, R>> {
+        self.indexes.get(resource).map(|&i| &self.resources[i])
+    }
+}
+
+/// Resolve the citations of each document in a corpus, like
+/// [`resolve_citations`], into one table of resources with the places each
+/// document cites them, for building a citator index. Full citations are
+/// resolved with [`resolve_canonical_citation`]. Short form citations only
+/// ever refer to full citations in the same document.
+pub fn resolve_corpus<'a, D: Clone, C: AsRef<[Citation<'a>]>>(
+    documents: impl IntoIterator<Item = (D, C)>,
+) -> CorpusResolutions<D, Resource> {
+    resolve_corpus_with(documents, resolve_canonical_citation)
+}
+
+/// Like [`resolve_corpus`], resolving full citations with
+/// `resolve_full_citation`. See [`resolve_citations_with`].
+pub fn resolve_corpus_with<'a, D: Clone, C: AsRef<[Citation<'a>]>, R: Clone + Eq + Hash>(
+    documents: impl IntoIterator<Item = (D, C)>,
+    mut resolve_full_citation: impl FnMut(&Citation<'a>) -> R,
+) -> CorpusResolutions<D, R> {
+    let mut corpus = CorpusResolutions {
+        resources: Vec::new(),
+        indexes: HashMap::new(),
+    };
+
+    for (document, citations) in documents {
+        let resolutions = resolve_citations_with(citations.as_ref(), &mut resolve_full_citation);
+
+        // Add resources in the order they are first cited in the document
+        let mut resolutions: Vec<_> = resolutions.into_iter().collect();
+        resolutions.sort_by_key(|(_, citations)| citations[0].source().index);
+
+        for (resource, citations) in resolutions {
+            let i = match corpus.indexes.get(&resource) {
+                Some(&i) => i,
+                None => {
+                    corpus
+                        .indexes
+                        .insert(resource.clone(), corpus.resources.len());
+                    corpus.resources.push(CorpusResource {
+                        resource,
+                        documents: Vec::new(),
+                    });
+                    corpus.resources.len() - 1
+                }
+            };
+            corpus.resources[i].documents.push(DocumentOccurrences {
+                document: document.clone(),
+                spans: citations.iter().map(|c| c.source().span()).collect(),
+            });
+        }
+    }
+
+    corpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        antecedent_ids, citation_ids, footnote_targets, resolve_canonical_citation,
+        resolve_citations, resolve_citations_with_abbreviations, resolve_citations_with_notes,
+        resolve_corpus, resolve_full_citation, NoteTarget, Resource,
+    };
+    use crate::find::{get_citations, get_citations_with_options, FindOptions};
+    use crate::footnotes::{find_footnotes, FootnoteStyle};
+    use crate::names::Abbreviations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn resolve_short_forms() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (1800). Bar, 1 U.S., at 2. Id. at 3. \
+            Baz v. Qux, 2 U.S. 5 (1801). Bar, supra, at 4. Id. at 400.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 6);
+
+        let resolutions = resolve_citations(&citations);
+        assert_eq!(resolutions.len(), 2);
+
+        let foo = &resolutions[&resolve_full_citation(&citations[0])];
+        let spans: Vec<_> = foo.iter().map(|c| c.source().index).collect();
+        let expected: Vec<_> = citations[..3]
+            .iter()
+            .chain(&citations[4..5])
+            .map(|c| c.source().index)
+            .collect();
+        // the last id. cite's pin cite is too far from the first page
+        assert_eq!(spans, expected);
+        assert_eq!(resolutions[&resolve_full_citation(&citations[3])].len(), 1);
+    }
+
+    #[test]
+    fn resolve_hereinafter() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Smith v. Jones, 1 U.S. 1 (1800) [hereinafter Jones Report]. \
+            Foo v. Bar, 2 U.S. 5 (1801). Jones Report, supra, at 4.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+        assert_eq!(citations[0].source().hereinafter(), Some("Jones Report"));
+
+        let resolutions = resolve_citations(&citations);
+        let jones = &resolutions[&resolve_full_citation(&citations[0])];
+        assert_eq!(jones.len(), 2);
+        assert_eq!(jones[1].source().index, citations[2].source().index);
+    }
+
+    #[test]
+    fn resolve_abbreviated_names() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Smith v. Acme Manufacturing Co., 1 U.S. 1 (1800). \
+            Foo v. Bar, 2 U.S. 5 (1801). Mfg., supra, at 4.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+
+        let resolutions = resolve_citations(&citations);
+        assert_eq!(resolutions[&resolve_full_citation(&citations[0])].len(), 2);
+
+        let resolutions = resolve_citations_with_abbreviations(
+            &citations,
+            resolve_full_citation,
+            &Abbreviations::empty(),
+        );
+        assert_eq!(resolutions[&resolve_full_citation(&citations[0])].len(), 1);
+    }
+
+    #[test]
+    fn resolve_across_corpus() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let first = "Foo v. Bar, 1 U.S. 1 (1800). Id. at 2.";
+        let second = "Baz v. Qux, 2 U.S. 5 (1801). See Foo v. Bar, 1 U. S. 1, 3 (1800).";
+        let documents = [
+            ("first", get_citations(first, false, &tokenizer)),
+            ("second", get_citations(second, false, &tokenizer)),
+        ];
+        let foo = resolve_canonical_citation(&documents[0].1[0]);
+        assert_eq!(foo, resolve_canonical_citation(&documents[1].1[1]));
+
+        let corpus = resolve_corpus(documents.iter().map(|(id, c)| (*id, c)));
+        assert_eq!(corpus.resources.len(), 2);
+        assert_eq!(corpus.resources[0].resource, foo);
+
+        let occurrences: Vec<_> = corpus
+            .get(&foo)
+            .unwrap()
+            .documents
+            .iter()
+            .map(|occurrences| (occurrences.document, occurrences.spans.len()))
+            .collect();
+        assert_eq!(occurrences, vec![("first", 2), ("second", 1)]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn resolve_in_parallel() {
+        use super::par_resolve_citations;
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Foo v. Bar, 1 U.S. 1 (1800). Bar, 1 U.S., at 2. Id. at 3. \
+            Baz v. Qux, 2 U.S. 5 (1801). Bar, supra, at 4. Id. at 400. \
+            Smith v. Jones, 1 U.S. 9 (1802). Jones, supra. Bar, 1 U.S., at 5. Id.";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let indexes = |resolutions: super::Resolutions<'_, '_, super::Resource>| {
+            let mut indexes: Vec<Vec<usize>> = resolutions
+                .into_values()
+                .map(|cites| cites.iter().map(|c| c.source().index).collect())
+                .collect();
+            indexes.sort();
+            indexes
+        };
+        assert_eq!(
+            indexes(par_resolve_citations(&citations)),
+            indexes(resolve_citations(&citations))
+        );
+    }
+
+    #[test]
+    fn placeholder_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Fulton v. Philadelphia, ___ U.S. ___, 141 S. Ct. 1868 (2021); \
+            Smith v. Jones, ___ U.S. ___, 141 S. Ct. 1163 (2021). Id. at 1165. \
+            Doe v. Roe, ___ U.S. ___ (2022).";
+        let citations = get_citations(text, false, &tokenizer);
+        assert!(citations[0].source().has_placeholder());
+
+        let resolutions = resolve_citations(&citations);
+        let mut texts: Vec<Vec<_>> = resolutions
+            .values()
+            .map(|citations| {
+                citations
+                    .iter()
+                    .map(|c| c.source().matched_text())
+                    .collect()
+            })
+            .collect();
+        texts.sort();
+        assert_eq!(
+            texts,
+            vec![
+                vec!["___ U.S. ___", "141 S. Ct. 1163", "Id."],
+                vec!["___ U.S. ___", "141 S. Ct. 1868"],
+            ]
+        );
+        assert!(resolutions
+            .keys()
+            .all(|resource| resource.groups["volume"] == "141"));
+    }
+
+    #[test]
+    fn resolve_within_sections() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973). Id. at 115.\n\n\
+            Dissent: Id. at 116. Wade, supra, at 117.";
+        let dissent = text.find("Dissent").unwrap();
+
+        let citations = get_citations(text, false, &tokenizer);
+        let resolutions = resolve_citations(&citations);
+        assert_eq!(resolutions[&resolve_full_citation(&citations[0])].len(), 4);
+
+        let options = FindOptions {
+            sections: vec![(0, dissent), (dissent, text.len())],
+            ..Default::default()
+        };
+        let citations = get_citations_with_options(text, &options, &tokenizer);
+        let sections: Vec<_> = citations
+            .iter()
+            .map(|c| c.source().document_section())
+            .collect();
+        assert_eq!(sections, vec![Some(0), Some(0), Some(1), Some(1)]);
+
+        let resolutions = resolve_citations(&citations);
+        assert_eq!(resolutions.len(), 1);
+        assert_eq!(resolutions[&resolve_full_citation(&citations[0])].len(), 2);
+    }
+
+    #[test]
+    fn resolve_supra_notes() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "The rule is settled.[fn1] It was extended.[fn2]\n\
+            [fn1] See Smith v. Jones, 1 U.S. 1 (1800).\n\
+            [fn2] Doe v. Roe, 2 U.S. 2 (1801); Roe, supra note 1, at 5.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citations.len(), 3);
+        assert_eq!(citations[2].source().note(), Some("1"));
+
+        // The antecedent alone would resolve the supra to Doe v. Roe
+        let footnotes = find_footnotes(text, FootnoteStyle::Bracketed);
+        let resolutions = resolve_citations_with_notes(
+            &citations,
+            resolve_full_citation,
+            &Abbreviations::default(),
+            &footnote_targets(&footnotes),
+        );
+        let smith = &resolutions[&resolve_full_citation(&citations[0])];
+        assert!(smith.iter().any(|c| std::ptr::eq(*c, &citations[2])));
+        let doe = &resolutions[&resolve_full_citation(&citations[1])];
+        assert_eq!(doe.len(), 1);
+
+        let known = Resource {
+            groups: [("id".to_string(), "smith".to_string())].into(),
+        };
+        let notes = [("1".to_string(), NoteTarget::Resource(known.clone()))].into();
+        let resolutions = resolve_citations_with_notes(
+            &citations,
+            resolve_full_citation,
+            &Abbreviations::default(),
+            &notes,
+        );
+        assert_eq!(resolutions[&known].len(), 1);
+        assert!(std::ptr::eq(resolutions[&known][0], &citations[2]));
+    }
+
+    #[test]
+    fn antecedents() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U.S. 113 (1973). Foo v. Bar, 1 U.S. 1. Id. at 2. \
+            Roe, 410 U.S., at 115. Wade, supra, at 116. Baz, supra.";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(citation_ids(&citations), vec![0, 1, 2, 3, 4, 5]);
+
+        let mut reversed = citations.clone();
+        reversed.reverse();
+        assert_eq!(citation_ids(&reversed), vec![5, 4, 3, 2, 1, 0]);
+
+        let resolutions = resolve_citations(&citations);
+        assert_eq!(
+            antecedent_ids(&citations, &resolutions),
+            vec![None, None, Some(1), Some(0), Some(0), None]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+/*!
+A lightweight sentence segmenter, used to keep the metadata found around a
+citation, like its case name or parenthetical, from bleeding into the
+sentences before or after it.
+ */
+
+/// Words that end with a period without ending a sentence, like the "Inc."
+/// in "Acme Inc. v. Smith".
+pub const ABBREVIATIONS: [&str; 25] = [
+    "Inc", "Co", "Corp", "Ltd", "Bros", "Ass'n", "Dep't", "Gov't", "Comm'n", "Nat'l", "Int'l",
+    "Bd", "Educ", "Ins", "Mfg", "No", "Nos", "Mr", "Mrs", "Ms", "Dr", "Jr", "Sr", "St", "Cf",
+];
+
+const OPENERS: [char; 6] = ['"', '\'', '“', '‘', '(', '['];
+const CLOSERS: [char; 6] = ['"', '\'', '”', '’', ')', ']'];
+
+/// Return true if the word before a period is an abbreviation rather than
+/// the end of a sentence: a known one, a single letter like "v.", or one
+/// with inner periods like "U.S.".
+pub fn is_abbreviation(word: &str) -> bool {
+    let word = word.trim_start_matches(OPENERS);
+    let mut chars = word.chars();
+    let is_letter = match (chars.next(), chars.next()) {
+        (None, _) => true,
+        (Some(c), None) => c.is_alphabetic(),
+        _ => false,
+    };
+
+    is_letter || word.contains('.') || ABBREVIATIONS.contains(&word)
+}
+
+/// If the punctuation at `end` ends a sentence, return the offset where the
+/// next sentence starts.
+fn next_sentence_start(text: &str, end: usize) -> Option<usize> {
+    let word = text[..end].rsplit(char::is_whitespace).next()?;
+    if is_abbreviation(word) {
+        return None;
+    }
+
+    let rest = text[end + 1..].trim_start_matches(CLOSERS);
+    let next = rest.trim_start();
+    if next.len() == rest.len() {
+        // no space after the punctuation, like "1.5"
+        return None;
+    }
+
+    next.trim_start_matches(OPENERS)
+        .starts_with(char::is_uppercase)
+        .then(|| text.len() - next.len())
+}
+
+/// Offsets in the text where new sentences start, in order.
+///
+/// A sentence ends with a period, question mark or exclamation point,
+/// optionally followed by closing quotes, and then whitespace and a
+/// capitalized word. Periods ending abbreviations (see [`is_abbreviation`])
+/// or inside parentheses, like "(Cal. Ct. App. 1990)", don't count.
+pub fn sentence_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut depth = 0usize;
+
+    text.char_indices().filter_map(move |(i, c)| match c {
+        '(' | '[' => {
+            depth += 1;
+            None
+        }
+        ')' | ']' => {
+            depth = depth.saturating_sub(1);
+            None
+        }
+        '.' | '!' | '?' if depth == 0 => next_sentence_start(text, i),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sentence_starts;
+
+    #[test]
+    fn sentences() {
+        let text = "See Acme Inc. v. Doe, 1 U.S. 1 (Cal. Ct. App. 1990). \
+            Id. at 2. \"Quoted.\" Roe, supra.";
+        let starts: Vec<_> = sentence_starts(text).map(|i| &text[i..i + 3]).collect();
+
+        assert_eq!(starts, vec!["Id.", "\"Qu", "Roe"]);
+    }
+}
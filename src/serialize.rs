@@ -0,0 +1,444 @@
+/*!
+Serialize citations with serde, behind the `serde` feature.
+
+Citations borrow the text they were found in and keep the tokens they were
+found from, so rather than implementing `Serialize` themselves, they are
+wrapped by [`Citation::serializable`] along with [`SerializeOptions`].
+Editions are serialized by name, or with [`SerializeOptions::editions`],
+along with their reporter's name, type and dates, so consumers don't need
+`reporters_db` to make sense of them.
+
+Serialize all the citations of a document with
+[`SerializableCitations`], which also records the version of the
+`reporters_db` data they were found with, so results can be reproduced,
+and gives each citation its [`CitationId`]. Short case, supra and id
+citations can link to the full citation they were resolved to by ID, with
+[`SerializableCitations::with_antecedents`], so consumers don't need to
+resolve citations again.
+
+The tokens a document was split into can be serialized too, wrapped by
+[`SerializableTokens`], to attach to bug reports about the find phase. See
+[`dump_tokens`](crate::tokenizers::models::dump_tokens) for a more compact
+text format.
+ */
+
+use crate::find::models::{Citation, CitationKind, CitationMetadata};
+use crate::resolve::{citation_ids, CitationId};
+use crate::tokenizers::extractors::{self, ReporterEdition};
+use crate::tokenizers::models::Token;
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashSet};
+
+/// How to serialize citations.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SerializeOptions {
+    /// Serialize the editions a citation might refer to with the metadata
+    /// of their reporter, rather than just their names.
+    pub editions: bool,
+}
+
+/// A citation to serialize, along with how. See [`Citation::serializable`].
+#[derive(Debug, Clone, Copy)]
+pub struct SerializableCitation<'c, 'a> {
+    citation: &'c Citation<'a>,
+    options: SerializeOptions,
+    id: Option<CitationId>,
+    antecedent: Option<CitationId>,
+}
+
+impl<'a> Citation<'a> {
+    /// Wrap the citation to serialize it with serde.
+    pub fn serializable(&self, options: SerializeOptions) -> SerializableCitation<'_, 'a> {
+        SerializableCitation {
+            citation: self,
+            options,
+            id: None,
+            antecedent: None,
+        }
+    }
+}
+
+impl Serialize for SerializableCitation<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let source = self.citation.source();
+        let groups: BTreeMap<_, _> = source
+            .groups
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+            .collect();
+        let editions: Vec<_> = source
+            .editions()
+            .map(|editions| editions.iter().collect())
+            .unwrap_or_default();
+
+        let mut state = serializer.serialize_struct("Citation", 8)?;
+        match self.id {
+            Some(id) => state.serialize_field("id", &id)?,
+            None => state.skip_field("id")?,
+        }
+        match self.antecedent {
+            Some(antecedent) => state.serialize_field("antecedent", &antecedent)?,
+            None => state.skip_field("antecedent")?,
+        }
+        state.serialize_field("kind", &self.citation.kind())?;
+        state.serialize_field("span", &source.span())?;
+        state.serialize_field("matched_text", source.matched_text())?;
+        state.serialize_field("groups", &groups)?;
+        state.serialize_field("metadata", &Metadata(&source.metadata))?;
+        if self.options.editions {
+            let editions: Vec<_> = editions.into_iter().map(Edition).collect();
+            state.serialize_field("editions", &editions)?;
+        } else {
+            let editions: Vec<_> = editions.iter().map(|e| e.short_name.as_str()).collect();
+            state.serialize_field("editions", &editions)?;
+        }
+        state.end()
+    }
+}
+
+/// Citations to serialize, along with the version of the `reporters_db`
+/// data they were found with, as `{"reporters_db_version": ..., "citations":
+/// [...]}`. Each citation has an `"id"`, and with
+/// [`with_antecedents`](Self::with_antecedents), short forms that were
+/// resolved have the ID of their full citation as their `"antecedent"`.
+#[derive(Debug, Clone, Copy)]
+pub struct SerializableCitations<'c, 'a> {
+    citations: &'c [Citation<'a>],
+    options: SerializeOptions,
+    reporters_db_version: &'c str,
+    antecedents: Option<&'c [Option<CitationId>]>,
+}
+
+impl<'c, 'a> SerializableCitations<'c, 'a> {
+    /// Wrap citations found with the built-in extractors to serialize them
+    /// with serde.
+    pub fn new(citations: &'c [Citation<'a>], options: SerializeOptions) -> Self {
+        Self {
+            citations,
+            options,
+            reporters_db_version: extractors::version(),
+            antecedents: None,
+        }
+    }
+
+    /// Link citations to the full citations they refer back to, given the
+    /// IDs [`antecedent_ids`](crate::resolve::antecedent_ids) found for
+    /// them.
+    pub fn with_antecedents(mut self, antecedents: &'c [Option<CitationId>]) -> Self {
+        self.antecedents = Some(antecedents);
+        self
+    }
+
+    /// Record another version of the `reporters_db` data, like that of the
+    /// [`ReportersSnapshot`](crate::tokenizers::extractors::ReportersSnapshot)
+    /// the extractors were built from.
+    pub fn with_reporters_db_version(mut self, version: &'c str) -> Self {
+        self.reporters_db_version = version;
+        self
+    }
+}
+
+impl Serialize for SerializableCitations<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let citations: Vec<_> = self
+            .citations
+            .iter()
+            .zip(citation_ids(self.citations))
+            .enumerate()
+            .map(|(i, (citation, id))| SerializableCitation {
+                id: Some(id),
+                antecedent: self.antecedents.and_then(|a| a.get(i).copied().flatten()),
+                ..citation.serializable(self.options)
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("Citations", 2)?;
+        state.serialize_field("reporters_db_version", self.reporters_db_version)?;
+        state.serialize_field("citations", &citations)?;
+        state.end()
+    }
+}
+
+/// Kinds are serialized by their snake case name, like "full_case", spelled
+/// out so renaming a variant doesn't change the output.
+impl Serialize for CitationKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (index, name) = match self {
+            CitationKind::Resource => (0, "resource"),
+            CitationKind::Law => (1, "law"),
+            CitationKind::Journal => (2, "journal"),
+            CitationKind::Case => (3, "case"),
+            CitationKind::FullCase => (4, "full_case"),
+            CitationKind::ShortCase => (5, "short_case"),
+            CitationKind::Supra => (6, "supra"),
+            CitationKind::Infra => (7, "infra"),
+            CitationKind::CrossReference => (8, "cross_reference"),
+            CitationKind::Record => (9, "record"),
+            CitationKind::Id => (10, "id"),
+            CitationKind::SlipOpinion => (11, "slip_opinion"),
+            CitationKind::Unknown => (12, "unknown"),
+            CitationKind::Custom => (13, "custom"),
+        };
+        serializer.serialize_unit_variant("CitationKind", index, name)
+    }
+}
+
+/// The metadata of a citation, as a map keyed by the snake case name of
+/// each kind of metadata.
+struct Metadata<'s, 'a>(&'s HashSet<CitationMetadata<'a>>);
+
+impl Serialize for Metadata<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut metadata: Vec<_> = self.0.iter().collect();
+        metadata.sort();
+
+        let mut candidates = Vec::new();
+        let mut custom = BTreeMap::new();
+        let mut map = serializer.serialize_map(None)?;
+        for m in metadata {
+            match m {
+                CitationMetadata::Parenthetical(v) => map.serialize_entry("parenthetical", v)?,
+                CitationMetadata::PinCite(v) => map.serialize_entry("pin_cite", v)?,
+                CitationMetadata::Year(v) => map.serialize_entry("year", v)?,
+                CitationMetadata::Court(v) => map.serialize_entry("court", v)?,
+                CitationMetadata::Plaintiff(v) => map.serialize_entry("plaintiff", v)?,
+                CitationMetadata::Defendant(v) => map.serialize_entry("defendant", v)?,
+                CitationMetadata::Extra(v) => map.serialize_entry("extra", v)?,
+                CitationMetadata::AntecedentGuess(v) => {
+                    map.serialize_entry("antecedent_guess", v)?
+                }
+                CitationMetadata::Volume(v) => map.serialize_entry("volume", v)?,
+                CitationMetadata::DocketNumber(v) => map.serialize_entry("docket_number", v)?,
+                CitationMetadata::Reference(v) => map.serialize_entry("reference", v)?,
+                CitationMetadata::Note(v) => map.serialize_entry("note", v)?,
+                CitationMetadata::Publisher(v) => map.serialize_entry("publisher", v)?,
+                CitationMetadata::Status(v) => map.serialize_entry("status", v)?,
+                CitationMetadata::Signal(v) => map.serialize_entry("signal", v)?,
+                CitationMetadata::StringCite(i) => map.serialize_entry("string_cite", i)?,
+                CitationMetadata::EnclosingCitation(i) => {
+                    map.serialize_entry("enclosing_citation", i)?
+                }
+                CitationMetadata::Quotation(start, end) => {
+                    map.serialize_entry("quotation", &(start, end))?
+                }
+                CitationMetadata::Quoted => map.serialize_entry("quoted", &true)?,
+                CitationMetadata::PinCiteSpan(start, end) => {
+                    map.serialize_entry("pin_cite_span", &(start, end))?
+                }
+                CitationMetadata::YearSpan(start, end) => {
+                    map.serialize_entry("year_span", &(start, end))?
+                }
+                CitationMetadata::ParentheticalSpan(start, end) => {
+                    map.serialize_entry("parenthetical_span", &(start, end))?
+                }
+                CitationMetadata::Hereinafter(v) => map.serialize_entry("hereinafter", v)?,
+                CitationMetadata::Month(v) => map.serialize_entry("month", v)?,
+                CitationMetadata::Day(v) => map.serialize_entry("day", v)?,
+                CitationMetadata::Ambiguous => map.serialize_entry("ambiguous", &true)?,
+                CitationMetadata::CandidateEdition(v) => candidates.push(v),
+                CitationMetadata::EditionChoice(choice) => {
+                    map.serialize_entry("edition_choice", choice.as_str())?
+                }
+                CitationMetadata::FuzzyReporter(v) => map.serialize_entry("fuzzy_reporter", v)?,
+                CitationMetadata::Disposition(v) => map.serialize_entry("disposition", v)?,
+                CitationMetadata::Judge(v) => map.serialize_entry("judge", v)?,
+                CitationMetadata::OpinionType(v) => map.serialize_entry("opinion_type", v)?,
+                CitationMetadata::DocumentSection(i) => {
+                    map.serialize_entry("document_section", i)?
+                }
+                CitationMetadata::JudgeTitle(title) => {
+                    map.serialize_entry("judge_title", title.abbreviation())?
+                }
+                CitationMetadata::OpinionRole(role) => {
+                    map.serialize_entry("opinion_role", role.as_str())?
+                }
+                CitationMetadata::Custom(key, value) => {
+                    custom.insert(key, value);
+                }
+            }
+        }
+        if !candidates.is_empty() {
+            map.serialize_entry("candidate_editions", &candidates)?;
+        }
+        if !custom.is_empty() {
+            map.serialize_entry("custom", &custom)?;
+        }
+        map.end()
+    }
+}
+
+/// An edition along with the metadata of its reporter, with dates as
+/// "YYYY-MM-DD".
+struct Edition<'e>(&'e ReporterEdition);
+
+impl Serialize for Edition<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let edition = self.0;
+        let date = |date: Option<chrono::NaiveDateTime>| date.map(|d| d.date().to_string());
+
+        let mut state = serializer.serialize_struct("Edition", 6)?;
+        state.serialize_field("reporter", &edition.reporter)?;
+        state.serialize_field("edition", &edition.short_name)?;
+        state.serialize_field("name", &edition.name)?;
+        state.serialize_field("cite_type", &edition.cite_type)?;
+        state.serialize_field("start", &date(edition.edition.start))?;
+        state.serialize_field("end", &date(edition.edition.end))?;
+        state.end()
+    }
+}
+
+/// Tokens to serialize, like those returned by
+/// [`Tokenizer::tokenize`](crate::tokenizers::Tokenizer::tokenize). Words
+/// are serialized with their text, and other tokens with their span, the
+/// text they matched, their groups and the names of the editions they might
+/// refer to.
+#[derive(Debug, Clone, Copy)]
+pub struct SerializableTokens<'t, 'a>(pub &'t [Token<'a>]);
+
+impl Serialize for SerializableTokens<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for token in self.0 {
+            seq.serialize_element(&SerializableToken(token))?;
+        }
+        seq.end()
+    }
+}
+
+struct SerializableToken<'t, 'a>(&'t Token<'a>);
+
+impl Serialize for SerializableToken<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.0.kind_name())?;
+        if let Token::Word(word) = self.0 {
+            map.serialize_entry("text", word)?;
+        }
+        if let Some(data) = self.0.try_data() {
+            let groups: BTreeMap<_, _> = data.groups.iter().map(|(n, v)| (n.as_str(), v)).collect();
+            let editions: Vec<_> = data
+                .editions()
+                .iter()
+                .map(|edition| edition.short_name.as_str())
+                .collect();
+            map.serialize_entry("span", &(data.start, data.end))?;
+            map.serialize_entry("text", data.data)?;
+            map.serialize_entry("groups", &groups)?;
+            map.serialize_entry("editions", &editions)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SerializableCitations, SerializableTokens, SerializeOptions};
+    use crate::find::get_citations;
+    use crate::resolve::{antecedent_ids, resolve_citations};
+    use crate::tokenizers::extractors::version;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, Tokenizer};
+    use serde_json::json;
+
+    #[test]
+    fn serialize_citations() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations("See Roe v. Wade, 410 U.S. 113 (1973)", false, &tokenizer);
+        let (start, end) = citations[0].source().span();
+
+        let value =
+            serde_json::to_value(citations[0].serializable(SerializeOptions::default())).unwrap();
+        assert_eq!(value["kind"], "full_case");
+        assert_eq!(value["span"], json!([start, end]));
+        assert_eq!(
+            value["groups"],
+            json!({"page": "113", "reporter": "U.S.", "volume": "410"})
+        );
+        assert_eq!(value["metadata"]["year"], "1973");
+        assert_eq!(value["metadata"]["plaintiff"], "Roe");
+        assert_eq!(value["editions"], json!(["U.S."]));
+
+        let options = SerializeOptions { editions: true };
+        let value = serde_json::to_value(citations[0].serializable(options)).unwrap();
+        let edition = &value["editions"][0];
+        assert_eq!(edition["reporter"], "U.S.");
+        assert_eq!(edition["edition"], "U.S.");
+        assert_eq!(edition["name"], "United States Supreme Court Reports");
+        assert_eq!(edition["cite_type"], "federal");
+        assert!(edition["start"].is_string());
+    }
+
+    #[test]
+    fn serialize_tokens() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let (all_tokens, _) = tokenizer.tokenize("See 410 U.S. 113");
+
+        let value = serde_json::to_value(SerializableTokens(&all_tokens)).unwrap();
+        assert_eq!(
+            value,
+            json!([
+                {"kind": "Word", "text": "See"},
+                {"kind": "Space"},
+                {
+                    "kind": "Citation",
+                    "span": [4, 16],
+                    "text": "410 U.S. 113",
+                    "groups": {"page": "113", "reporter": "U.S.", "volume": "410"},
+                    "editions": ["U.S."],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn serialize_reporters_db_version() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations("410 U.S. 113. Id. at 115.", false, &tokenizer);
+
+        let serializable = SerializableCitations::new(&citations, SerializeOptions::default());
+        let value = serde_json::to_value(serializable).unwrap();
+        assert_eq!(value["reporters_db_version"], version());
+        assert_eq!(value["citations"][1]["kind"], "id");
+
+        let value = serde_json::to_value(serializable.with_reporters_db_version("0.0.4")).unwrap();
+        assert_eq!(value["reporters_db_version"], "0.0.4");
+    }
+
+    #[test]
+    fn serialize_antecedents() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations(
+            "Roe, 410 U.S. 113. Id. at 115. Baz, supra.",
+            false,
+            &tokenizer,
+        );
+        let resolutions = resolve_citations(&citations);
+        let antecedents = antecedent_ids(&citations, &resolutions);
+
+        let serializable = SerializableCitations::new(&citations, SerializeOptions::default());
+        let value = serde_json::to_value(serializable).unwrap();
+        assert_eq!(value["citations"][1]["id"], 1);
+        assert!(value["citations"][1].get("antecedent").is_none());
+
+        let value = serde_json::to_value(serializable.with_antecedents(&antecedents)).unwrap();
+        let ids: Vec<_> = value["citations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| (c["id"].clone(), c.get("antecedent").cloned()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (json!(0), None),
+                (json!(1), Some(json!(0))),
+                (json!(2), None)
+            ]
+        );
+        // A single citation has no ID
+        let value =
+            serde_json::to_value(citations[0].serializable(SerializeOptions::default())).unwrap();
+        assert!(value.get("id").is_none());
+    }
+}
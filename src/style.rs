@@ -0,0 +1,172 @@
+/*!
+Render corrected citations in the conventions of a style guide, like the
+Bluebook or the California Style Manual, so products don't have to rewrite
+eyecite's output.
+
+[`CitationSource::corrected_citation`] renders the volume, the reporter
+from the edition guess, the page, the pin cite and the year. Styles differ
+in where the year goes, whether reporter abbreviations are spaced, and
+whether page ranges in pin cites drop repeated digits.
+ */
+
+use crate::find::models::CitationSource;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// Only ASCII digits, since ranges are shortened and expanded by byte.
+    static ref PAGE_RANGE_RE: Regex =
+        Regex::new(r"([0-9]+)[-–]([0-9]+)").expect("page range regex should compile");
+}
+
+/// A style guide to render citations in.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum CitationStyle {
+    /// "410 U.S. 113, 115-16 (1973)": ranges drop repeated digits but keep
+    /// two (rule 3.2(a)).
+    #[default]
+    Bluebook,
+    /// "410 U.S. 113, 115-116 (1973)": like the Bluebook, keeping every
+    /// digit of ranges.
+    Alwd,
+    /// "(1973) 410 U.S. 113, 115-116": the year comes first, reporter
+    /// abbreviations are closed up, like "Cal.App.4th", and ranges keep
+    /// every digit.
+    CaliforniaStyleManual,
+}
+
+impl CitationStyle {
+    /// Rewrite the page ranges of a pin cite, like "115-16" or "115-116",
+    /// the way the style wants them.
+    fn pin_cite(&self, pin_cite: &str) -> String {
+        PAGE_RANGE_RE
+            .replace_all(pin_cite, |captures: &regex::Captures| {
+                let (start, end) = (&captures[1], &captures[2]);
+                let end = match self {
+                    CitationStyle::Bluebook => shorten_range(start, end),
+                    CitationStyle::Alwd | CitationStyle::CaliforniaStyleManual => {
+                        expand_range(start, end)
+                    }
+                };
+                format!("{start}-{end}")
+            })
+            .into_owned()
+    }
+
+    fn reporter(&self, reporter: &str) -> String {
+        match self {
+            CitationStyle::CaliforniaStyleManual => reporter.replace(' ', ""),
+            CitationStyle::Bluebook | CitationStyle::Alwd => reporter.to_string(),
+        }
+    }
+}
+
+/// The end of a range with every digit, like "116" for "115-16".
+fn expand_range<'e>(start: &str, end: &'e str) -> std::borrow::Cow<'e, str> {
+    if end.len() < start.len() {
+        format!("{}{end}", &start[..start.len() - end.len()]).into()
+    } else {
+        end.into()
+    }
+}
+
+/// The end of a range without the digits it repeats from the start,
+/// keeping at least two, like "16" for "115-116".
+fn shorten_range<'e>(start: &str, end: &'e str) -> std::borrow::Cow<'e, str> {
+    let end = expand_range(start, end);
+    if end.len() != start.len() {
+        return end;
+    }
+    let repeated = start
+        .bytes()
+        .zip(end.bytes())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(end.len().saturating_sub(2));
+    end[repeated..].to_string().into()
+}
+
+impl CitationSource<'_> {
+    /// The citation with its official reporter, like "410 U.S. 113, 115
+    /// (1973)" for "410 U. S. 113, 115 (1973)", rendered in the Bluebook's
+    /// style. Returns `None` for citations without a volume and page.
+    pub fn corrected_citation(&self) -> Option<String> {
+        self.corrected_citation_in(CitationStyle::default())
+    }
+
+    /// Like [`corrected_citation`](Self::corrected_citation), rendered in
+    /// the given style.
+    pub fn corrected_citation_in(&self, style: CitationStyle) -> Option<String> {
+        let volume = self.groups.get("volume")?;
+        let page = self.groups.get("page")?;
+        let reporter = style.reporter(self.corrected_reporter()?);
+
+        let mut citation = format!("{volume} {reporter} {page}");
+        if let Some(pin_cite) = self.pin_cite() {
+            let pin_cite = pin_cite.strip_prefix("at ").unwrap_or(pin_cite);
+            citation = format!("{citation}, {}", style.pin_cite(pin_cite));
+        }
+        Some(match (style, self.year()) {
+            (_, None) => citation,
+            (CitationStyle::CaliforniaStyleManual, Some(year)) => format!("({year}) {citation}"),
+            (CitationStyle::Bluebook | CitationStyle::Alwd, Some(year)) => {
+                format!("{citation} ({year})")
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_range, shorten_range, CitationStyle};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(shorten_range("115", "116"), "16");
+        assert_eq!(shorten_range("115", "16"), "16");
+        assert_eq!(shorten_range("1115", "1120"), "20");
+        assert_eq!(shorten_range("99", "101"), "101");
+        assert_eq!(expand_range("115", "16"), "116");
+        assert_eq!(expand_range("99", "101"), "101");
+
+        // Other digits are left alone rather than sliced mid-character
+        let style = CitationStyle::Alwd;
+        assert_eq!(style.pin_cite("١١٥-6"), "١١٥-6");
+        assert_eq!(style.pin_cite("115-٦"), "115-٦");
+        assert_eq!(CitationStyle::Bluebook.pin_cite("١١٥-١١٦"), "١١٥-١١٦");
+    }
+
+    #[test]
+    fn styles() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "Roe v. Wade, 410 U. S. 113, 115-116 (1973); Smith v. Jones, \
+            1 So. 2d 5, 7-8.";
+        let citations = get_citations(text, false, &tokenizer);
+        let rendered = |style| -> Vec<_> {
+            citations
+                .iter()
+                .map(|c| c.source().corrected_citation_in(style).unwrap())
+                .collect()
+        };
+
+        assert_eq!(
+            rendered(CitationStyle::Bluebook),
+            vec!["410 U.S. 113, 115-16 (1973)", "1 So. 2d 5, 7-8"]
+        );
+        assert_eq!(
+            rendered(CitationStyle::Alwd),
+            vec!["410 U.S. 113, 115-116 (1973)", "1 So. 2d 5, 7-8"]
+        );
+        assert_eq!(
+            rendered(CitationStyle::CaliforniaStyleManual),
+            vec!["(1973) 410 U.S. 113, 115-116", "1 So.2d 5, 7-8"]
+        );
+        assert_eq!(
+            citations[0].source().corrected_citation().as_deref(),
+            Some("410 U.S. 113, 115-16 (1973)")
+        );
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+Suggest corrected citation strings for near misses: text that is almost a
+citation, so editor integrations can offer quick fixes.
+
+Two kinds of near misses are found: a volume and a known reporter without
+a page, like "410 U.S. (1973)", and citations whose reporter is one
+character off from a known one, found by a
+[`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer). See
+[`diagnostics::suggestion_diagnostics`](crate::diagnostics::suggestion_diagnostics)
+to publish them.
+ */
+
+use crate::find::models::Citation;
+use crate::regexes::MISSING_PAGE_REGEX;
+use crate::tokenizers::extractors::TokenExtractor;
+use crate::tokenizers::models::TokenFactories;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+
+lazy_static! {
+    static ref MISSING_PAGE_RE: Regex =
+        Regex::new(MISSING_PAGE_REGEX).expect("missing page regex should compile");
+}
+
+/// What is wrong with a near miss.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum NearMiss {
+    /// A volume and a reporter without a page. The suggestion fills the
+    /// page in with a placeholder, like "410 U.S. ___".
+    MissingPage,
+    /// A citation whose reporter is one character off from a known one.
+    /// The suggestion uses the known reporter.
+    FuzzyReporter,
+}
+
+/// A suggested replacement for a span of text.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Suggestion {
+    pub span: (usize, usize),
+    pub near_miss: NearMiss,
+    pub replacement: String,
+}
+
+/// The corrected citation string for a citation found with a fuzzy matched
+/// reporter, like "1 So.2d 5" for "1 SO.2d 5".
+pub fn fuzzy_suggestion(citation: &Citation) -> Option<Suggestion> {
+    let source = citation.source();
+    let known = source.fuzzy_reporter()?;
+    let volume = source.groups.get("volume")?;
+    let page = source.groups.get("page")?;
+
+    Some(Suggestion {
+        span: source.matched_span(),
+        near_miss: NearMiss::FuzzyReporter,
+        replacement: format!("{volume} {known} {page}"),
+    })
+}
+
+/// Finds near misses, knowing the reporter strings of the extractors.
+#[derive(Debug, Clone, Default)]
+pub struct Suggester {
+    reporters: HashSet<String>,
+}
+
+impl Suggester {
+    /// Know the reporter strings of the full citation extractors in `items`.
+    pub fn new(items: &[TokenExtractor]) -> Self {
+        let reporters = items
+            .iter()
+            .filter(|e| {
+                e.token_factory == TokenFactories::Citation && !e.extra.short && !e.extra.law
+            })
+            .flat_map(|e| e.strings.iter().cloned())
+            .collect();
+
+        Self { reporters }
+    }
+
+    /// Volumes and known reporters without a page, outside of the
+    /// citations found in the text.
+    pub fn missing_pages(&self, text: &str, citations: &[Citation]) -> Vec<Suggestion> {
+        let spans: Vec<_> = citations.iter().map(|c| c.source().span()).collect();
+
+        MISSING_PAGE_RE
+            .captures_iter(text)
+            .filter_map(|captures| {
+                let (volume, reporter) = (captures.name("volume")?, captures.name("reporter")?);
+                let span = (volume.start(), reporter.end());
+                if !self.reporters.contains(reporter.as_str())
+                    || spans
+                        .iter()
+                        .any(|&(start, end)| start < span.1 && span.0 < end)
+                {
+                    return None;
+                }
+
+                Some(Suggestion {
+                    span,
+                    near_miss: NearMiss::MissingPage,
+                    replacement: format!("{} {} ___", volume.as_str(), reporter.as_str()),
+                })
+            })
+            .collect()
+    }
+
+    /// All near misses in a text, in the order they appear.
+    pub fn suggest(&self, text: &str, citations: &[Citation]) -> Vec<Suggestion> {
+        let mut suggestions = self.missing_pages(text, citations);
+        suggestions.extend(citations.iter().filter_map(fuzzy_suggestion));
+        suggestions.sort_by_key(|s| s.span);
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NearMiss, Suggester};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::{Ahocorasick, FuzzyTokenizer};
+
+    #[test]
+    fn near_misses() {
+        let tokenizer = FuzzyTokenizer::new(
+            Ahocorasick::new(EXTRACTORS.as_slice()).unwrap(),
+            EXTRACTORS.as_slice(),
+        );
+        let text = "See Roe v. Wade, 410 U.S. (1973); Smith, 1 SO.2d 5; 2 U.S. 3; 7 Foo. (1990).";
+        let citations = get_citations(text, false, &tokenizer);
+
+        let suggestions = Suggester::new(EXTRACTORS.as_slice()).suggest(text, &citations);
+        let found: Vec<_> = suggestions
+            .iter()
+            .map(|s| {
+                (
+                    &text[s.span.0..s.span.1],
+                    s.near_miss,
+                    s.replacement.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("410 U.S.", NearMiss::MissingPage, "410 U.S. ___"),
+                ("1 SO.2d 5", NearMiss::FuzzyReporter, "1 So.2d 5"),
+            ]
+        );
+    }
+}
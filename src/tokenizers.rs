@@ -1,7 +1,18 @@
-use crate::tokenizers::extractors::TokenExtractor;
-use crate::tokenizers::models::{Token, Tokens};
+use crate::helpers;
+use crate::limits::InputLimits;
+use crate::regexes::FUZZY_CITATION_REGEX;
+use crate::tokenizers::extractors::{Editions, TokenExtractor, TokenExtractorExtra};
+use crate::tokenizers::models::{GroupName, Token, TokenData, TokenFactories, TokenKinds, Tokens};
 use crate::EyeciteError;
-use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+lazy_static! {
+    static ref FUZZY_CITATION_RE: Regex =
+        Regex::new(FUZZY_CITATION_REGEX).expect("fuzzy citation regex should compile");
+}
 
 pub mod extractors;
 pub mod models;
@@ -11,129 +22,903 @@ pub trait Tokenizer<'a> {
         -> Box<dyn Iterator<Item = &'a TokenExtractor> + 'a>;
 
     fn extract_tokens(&'a self, text: &'a str) -> Vec<Token<'a>> {
-        self.get_extractors(text)
-            .flat_map(|e| e.get_matches(text).into_iter().map(move |m| (e, m)))
-            .map(|(e, m)| e.get_token(m))
-            .collect()
+        let mut tokens = Vec::new();
+        self.extract_tokens_into(text, &mut tokens);
+        tokens
     }
 
-    fn tokenize(&'a self, text: &'a str) -> (Tokens<'a>, Vec<(usize, Token<'a>)>) {
-        let mut citation_tokens: Vec<(usize, Token)> = Vec::new();
-        let mut all_tokens: Vec<Token> = Vec::new();
-
-        let tokens = self.extract_tokens(text);
-        let mut last_token: Option<Token> = None;
-        let mut offset: usize = 0;
-
-        /// Split text into words, treating whitespace as a word, and append
-        /// to tokens. NOTE this is a significant portion of total runtime of
-        /// get_citations(), so benchmark if changing
-        fn append_text<'a>(tokens: &mut Vec<Token<'a>>, text: &'a str) {
-            for part in text.split(' ') {
-                // TODO: maybe filter repeated strings which will be empty
-                if !part.is_empty() {
-                    tokens.push(Token::Word(part));
-                    tokens.push(Token::Space);
-                } else {
-                    tokens.push(Token::Space);
-                }
-            }
+    /// Like [`extract_tokens`](Tokenizer::extract_tokens), appending to an
+    /// existing vector.
+    fn extract_tokens_into(&'a self, text: &'a str, tokens: &mut Vec<Token<'a>>) {
+        self.extract_tokens_of(text, TokenKinds::default(), tokens)
+    }
+
+    /// Like [`extract_tokens_into`](Tokenizer::extract_tokens_into), only
+    /// running the extractors of some kinds of tokens.
+    fn extract_tokens_of(&'a self, text: &'a str, kinds: TokenKinds, tokens: &mut Vec<Token<'a>>) {
+        tokens.extend(
+            self.get_extractors(text)
+                .filter(|e| kinds.allows(&e.token_factory))
+                .flat_map(|e| e.iter_matches(text).map(move |m| (e, m)))
+                .map(|(e, m)| e.get_token(m)),
+        )
+    }
 
-            tokens.pop(); // remove final extra space
+    /// Like [`extract_tokens_of`](Tokenizer::extract_tokens_of), giving up
+    /// when the limits on matches or time are exceeded.
+    fn try_extract_tokens(
+        &'a self,
+        text: &'a str,
+        kinds: TokenKinds,
+        limits: &InputLimits,
+    ) -> Result<Vec<Token<'a>>, EyeciteError> {
+        let mut tokens = Vec::new();
+
+        for extractor in self
+            .get_extractors(text)
+            .filter(|e| kinds.allows(&e.token_factory))
+        {
+            for (count, m) in extractor.iter_matches(text).enumerate() {
+                limits.check_matches(count + 1)?;
+                limits.check_deadline()?;
+                tokens.push(extractor.get_token(m));
+            }
+            limits.check_deadline()?;
         }
 
-        for token in tokens {
-            if let Some(last) = last_token.as_mut() {
-                // Sometimes the exact same cite is matched by two different
-                // regexes. Attempt to merge rather than discarding one or the
-                // other:
-                let merged = last.merge(&token);
-                if let Some(merged) = merged {
-                    citation_tokens.pop();
-                    all_tokens.pop();
+        Ok(tokens)
+    }
 
-                    citation_tokens.push((all_tokens.len(), merged.clone()));
-                    all_tokens.push(merged);
+    /// Find the citation tokens in text, sorted and without overlaps, but
+    /// skip splitting the rest of the text into words. This is much faster
+    /// when only the tokens and their offsets are needed. Words can be
+    /// split later, if needed, with [`split_words`].
+    fn tokenize_citations_only(&'a self, text: &'a str) -> Tokens<'a> {
+        self.tokenize_citations_of(text, TokenKinds::default())
+    }
 
-                    continue;
-                }
-            }
+    /// Like [`tokenize_citations_only`](Tokenizer::tokenize_citations_only),
+    /// only running the extractors of some kinds of tokens.
+    fn tokenize_citations_of(&'a self, text: &'a str, kinds: TokenKinds) -> Tokens<'a> {
+        let mut tokens = Vec::new();
+        self.extract_tokens_of(text, kinds, &mut tokens);
+        select_tokens(&mut tokens, &mut Vec::new());
+        tokens
+    }
+
+    fn tokenize(&'a self, text: &'a str) -> (Tokens<'a>, Vec<(usize, Token<'a>)>) {
+        let (all_tokens, citation_tokens, _) = self.tokenize_with_skipped(text);
+        (all_tokens, citation_tokens)
+    }
+
+    /// Like [`tokenize`](Tokenizer::tokenize), also returning the tokens that
+    /// were skipped because they overlap an earlier token.
+    fn tokenize_with_skipped(&'a self, text: &'a str) -> Tokenization<'a> {
+        split_tokens(text, self.extract_tokens(text))
+    }
+
+    /// Like [`tokenize_with_skipped`](Tokenizer::tokenize_with_skipped),
+    /// filling reusable buffers instead of allocating new vectors. Anything
+    /// already in the buffers is cleared first. Only the extractors of the
+    /// buffers' [`kinds`](TokenBuffers::kinds) are run.
+    fn tokenize_into(&'a self, text: &'a str, buffers: &mut TokenBuffers<'a>) {
+        buffers.clear();
+        self.extract_tokens_of(text, buffers.kinds, &mut buffers.extracted);
+        split_tokens_into(
+            text,
+            &mut buffers.extracted,
+            &mut buffers.words,
+            &mut buffers.citation_tokens,
+            &mut buffers.skipped,
+        );
+    }
+
+    /// Like [`tokenize_with_skipped`](Tokenizer::tokenize_with_skipped),
+    /// giving up when the limits on matches or time are exceeded.
+    fn try_tokenize(
+        &'a self,
+        text: &'a str,
+        kinds: TokenKinds,
+        limits: &InputLimits,
+    ) -> Result<Tokenization<'a>, EyeciteError> {
+        Ok(split_tokens(
+            text,
+            self.try_extract_tokens(text, kinds, limits)?,
+        ))
+    }
+}
+
+/// All tokens, the citation tokens along with their index in all tokens, and
+/// the tokens skipped because they overlap an earlier token.
+pub type Tokenization<'a> = (Tokens<'a>, Vec<(usize, Token<'a>)>, Tokens<'a>);
+
+/// Split text into words around the extracted tokens.
+fn split_tokens<'a>(text: &'a str, mut tokens: Vec<Token<'a>>) -> Tokenization<'a> {
+    let mut skipped: Vec<Token> = Vec::new();
+    let mut citation_tokens: Vec<(usize, Token)> = Vec::new();
+    let mut all_tokens: Vec<Token> = Vec::new();
+
+    split_tokens_into(
+        text,
+        &mut tokens,
+        &mut all_tokens,
+        &mut citation_tokens,
+        &mut skipped,
+    );
+
+    (all_tokens, citation_tokens, skipped)
+}
+
+/// Like [`split_tokens`], draining the extracted tokens and appending to
+/// existing vectors.
+fn split_tokens_into<'a>(
+    text: &'a str,
+    tokens: &mut Vec<Token<'a>>,
+    all_tokens: &mut Vec<Token<'a>>,
+    citation_tokens: &mut Vec<(usize, Token<'a>)>,
+    skipped: &mut Vec<Token<'a>>,
+) {
+    select_tokens(tokens, skipped);
+    split_words_into(text, tokens.drain(..), all_tokens, citation_tokens);
+}
 
-            if offset > token.start() {
+/// Sort extracted tokens, keeping only those that don't overlap an earlier
+/// token and moving the rest to `skipped`.
+fn select_tokens<'a>(tokens: &mut Vec<Token<'a>>, skipped: &mut Vec<Token<'a>>) {
+    tokens.sort_by_key(|t| (t.start(), Reverse(t.end())));
+    let mut selected = 0;
+    let mut offset: usize = 0;
+
+    for i in 0..tokens.len() {
+        if selected > 0 {
+            // Sometimes the exact same cite is matched by two different
+            // regexes. Attempt to merge rather than discarding one or the
+            // other:
+            if let Some(merged) = tokens[selected - 1].merge(&tokens[i]) {
+                tokens[selected - 1] = merged;
                 continue;
             }
+        }
+
+        if offset > tokens[i].start() {
+            skipped.push(tokens[i].clone());
+            continue;
+        }
+
+        // Tokens before `i` that weren't selected have been merged or
+        // skipped, so they can be swapped out of the way
+        offset = tokens[i].end();
+        tokens.swap(selected, i);
+        selected += 1;
+    }
 
-            if offset < token.start() {
-                // capture plain text before each match
-                append_text(&mut all_tokens, &text[offset..token.start()]);
+    tokens.truncate(selected);
+}
+
+/// Split text into words around tokens that are sorted and don't overlap,
+/// like those returned by [`Tokenizer::tokenize_citations_only`].
+/// Returns all tokens, and the citation tokens along with their index in
+/// all tokens.
+pub fn split_words<'a>(
+    text: &'a str,
+    tokens: impl IntoIterator<Item = Token<'a>>,
+) -> (Tokens<'a>, Vec<(usize, Token<'a>)>) {
+    let mut all_tokens = Vec::new();
+    let mut citation_tokens = Vec::new();
+    split_words_into(text, tokens, &mut all_tokens, &mut citation_tokens);
+
+    (all_tokens, citation_tokens)
+}
+
+/// Like [`split_words`], appending to existing vectors.
+fn split_words_into<'a>(
+    text: &'a str,
+    tokens: impl IntoIterator<Item = Token<'a>>,
+    all_tokens: &mut Vec<Token<'a>>,
+    citation_tokens: &mut Vec<(usize, Token<'a>)>,
+) {
+    let mut offset: usize = 0;
+
+    /// Split text into words, treating whitespace as a word, and append
+    /// to tokens. NOTE this is a significant portion of total runtime of
+    /// get_citations(), so benchmark if changing
+    fn append_text<'a>(tokens: &mut Vec<Token<'a>>, text: &'a str) {
+        // memchr finds spaces many bytes at a time, which is much faster than
+        // str::split on long stretches of plain text
+        let mut start = 0;
+        for space in memchr::memchr_iter(b' ', text.as_bytes()) {
+            if space > start {
+                tokens.push(Token::Word(&text[start..space]));
             }
+            tokens.push(Token::Space);
+            start = space + 1;
+        }
 
-            // capture match
-            citation_tokens.push((all_tokens.len(), token.clone()));
-            all_tokens.push(token.clone());
-            offset = token.end();
-            last_token = Some(token)
+        if start < text.len() {
+            tokens.push(Token::Word(&text[start..]));
         }
+    }
 
-        // capture plain text after final match
-        if offset < text.len() {
-            append_text(&mut all_tokens, &text[offset..]);
+    for token in tokens {
+        if offset < token.start() {
+            // capture plain text before each match
+            append_text(all_tokens, &text[offset..token.start()]);
         }
 
-        (all_tokens, citation_tokens)
+        // capture match
+        offset = token.end();
+        citation_tokens.push((all_tokens.len(), token.clone()));
+        all_tokens.push(token);
+    }
+
+    // capture plain text after final match
+    if offset < text.len() {
+        append_text(all_tokens, &text[offset..]);
     }
 }
 
-pub struct Ahocorasick<'a> {
-    extractors: HashMap<String, Vec<&'a TokenExtractor>>,
-    strings: Vec<String>,
-    corasick: daachorse::DoubleArrayAhoCorasick,
+/// Reuse the allocation of a vector for the tokens of another document.
+#[allow(clippy::unnecessary_filter_map)]
+fn recycle<'b>(mut tokens: Vec<Token<'_>>) -> Vec<Token<'b>> {
+    tokens.clear();
+    // Collecting from the emptied vector reuses its allocation, since the
+    // layout of its elements doesn't change
+    tokens.into_iter().filter_map(|_| None).collect()
 }
 
-impl<'a> Ahocorasick<'a> {
-    pub fn new(items: &'a [TokenExtractor]) -> Result<Self, EyeciteError> {
-        let mut extractors: HashMap<String, Vec<_>> = HashMap::new();
+/// Like [`recycle`], for citation tokens and their indexes.
+#[allow(clippy::unnecessary_filter_map)]
+fn recycle_indexed<'b>(mut tokens: Vec<(usize, Token<'_>)>) -> Vec<(usize, Token<'b>)> {
+    tokens.clear();
+    tokens.into_iter().filter_map(|_| None).collect()
+}
 
-        for e in items {
-            for s in e.strings.iter().cloned() {
-                let _v = extractors.entry(s).or_default().push(e);
-            }
+/// Vectors filled by [`Tokenizer::tokenize_into`], which can be reused
+/// between documents to avoid growing new ones for each.
+#[derive(Debug, Default)]
+pub struct TokenBuffers<'a> {
+    /// Tokens found by the extractors, before splitting the text into words.
+    extracted: Vec<Token<'a>>,
+    /// All tokens, like the first vector returned by
+    /// [`tokenize`](Tokenizer::tokenize).
+    pub words: Tokens<'a>,
+    /// Citation tokens along with their index in `words`.
+    pub citation_tokens: Vec<(usize, Token<'a>)>,
+    /// Tokens skipped because they overlap an earlier token.
+    pub skipped: Tokens<'a>,
+    /// Kinds of tokens to extract, see [`TokenKinds::default`].
+    pub kinds: TokenKinds,
+}
+
+impl<'a> TokenBuffers<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffers that only extract some kinds of tokens.
+    pub fn with_kinds(kinds: TokenKinds) -> Self {
+        Self {
+            kinds,
+            ..Default::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.extracted.clear();
+        self.words.clear();
+        self.citation_tokens.clear();
+        self.skipped.clear();
+    }
+
+    /// Empty the buffers, keeping their allocations for tokens of a document
+    /// with a different lifetime.
+    pub fn recycle<'b>(self) -> TokenBuffers<'b> {
+        TokenBuffers {
+            extracted: recycle(self.extracted),
+            words: recycle(self.words),
+            citation_tokens: recycle_indexed(self.citation_tokens),
+            skipped: recycle(self.skipped),
+            kinds: self.kinds,
+        }
+    }
+}
+
+/// Scratch space for tokenizing documents, reused from one document to the
+/// next so batch pipelines don't grow fresh token vectors for every one.
+///
+/// Tokenizing a large opinion pushes a token for every word, so reusing the
+/// vectors saves most of the allocations outside of the citations
+/// themselves. See [`get_citations_in`](crate::find::get_citations_in).
+#[derive(Debug, Default)]
+pub struct TokenArena {
+    buffers: TokenBuffers<'static>,
+}
+
+impl TokenArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tokenize text using the arena's vectors, passing all tokens and the
+    /// citation tokens along with their index to `f`. The vectors are
+    /// emptied and kept for the next document once `f` returns.
+    pub fn with_tokens<'a, R>(
+        &mut self,
+        text: &'a str,
+        tokenizer: &'a dyn Tokenizer<'a>,
+        kinds: TokenKinds,
+        f: impl FnOnce(&[Token<'a>], &mut Vec<(usize, Token<'a>)>) -> R,
+    ) -> R {
+        let mut buffers = std::mem::take(&mut self.buffers).recycle();
+        buffers.kinds = kinds;
+
+        tokenizer.tokenize_into(text, &mut buffers);
+        let result = f(&buffers.words, &mut buffers.citation_tokens);
+
+        self.buffers = buffers.recycle();
+        result
+    }
+}
+
+pub use daachorse::MatchKind;
+
+/**
+How [`Ahocorasick`] scans text for trigger strings.
+
+Scanning without overlaps reports one string wherever several overlap,
+like "So." and "So. 2d" in "1 So. 2d 1", so the extractors of the others
+don't run unless their strings show up again elsewhere in the text. An
+overlapping scan reports every string, even those inside other hits, so it
+runs every extractor that could match, at the cost of running more
+extractors, and of a slower scan on texts dense with trigger strings.
+
+Pipelines that care most about recall, like building research datasets,
+should scan with overlaps, and servers that care most about latency
+without.
+
+The `scan` group of the `tokenize` benchmark,
+`cargo bench --bench tokenize -- scan`, measures the throughput of each
+scan on `tests/assets/opinion.txt`, an 87 KB opinion, and prints how many
+extractors each one runs on it. An overlapping scan always runs at least
+the extractors a leftmost longest one does, which the tests check on the
+same opinion. Each extra extractor runs its regex over the whole text, so
+the cost of an overlapping scan grows with how many distinct reporters a
+text mentions. Run the benchmark on your own texts before switching.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Scan {
+    /// Find strings without overlaps, choosing between overlapping strings
+    /// with the match kind. See [`Ahocorasick::with_match_kind`].
+    NonOverlapping(MatchKind),
+    /// Find every string, even those overlapping others.
+    Overlapping,
+}
+
+impl Default for Scan {
+    fn default() -> Self {
+        Scan::NonOverlapping(MatchKind::LeftmostLongest)
+    }
+}
+
+impl Scan {
+    /// The match kind to build automata with. Overlapping scans are only
+    /// supported by [`MatchKind::Standard`] automata.
+    fn match_kind(&self) -> MatchKind {
+        match self {
+            Scan::NonOverlapping(match_kind) => *match_kind,
+            Scan::Overlapping => MatchKind::Standard,
         }
+    }
+}
 
-        let strings: Vec<_> = extractors.keys().cloned().collect();
+/// Size of one of the automata of an [`Ahocorasick`] tokenizer. See
+/// [`Ahocorasick::automaton_stats`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct AutomatonStats {
+    /// Whether the automaton's strings are found in lowercased text.
+    pub case_insensitive: bool,
+    pub strings: usize,
+    pub states: usize,
+    pub heap_bytes: usize,
+}
+
+/// Trigger strings of some extractors, along with an automaton to find
+/// them. Building an automaton without any strings fails, so there is no
+/// automaton without strings.
+///
+/// Strings are added to the automaton in sorted order, so the string that
+/// wins under [`MatchKind::LeftmostFirst`] is the same on every run.
+struct Triggers {
+    /// Indexes of the extractors each string triggers, by the string's index
+    /// in the automaton.
+    extractors: Vec<Vec<usize>>,
+    corasick: Option<daachorse::DoubleArrayAhoCorasick>,
+    scan: Scan,
+}
 
-        let corasick = daachorse::DoubleArrayAhoCorasickBuilder::new().build(strings.as_slice())?;
+impl Triggers {
+    fn new(strings: BTreeMap<String, Vec<usize>>, scan: Scan) -> Result<Self, EyeciteError> {
+        let (strings, extractors): (Vec<_>, Vec<_>) = strings.into_iter().unzip();
+        let corasick = if strings.is_empty() {
+            None
+        } else {
+            Some(
+                daachorse::DoubleArrayAhoCorasickBuilder::new()
+                    .match_kind(scan.match_kind())
+                    .build(strings.as_slice())?,
+            )
+        };
 
         Ok(Self {
             extractors,
-            strings,
             corasick,
+            scan,
+        })
+    }
+
+    /// Indexes of the extractors triggered by strings found in text.
+    fn find(&self, text: &str, found: &mut Vec<usize>) {
+        let Some(corasick) = &self.corasick else {
+            return;
+        };
+        let hits: Box<dyn Iterator<Item = daachorse::Match>> = match self.scan {
+            Scan::Overlapping => Box::new(corasick.find_overlapping_iter(text)),
+            Scan::NonOverlapping(MatchKind::Standard) => Box::new(corasick.find_iter(text)),
+            Scan::NonOverlapping(_) => Box::new(corasick.leftmost_find_iter(text)),
+        };
+        for hit in hits {
+            found.extend(&self.extractors[hit.value()]);
+        }
+    }
+
+    fn stats(&self, case_insensitive: bool) -> Option<AutomatonStats> {
+        let corasick = self.corasick.as_ref()?;
+
+        Some(AutomatonStats {
+            case_insensitive,
+            strings: self.extractors.len(),
+            states: corasick.num_states(),
+            heap_bytes: corasick.heap_bytes(),
         })
     }
 }
 
+/// Tokenizer that only runs the extractors whose trigger strings, like
+/// reporter names, appear in the text.
+///
+/// Strings of case insensitive extractors are found in lowercased text. Each
+/// extractor is run once, no matter how many times its strings are found.
+/// Extractors without any strings are always run.
+pub struct Ahocorasick<'a> {
+    items: &'a [TokenExtractor],
+    unfiltered: Vec<usize>,
+    case_sensitive: Triggers,
+    case_insensitive: Triggers,
+}
+
+impl<'a> Ahocorasick<'a> {
+    /// Find trigger strings with [`MatchKind::LeftmostLongest`]. See
+    /// [`Ahocorasick::with_match_kind`].
+    pub fn new(items: &'a [TokenExtractor]) -> Result<Self, EyeciteError> {
+        Self::with_match_kind(items, MatchKind::LeftmostLongest)
+    }
+
+    /**
+    Build a tokenizer that finds trigger strings with the given match kind.
+
+    Strings are found without overlaps, so the match kind decides which
+    extractors run when strings overlap, like "So." and "So. 2d" in
+    "1 So. 2d 1":
+
+    - [`MatchKind::LeftmostLongest`] finds the longest string starting at
+      the leftmost position, "So. 2d", so only the extractors of the edition
+      actually cited are run. This is the default.
+    - [`MatchKind::LeftmostFirst`] finds the string that was added first
+      among those starting at the leftmost position. Strings are added in
+      sorted order, so this is the one that sorts first, "So.".
+    - [`MatchKind::Standard`] finds the string that ends first, "So.", which
+      runs the extractors of the shorter string instead, and misses the
+      longer one unless it shows up again elsewhere.
+
+    To find every string instead, see [`Scan::Overlapping`].
+     */
+    pub fn with_match_kind(
+        items: &'a [TokenExtractor],
+        match_kind: MatchKind,
+    ) -> Result<Self, EyeciteError> {
+        Self::with_scan(items, Scan::NonOverlapping(match_kind))
+    }
+
+    /// Build a tokenizer that scans for trigger strings with or without
+    /// overlaps. See [`Scan`].
+    pub fn with_scan(items: &'a [TokenExtractor], scan: Scan) -> Result<Self, EyeciteError> {
+        let mut case_sensitive: BTreeMap<String, Vec<_>> = BTreeMap::new();
+        let mut case_insensitive: BTreeMap<String, Vec<_>> = BTreeMap::new();
+        let mut unfiltered = Vec::new();
+
+        for (i, e) in items.iter().enumerate() {
+            if e.strings.is_empty() {
+                unfiltered.push(i);
+            }
+            for s in e.strings.iter() {
+                if e.ignore_case {
+                    case_insensitive
+                        .entry(s.to_lowercase())
+                        .or_default()
+                        .push(i);
+                } else {
+                    case_sensitive.entry(s.clone()).or_default().push(i);
+                }
+            }
+        }
+
+        Ok(Self {
+            items,
+            unfiltered,
+            case_sensitive: Triggers::new(case_sensitive, scan)?,
+            case_insensitive: Triggers::new(case_insensitive, scan)?,
+        })
+    }
+
+    /// Sizes of the automata trigger strings are found with, the case
+    /// sensitive one first.
+    pub fn automaton_stats(&self) -> Vec<AutomatonStats> {
+        self.case_sensitive
+            .stats(false)
+            .into_iter()
+            .chain(self.case_insensitive.stats(true))
+            .collect()
+    }
+}
+
 impl<'a> Tokenizer<'a> for Ahocorasick<'a> {
     fn get_extractors(
         &'a self,
         text: &'a str,
     ) -> Box<dyn Iterator<Item = &'a TokenExtractor> + 'a> {
-        Box::new(self.corasick.find_iter(text).flat_map(|m| {
-            self.extractors[self.strings[m.value()].as_str()]
-                .as_slice()
-                .iter()
-                .copied()
-        }))
+        let mut found = self.unfiltered.clone();
+        self.case_sensitive.find(text, &mut found);
+        // Lowercasing copies the whole text, so skip it without any strings
+        if self.case_insensitive.corasick.is_some() {
+            self.case_insensitive.find(&text.to_lowercase(), &mut found);
+        }
+
+        // Each extractor runs over the whole text, so running it again for
+        // another occurrence of its strings would only find the same tokens
+        let mut seen = vec![false; self.items.len()];
+        found.retain(|&i| !std::mem::replace(&mut seen[i], true));
+
+        Box::new(found.into_iter().map(move |i| &self.items[i]))
+    }
+}
+
+/// Tokenizer that runs every extractor over the whole text, like python's
+/// default `Tokenizer`.
+///
+/// This is much slower than [`Ahocorasick`] on real documents, but simpler,
+/// so it is handy on small inputs and for measuring the recall lost to
+/// prefiltering.
+pub struct ExhaustiveTokenizer<'a> {
+    items: &'a [TokenExtractor],
+}
+
+impl<'a> ExhaustiveTokenizer<'a> {
+    pub fn new(items: &'a [TokenExtractor]) -> Self {
+        Self { items }
+    }
+}
+
+impl<'a> Tokenizer<'a> for ExhaustiveTokenizer<'a> {
+    fn get_extractors(
+        &'a self,
+        _text: &'a str,
+    ) -> Box<dyn Iterator<Item = &'a TokenExtractor> + 'a> {
+        Box::new(self.items.iter())
+    }
+}
+
+/// Whether two strings are at most one insertion, deletion, substitution or
+/// swap of adjacent characters apart.
+fn within_one_edit(a: &str, b: &str) -> bool {
+    // Bytes of the common prefix, and then of the common suffix of what's
+    // left, which both end on char boundaries
+    let common = |a: &mut dyn Iterator<Item = char>, b: &mut dyn Iterator<Item = char>| -> usize {
+        a.zip(b)
+            .take_while(|(x, y)| x == y)
+            .map(|(x, _)| x.len_utf8())
+            .sum()
+    };
+    let prefix = common(&mut a.chars(), &mut b.chars());
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = common(&mut a.chars().rev(), &mut b.chars().rev());
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    match (a.chars().nth(2), b.chars().nth(2)) {
+        // At most two characters differ on each side
+        (None, None) => match (a.chars().count(), b.chars().count()) {
+            (0..=1, 0..=1) => true,
+            // Two adjacent characters swapped
+            (2, 2) => a.chars().eq(b.chars().rev()),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The three pieces of a string, split by characters, that a string one
+/// edit away from it keeps at least one of, since an edit changes at most
+/// two adjacent pieces. Strings shorter than three characters are their own
+/// piece.
+fn edit_pieces(string: &str) -> Vec<&str> {
+    let bounds: Vec<_> = string
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([string.len()])
+        .collect();
+    let chars = bounds.len() - 1;
+    if chars < 3 {
+        return vec![string];
+    }
+
+    let (first, second) = (bounds[chars / 3], bounds[2 * chars / 3]);
+    vec![&string[..first], &string[first..second], &string[second..]]
+}
+
+/// Most bytes a fuzzy matched citation extends past either side of an edit
+/// piece of its reporter: the rest of a reporter of up to 31 characters,
+/// and a volume or page of up to six digits of up to four bytes each,
+/// along with the comma and spaces around them.
+const FUZZY_WINDOW: usize = 64;
+
+/**
+Tokenizer that also finds full citations whose reporter string is one
+character off from a known one, like "1 F.Supp,2d 5" or "1 SO.2d 5", after
+running another tokenizer.
+
+Citations found this way have
+[`CitationMetadata::FuzzyReporter`](crate::find::models::CitationMetadata::FuzzyReporter)
+with the reporter string they were matched to, so they can be reviewed.
+Only reporters with a period are matched, and only when exactly one known
+string is close enough, but this still finds more false positives than the
+exact extractors, so it is opt in.
+
+Citations are only looked for around the hits of an automaton of pieces of
+the known reporter strings, which any string one edit away keeps one of,
+and are only compared to known strings of about the same length that
+start or end with the same character.
+ */
+pub struct FuzzyTokenizer<T> {
+    inner: T,
+    /// Reporter strings of full citation extractors, along with the extra
+    /// data of the first extractor matching each.
+    reporters: Vec<(String, TokenExtractorExtra)>,
+    /// Indexes of reporters by their length in characters and their first
+    /// character, and by their length and last character. Strings one edit
+    /// apart share one or the other.
+    by_first: HashMap<(usize, char), Vec<usize>>,
+    by_last: HashMap<(usize, char), Vec<usize>>,
+    /// Edit pieces of the reporter strings. See [`edit_pieces`].
+    pieces: Option<daachorse::DoubleArrayAhoCorasick>,
+}
+
+impl<T> FuzzyTokenizer<T> {
+    /// Wrap a tokenizer, fuzzy matching the reporter strings of the
+    /// citation extractors in `items`.
+    pub fn new(inner: T, items: &[TokenExtractor]) -> Self {
+        let mut reporters = Vec::new();
+        let mut seen = HashSet::new();
+
+        for extractor in items.iter().filter(|e| {
+            e.token_factory == TokenFactories::Citation && !e.extra.short && !e.extra.law
+        }) {
+            let mut strings: Vec<_> = extractor.strings.iter().collect();
+            strings.sort();
+            for string in strings {
+                if seen.insert(string) {
+                    let extra = TokenExtractorExtra {
+                        fuzzy: Some(string.clone()),
+                        ..extractor.extra.clone()
+                    };
+                    reporters.push((string.clone(), extra));
+                }
+            }
+        }
+
+        let mut by_first: HashMap<_, Vec<_>> = HashMap::new();
+        let mut by_last: HashMap<_, Vec<_>> = HashMap::new();
+        let mut pieces = HashSet::new();
+        for (i, (string, _)) in reporters.iter().enumerate() {
+            let (Some(first), Some(last)) = (string.chars().next(), string.chars().last()) else {
+                continue;
+            };
+            let length = string.chars().count();
+            by_first.entry((length, first)).or_default().push(i);
+            by_last.entry((length, last)).or_default().push(i);
+            pieces.extend(edit_pieces(string));
+        }
+        let pieces: Vec<_> = pieces.into_iter().collect();
+        let pieces = daachorse::DoubleArrayAhoCorasick::new(pieces).ok();
+
+        Self {
+            inner,
+            reporters,
+            by_first,
+            by_last,
+            pieces,
+        }
+    }
+
+    /// The known reporters within one edit of a reporter string.
+    fn close_reporters<'s>(
+        &'s self,
+        reporter: &'s str,
+    ) -> impl Iterator<Item = &'s (String, TokenExtractorExtra)> + 's {
+        let length = reporter.chars().count();
+        let ends = reporter.chars().next().zip(reporter.chars().last());
+        let mut candidates: Vec<usize> = ends
+            .into_iter()
+            .flat_map(|(first, last)| {
+                (length.saturating_sub(1)..=length + 1).flat_map(move |length| {
+                    let first = self.by_first.get(&(length, first));
+                    let last = self.by_last.get(&(length, last));
+                    first.into_iter().chain(last).flatten().copied()
+                })
+            })
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates
+            .into_iter()
+            .map(|i| &self.reporters[i])
+            .filter(move |(known, _)| within_one_edit(reporter, known))
+    }
+
+    /// Spans of the text around the edit pieces of known reporters, merged
+    /// where they overlap. The end of each is moved past the word it's in,
+    /// so matches in it end where they would in the whole text.
+    fn fuzzy_windows(&self, text: &str) -> Vec<(usize, usize)> {
+        let Some(pieces) = &self.pieces else {
+            return Vec::new();
+        };
+        let mut windows: Vec<(usize, usize)> = Vec::new();
+
+        for hit in pieces.find_overlapping_iter(text) {
+            let start =
+                helpers::floor_char_boundary(text, hit.start().saturating_sub(FUZZY_WINDOW));
+            let end = helpers::ceil_char_boundary(text, (hit.end() + FUZZY_WINDOW).min(text.len()));
+            let end = text[end..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .map_or(text.len(), |i| end + i);
+
+            match windows.last_mut() {
+                Some(last) if start <= last.1 => *last = (last.0.min(start), last.1.max(end)),
+                _ => windows.push((start, end)),
+            }
+        }
+
+        windows
+    }
+
+    /// Citation tokens with a fuzzy matched reporter that don't overlap the
+    /// citation tokens already found.
+    fn fuzzy_tokens<'a>(&'a self, text: &'a str, tokens: &[Token<'a>]) -> Vec<Token<'a>> {
+        let found: Vec<_> = tokens
+            .iter()
+            .filter(|t| matches!(t, Token::Citation(_)))
+            .map(|t| (t.start(), t.end()))
+            .collect();
+        let mut fuzzy = Vec::new();
+
+        let window_captures = self
+            .fuzzy_windows(text)
+            .into_iter()
+            .flat_map(|(start, end)| {
+                // Searching from the start of the window, in the text, keeps
+                // the word boundary before the volume
+                let window = &text[..end];
+                let mut at = start;
+                std::iter::from_fn(move || {
+                    let captures = FUZZY_CITATION_RE.captures_at(window, at)?;
+                    at = captures.get(0)?.end();
+                    Some(captures)
+                })
+            });
+        for captures in window_captures {
+            let m = captures.get_match();
+            let (Some(volume), Some(reporter), Some(page)) = (
+                captures.name("volume"),
+                captures.name("reporter"),
+                captures.name("page"),
+            ) else {
+                continue;
+            };
+            if !reporter.as_str().contains('.')
+                || found
+                    .iter()
+                    .any(|&(start, end)| start < m.end() && m.start() < end)
+            {
+                continue;
+            }
+
+            let mut close = self.close_reporters(reporter.as_str());
+            // Exact matches are left to the wrapped tokenizer
+            let (Some((known, extra)), None) = (close.next(), close.next()) else {
+                continue;
+            };
+            if known == reporter.as_str() {
+                continue;
+            }
+
+            fuzzy.push(Token::Citation(TokenData {
+                data: m.as_str(),
+                start: m.start(),
+                end: m.end(),
+                extra,
+                merged: Vec::new(),
+                editions: Editions::of(extra),
+                groups: [
+                    (GroupName::Volume, volume.as_str()),
+                    (GroupName::Reporter, reporter.as_str()),
+                    (GroupName::Page, page.as_str()),
+                ]
+                .into_iter()
+                .collect(),
+            }));
+        }
+
+        fuzzy
+    }
+}
+
+impl<'a, T: Tokenizer<'a> + 'a> Tokenizer<'a> for FuzzyTokenizer<T> {
+    fn get_extractors(
+        &'a self,
+        text: &'a str,
+    ) -> Box<dyn Iterator<Item = &'a TokenExtractor> + 'a> {
+        self.inner.get_extractors(text)
+    }
+
+    fn extract_tokens_of(&'a self, text: &'a str, kinds: TokenKinds, tokens: &mut Vec<Token<'a>>) {
+        self.inner.extract_tokens_of(text, kinds, tokens);
+        if kinds.allows(&TokenFactories::Citation) {
+            let fuzzy = self.fuzzy_tokens(text, tokens);
+            tokens.extend(fuzzy);
+        }
+    }
+
+    fn try_extract_tokens(
+        &'a self,
+        text: &'a str,
+        kinds: TokenKinds,
+        limits: &InputLimits,
+    ) -> Result<Vec<Token<'a>>, EyeciteError> {
+        let mut tokens = self.inner.try_extract_tokens(text, kinds, limits)?;
+        if kinds.allows(&TokenFactories::Citation) {
+            let fuzzy = self.fuzzy_tokens(text, &tokens);
+            tokens.extend(fuzzy);
+            limits.check_deadline()?;
+        }
+
+        Ok(tokens)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::extractors::EXTRACTORS;
-    use crate::tokenizers::extractors::TokenExtractorExtra;
-    use crate::tokenizers::models::{Token, TokenData};
-    use crate::tokenizers::{Ahocorasick, Tokenizer};
+    use crate::tokenizers::extractors::{
+        Editions, ReporterEdition, TokenExtractor, TokenExtractorExtra,
+    };
+    use crate::tokenizers::models::{dump_tokens, Token, TokenData, TokenFactories};
+    use crate::tokenizers::{
+        edit_pieces, split_words, within_one_edit, Ahocorasick, ExhaustiveTokenizer,
+        FuzzyTokenizer, Scan, TokenBuffers, Tokenizer,
+    };
     use reporters_db::laws::NaiveDateTime;
+    use reporters_db::regexes::ResolvedRegex;
     use reporters_db::reporters::Edition;
+    use std::collections::HashSet;
     use std::str::FromStr;
 
     #[test]
@@ -146,16 +931,26 @@ mod tests {
             exact_editions: vec![],
             variation_editions: vec![],
             short: false,
+            law: false,
+            fuzzy: None,
         };
 
         let edition_extra = TokenExtractorExtra {
             exact_editions: vec![],
-            variation_editions: vec![Edition {
-                end: None,
-                start: Some(NaiveDateTime::from_str("1875-01-01T00:00:00").unwrap()),
-                regexes: None,
+            variation_editions: vec![ReporterEdition {
+                reporter: "U.S.".into(),
+                short_name: "U.S.".into(),
+                name: "United States Supreme Court Reports".into(),
+                cite_type: "federal".into(),
+                edition: Edition {
+                    end: None,
+                    start: Some(NaiveDateTime::from_str("1875-01-01T00:00:00").unwrap()),
+                    regexes: None,
+                },
             }],
             short: false,
+            law: false,
+            fuzzy: None,
         };
 
         let see_token = Token::StopWord(TokenData {
@@ -163,7 +958,9 @@ mod tests {
             start: 0,
             end: 3,
             extra: &stop_word_extra,
+            editions: Editions::of(&stop_word_extra),
             groups: vec![("stop_word".into(), "See")].into_iter().collect(),
+            merged: Vec::new(),
         });
 
         let v_token = Token::StopWord(TokenData {
@@ -171,7 +968,9 @@ mod tests {
             start: 8,
             end: 10,
             extra: &stop_word_extra,
+            editions: Editions::of(&stop_word_extra),
             groups: vec![("stop_word".into(), "v")].into_iter().collect(),
+            merged: Vec::new(),
         });
 
         let us_citation = Token::Citation(TokenData {
@@ -179,6 +978,7 @@ mod tests {
             start: 17,
             end: 30,
             extra: &edition_extra,
+            editions: Editions::of(&edition_extra),
             groups: vec![
                 ("reporter".into(), "U. S."),
                 ("volume".into(), "410"),
@@ -186,6 +986,7 @@ mod tests {
             ]
             .into_iter()
             .collect(),
+            merged: Vec::new(),
         });
 
         let expected_tokens = vec![
@@ -205,4 +1006,254 @@ mod tests {
         assert_eq!(all_tokens, expected_tokens);
         assert_eq!(tokens, vec![(0, see_token), (4, v_token), (8, us_citation)]);
     }
+
+    #[test]
+    fn tokenize_into() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let mut buffers = TokenBuffers::new();
+
+        for text in [
+            "See Roe v. Wade, 410 U. S. 113 (1973)",
+            "1 U.S. 1, 2 U.S. 2",
+        ] {
+            let (all_tokens, tokens, skipped) = tokenizer.tokenize_with_skipped(text);
+            tokenizer.tokenize_into(text, &mut buffers);
+
+            assert_eq!(buffers.words, all_tokens);
+            assert_eq!(buffers.citation_tokens, tokens);
+            assert_eq!(buffers.skipped, skipped);
+        }
+    }
+
+    #[test]
+    fn split_spaces() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+
+        let (all_tokens, _) = tokenizer.tokenize(" foo  bar ");
+        assert_eq!(
+            all_tokens,
+            vec![
+                Token::Space,
+                Token::Word("foo"),
+                Token::Space,
+                Token::Space,
+                Token::Word("bar"),
+                Token::Space,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_citations_only() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "See Roe v. Wade, 410 U. S. 113 (1973)";
+
+        let tokens = tokenizer.tokenize_citations_only(text);
+        let spans: Vec<_> = tokens.iter().map(|t| &text[t.start()..t.end()]).collect();
+        assert_eq!(spans, vec!["See", "v.", "410 U. S. 113"]);
+
+        assert_eq!(split_words(text, tokens), tokenizer.tokenize(text));
+    }
+
+    #[test]
+    fn run_extractors_once() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "1 U.S. 1, 2 U.S. 2. ID. at 3. Id. at 4.";
+
+        let extractors: Vec<_> = tokenizer.get_extractors(text).collect();
+        for (i, extractor) in extractors.iter().enumerate() {
+            assert!(!extractors[..i].iter().any(|e| std::ptr::eq(*e, *extractor)));
+        }
+
+        // case insensitive extractors are triggered regardless of case
+        let (_, tokens) = tokenizer.tokenize(text);
+        let ids = tokens
+            .iter()
+            .filter(|(_, t)| matches!(t, Token::Id(_)))
+            .count();
+        assert_eq!(ids, 2);
+    }
+
+    #[test]
+    fn exhaustive_tokenizer() {
+        let ahocorasick = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let exhaustive = ExhaustiveTokenizer::new(EXTRACTORS.as_slice());
+        let text = "See Roe v. Wade, 410 U. S. 113 (1973). Id. at 114. Bar, supra.";
+
+        assert_eq!(exhaustive.tokenize(text), ahocorasick.tokenize(text));
+    }
+
+    #[test]
+    fn custom_tokens() {
+        let extractors = vec![TokenExtractor::try_new(
+            ResolvedRegex::of(r"(Exhibit (?P<exhibit>\d+))".into()),
+            TokenFactories::Custom("exhibit"),
+            false,
+            vec!["Exhibit".into()].into_iter().collect(),
+            Default::default(),
+        )
+        .unwrap()];
+        let tokenizer = Ahocorasick::new(&extractors).unwrap();
+
+        let (_, tokens) = tokenizer.tokenize("As shown in Exhibit 4, the");
+        match tokens.as_slice() {
+            [(6, token)] => {
+                assert_eq!(token.custom_kind(), Some("exhibit"));
+                assert_eq!(token.try_data().unwrap().groups.get("exhibit"), Some("4"));
+            }
+            _ => panic!("expected one custom token, got {:?}", tokens),
+        }
+    }
+
+    #[test]
+    fn merge_tokens() {
+        let edition = |reporter: &str, short_name: &str| ReporterEdition {
+            reporter: reporter.into(),
+            short_name: short_name.into(),
+            name: String::new(),
+            cite_type: "state".into(),
+            edition: Edition {
+                end: None,
+                start: None,
+                regexes: None,
+            },
+        };
+        let extractor = |regex: &str, exact, variations| {
+            TokenExtractor::try_new(
+                ResolvedRegex::of(regex.into()),
+                TokenFactories::Citation,
+                false,
+                vec!["W.".into()].into_iter().collect(),
+                TokenExtractorExtra {
+                    exact_editions: exact,
+                    variation_editions: variations,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let variation = extractor(
+            r"(?P<volume>\d+) (?P<reporter>W\.2d) (?P<page>\d+)",
+            vec![],
+            vec![edition("Wis.", "Wis. 2d"), edition("Wash.", "Wash. 2d")],
+        );
+        let exact = extractor(
+            r"(?P<volume>\d+) (?P<reporter>W\. ?2d) (?P<page>\d+)",
+            vec![edition("Wash.", "Wash. 2d")],
+            vec![edition("Wis.", "Wis. 2d")],
+        );
+
+        let editions = |extractors: &[TokenExtractor]| {
+            let tokenizer = ExhaustiveTokenizer::new(extractors);
+            let (_, tokens) = tokenizer.tokenize("See 1 W.2d 1.");
+            assert_eq!(tokens.len(), 1);
+            let editions = tokens[0].1.try_data().unwrap().editions();
+            let names = |list: &[&ReporterEdition]| -> Vec<String> {
+                list.iter().map(|e| e.short_name.clone()).collect()
+            };
+            (names(&editions.exact), names(&editions.variations))
+        };
+
+        let merged = editions(&[variation.clone(), exact.clone()]);
+        assert_eq!(
+            merged,
+            (vec!["Wash. 2d".to_string()], vec!["Wis. 2d".to_string()])
+        );
+        assert_eq!(editions(&[exact, variation]), merged);
+    }
+
+    #[test]
+    fn fuzzy_reporters() {
+        assert!(within_one_edit("SO.2d", "So.2d"));
+        assert!(within_one_edit("F.Supp,2d", "F.Supp.2d"));
+        assert!(within_one_edit("F.Sup.2d", "F.Supp.2d"));
+        assert!(within_one_edit("U.S.", "U.S.."));
+        assert!(within_one_edit("S.Ct.", "S.tC."));
+        assert!(!within_one_edit("So.3d", "SO.2d"));
+        assert!(!within_one_edit("F.2d", "F.4th"));
+        assert!(within_one_edit("Rép.", "Rep."));
+        assert!(!within_one_edit("Rép.", "Rpe."));
+
+        assert_eq!(edit_pieces("F.Supp.2d"), vec!["F.S", "upp", ".2d"]);
+        assert_eq!(edit_pieces("So"), vec!["So"]);
+
+        let tokenizer = FuzzyTokenizer::new(
+            Ahocorasick::new(EXTRACTORS.as_slice()).unwrap(),
+            EXTRACTORS.as_slice(),
+        );
+        let text = "See 1 SO.2d 5, and 2 F.Supp,2d 6, but 3 So. 2d 7.";
+        let citations = crate::find::get_citations(text, false, &tokenizer);
+
+        let fuzzy: Vec<_> = citations
+            .iter()
+            .map(|c| {
+                (
+                    c.source().groups["reporter"],
+                    c.source().fuzzy_reporter().is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fuzzy,
+            vec![("SO.2d", true), ("F.Supp,2d", true), ("So. 2d", false)]
+        );
+    }
+
+    #[test]
+    fn dump() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let (all_tokens, _) = tokenizer.tokenize("See 410 U.S. 113 here");
+
+        assert_eq!(
+            dump_tokens(&all_tokens),
+            "Word \"See\"\n\
+            Space\n\
+            Citation 4..16 \"410 U.S. 113\" volume=\"410\" reporter=\"U.S.\" page=\"113\" \
+            editions=[U.S.]\n\
+            Space\n\
+            Word \"here\"\n"
+        );
+    }
+
+    #[test]
+    fn automaton_sizes() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let stats = tokenizer.automaton_stats();
+        assert_eq!(stats.len(), 2);
+        assert!(!stats[0].case_insensitive);
+        assert!(stats[1].case_insensitive);
+        assert!(stats.iter().all(|s| s.strings > 0 && s.states > s.strings));
+
+        let strings: HashSet<_> = EXTRACTORS
+            .iter()
+            .filter(|e| !e.ignore_case)
+            .flat_map(|e| &e.strings)
+            .collect();
+        assert_eq!(stats[0].strings, strings.len());
+    }
+
+    #[test]
+    fn overlapping_scan() {
+        let overlapping = Ahocorasick::with_scan(EXTRACTORS.as_slice(), Scan::Overlapping).unwrap();
+        let leftmost = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let extractors = |tokenizer: &Ahocorasick, text| -> Vec<_> {
+            tokenizer
+                .get_extractors(text)
+                .map(|e| e.regex.value().to_string())
+                .collect()
+        };
+
+        // "So." is inside "So. 2d", so only an overlapping scan finds both
+        let text = "1 So. 2d 1";
+        let all = extractors(&overlapping, text);
+        let longest = extractors(&leftmost, text);
+        assert!(all.len() > longest.len());
+        assert!(longest.iter().all(|regex| all.contains(regex)));
+
+        let text = include_str!("../tests/assets/opinion.txt");
+        let all = extractors(&overlapping, text);
+        assert!(extractors(&leftmost, text)
+            .iter()
+            .all(|regex| all.contains(regex)));
+    }
 }
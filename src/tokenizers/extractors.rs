@@ -1,24 +1,180 @@
-use crate::regexes;
-use crate::tokenizers::models::{Token, TokenData, TokenFactories, TokenFactory};
+use crate::regexes::{self, RegexMode};
+use crate::tokenizers::models::{GroupName, Token, TokenData, TokenFactories, TokenFactory};
+use crate::EyeciteError;
+use chrono::Datelike;
 use lazy_static::lazy_static;
+use reporters_db::regexes::RegexVariable;
 use reporters_db::regexes::{RegexTemplate, ResolvedRegex};
-use reporters_db::reporters::{reporters, Edition, EditionName};
+use reporters_db::reporters::{reporters, Edition, EditionName, Reporter};
 use reporters_db::utils::process_variables;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Editions cited by year and opinion number rather than volume and page,
+/// like "T.C. Memo. 2020-123".
+const YEAR_PAGE_EDITIONS: [&str; 2] = ["T.C. Memo.", "T.C. Summ. Op."];
+
+/// How an edition numbers the pages it is cited by. Editions `reporters_db`
+/// gives their own regexes use those; for the others, this picks the
+/// template their citations are built from.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum PageFormat {
+    /// Digits or roman numerals, like "410 U.S. 113".
+    #[default]
+    Standard,
+    /// Paragraphs, often without a volume, like
+    /// "Bankr. L. Rep. (CCH) ¶ 12,345".
+    Paragraph,
+    /// Two numbers joined by a hyphen, like "2 Tax Ct. Rep. (CCH) 12-345".
+    Hyphenated,
+    /// A year in place of the volume, followed by an opinion number, like
+    /// "T.C. Memo. 2020-123".
+    YearPage,
+}
+
+impl PageFormat {
+    /// The citation template of editions paginated this way.
+    pub fn template(&self) -> RegexTemplate {
+        RegexTemplate::of(match self {
+            PageFormat::Standard => "$full_cite",
+            PageFormat::Paragraph => "$full_cite_paragraph",
+            PageFormat::Hyphenated => "$full_cite_hyphenated",
+            PageFormat::YearPage => "$full_cite_year_page",
+        })
+    }
+}
+
+/// Page formats of the editions that aren't [`PageFormat::Standard`] and
+/// that `reporters_db` has no regexes for, keyed by edition name.
+/// `reporters_db` doesn't record how editions are paginated, so these are
+/// listed here.
+pub fn default_page_formats() -> HashMap<String, PageFormat> {
+    YEAR_PAGE_EDITIONS
+        .into_iter()
+        .map(|edition| (edition.to_string(), PageFormat::YearPage))
+        .collect()
+}
+
+/// An edition from `reporters_db`, along with the reporter it belongs to.
+///
+/// `reporters_db`'s [`Edition`] only carries dates and regexes, so this keeps
+/// the names needed to tell candidate editions apart once a citation is found.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReporterEdition {
+    /// Key of the reporter in `reporters.json`, like "S.W."
+    pub reporter: String,
+    /// Name of this edition, like "S.W.2d"
+    pub short_name: String,
+    /// Full name of the reporter, like "South Western Reporter"
+    pub name: String,
+    /// Kind of reporter, like "state" or "federal"
+    pub cite_type: String,
+    pub edition: Edition,
+}
+
+impl ReporterEdition {
+    /// Return true if edition contains cases for the given year.
+    pub fn includes_year(&self, year: i32) -> bool {
+        !matches!(self.edition.start, Some(start) if start.year() > year)
+            && !matches!(self.edition.end, Some(end) if end.year() < year)
+    }
+}
 
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct TokenExtractorExtra {
-    pub exact_editions: Vec<Edition>,
-    pub variation_editions: Vec<Edition>,
+    pub exact_editions: Vec<ReporterEdition>,
+    pub variation_editions: Vec<ReporterEdition>,
     pub short: bool,
+    /// The extractor matches statutes or regulations rather than reporters,
+    /// so its matches are law citations. Without the `laws` feature, its
+    /// matches are skipped.
+    ///
+    /// None of the extractors built from reporters-db set this, since its
+    /// laws aren't ported yet, so law citations are only found by
+    /// extractors passed in along with them.
+    pub law: bool,
+    /// The reporter string this token's reporter was one character off
+    /// from, for tokens found by a
+    /// [`FuzzyTokenizer`](crate::tokenizers::FuzzyTokenizer).
+    pub fuzzy: Option<String>,
+}
+
+/**
+The editions a citation token might refer to, merged across the extra data
+of every token combined into it. See [`TokenData::editions`].
+
+Exact editions come first: an edition the reporter string is the
+abbreviation of in any of the tokens is exact, and isn't also listed as a
+variation. Each list has no duplicates and keeps the order the editions
+were extracted in: those of the token's own extractor, then those of the
+tokens merged into it, in the order they were merged.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Editions<'a> {
+    pub exact: Vec<&'a ReporterEdition>,
+    pub variations: Vec<&'a ReporterEdition>,
+}
+
+impl<'a> Editions<'a> {
+    pub fn of(extra: &'a TokenExtractorExtra) -> Self {
+        Self::merge([extra])
+    }
+
+    pub fn merge(extras: impl IntoIterator<Item = &'a TokenExtractorExtra>) -> Self {
+        let mut editions = Self::default();
+        for extra in extras {
+            editions.add(&extra.exact_editions, &extra.variation_editions);
+        }
+        editions
+    }
+
+    /// Add the editions of a token merged into this one, after those
+    /// already listed.
+    pub fn extend(&mut self, other: &Editions<'a>) {
+        self.add(
+            other.exact.iter().copied(),
+            other.variations.iter().copied(),
+        );
+    }
+
+    fn add(
+        &mut self,
+        exact: impl IntoIterator<Item = &'a ReporterEdition>,
+        variations: impl IntoIterator<Item = &'a ReporterEdition>,
+    ) {
+        fn same(a: &ReporterEdition, b: &ReporterEdition) -> bool {
+            (&a.reporter, &a.short_name) == (&b.reporter, &b.short_name)
+        }
+
+        for edition in exact {
+            if !self.exact.iter().any(|e| same(e, edition)) {
+                self.variations.retain(|e| !same(e, edition));
+                self.exact.push(edition);
+            }
+        }
+        for edition in variations {
+            if !self.iter().any(|e| same(e, edition)) {
+                self.variations.push(edition);
+            }
+        }
+    }
+
+    /// Exact editions, then variations.
+    pub fn iter(&self) -> impl Iterator<Item = &'a ReporterEdition> + '_ {
+        self.exact.iter().chain(&self.variations).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.variations.is_empty()
+    }
 }
 
 pub struct TokenMatch<'a> {
     pub(crate) regex_match: regex::Captures<'a>,
-    pub(crate) names: Vec<&'a str>,
+    pub(crate) groups: &'a [(usize, String)],
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TokenExtractor {
     pub regex: ResolvedRegex,
     pub token_factory: TokenFactories,
@@ -26,48 +182,85 @@ pub struct TokenExtractor {
     pub strings: HashSet<String>,
     pub ignore_case: bool,
     built_regex: regex::Regex,
+    /// Index and name of each named group in the regex, looked up once here
+    /// rather than for every match.
+    group_indexes: Vec<(usize, String)>,
+    /// Index of the group holding the token: the factory's
+    /// [`token_group`](TokenFactories::token_group) when the regex has it,
+    /// otherwise group 1.
+    token_index: usize,
 }
 
 impl TokenExtractor {
-    pub fn new(
+    /// Build an extractor, failing if its regex doesn't compile.
+    pub fn try_new(
         regex: ResolvedRegex,
         token_factory: TokenFactories,
         ignore_case: bool,
         strings: HashSet<String>,
         extra: TokenExtractorExtra,
-    ) -> Self {
+    ) -> Result<Self, EyeciteError> {
         let built_regex = regex::RegexBuilder::new(regex.value())
             .case_insensitive(ignore_case)
-            .build()
-            .expect("unable to build regex");
+            .build()?;
+        // Extractors without trigger strings would never run under a
+        // prefiltering tokenizer, so find strings their matches require
+        let strings = if strings.is_empty() {
+            regexes::required_literals(regex.value())
+                .map(|literals| literals.into_iter().collect())
+                .unwrap_or_default()
+        } else {
+            strings
+        };
+        let group_indexes: Vec<_> = built_regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| Some((index, name?.to_string())))
+            .collect();
+        let token_index = token_factory
+            .token_group()
+            .and_then(|group| {
+                group_indexes
+                    .iter()
+                    .find(|(_, name)| name == group.as_str())
+                    .map(|(index, _)| *index)
+            })
+            .unwrap_or(1);
 
-        Self {
+        Ok(Self {
             regex,
             token_factory,
             built_regex,
+            group_indexes,
+            token_index,
             ignore_case,
             strings,
             extra,
-        }
+        })
     }
 
     /// Return match objects for all matches in text.
     pub fn get_matches<'a>(&'a self, text: &'a str) -> Vec<TokenMatch<'a>> {
-        let matches = self.built_regex.captures_iter(text);
-        let names: Vec<_> = self.built_regex.capture_names().flatten().collect();
+        self.iter_matches(text).collect()
+    }
 
-        matches
-            .into_iter()
-            .map(|regex_match| TokenMatch {
+    /// Lazily find matches in text, so callers can stop early.
+    pub fn iter_matches<'a>(&'a self, text: &'a str) -> impl Iterator<Item = TokenMatch<'a>> {
+        self.built_regex
+            .captures_iter(text)
+            .map(move |regex_match| TokenMatch {
                 regex_match,
-                names: names.clone(),
+                groups: &self.group_indexes,
             })
-            .collect()
     }
 
     /// For a given match object, return a Token.
     pub fn get_token<'a>(&'a self, token_match: TokenMatch<'a>) -> Token<'a> {
-        let m = token_match.regex_match.get(1).unwrap();
+        // Custom regexes may not have a group 1, or may not use it
+        let m = token_match
+            .regex_match
+            .get(self.token_index)
+            .unwrap_or_else(|| token_match.regex_match.get_match());
         let start = m.start();
         let end = m.end();
         let data: &'a str = m.as_str();
@@ -79,34 +272,247 @@ impl TokenExtractor {
             end,
             data,
             extra,
+            merged: Vec::new(),
+            editions: Editions::of(extra),
             groups: token_match
-                .names
-                .into_iter()
-                .flat_map(|name| {
-                    token_match
-                        .regex_match
-                        .name(name)
-                        .map(move |m| (name, m.as_str()))
+                .groups
+                .iter()
+                .filter_map(|(index, name)| {
+                    let m = token_match.regex_match.get(*index)?;
+                    Some((GroupName::from(name.as_str()), m.as_str()))
                 })
                 .collect(),
         })
     }
 }
 
+/// Version of `reporters_db` whose data is bundled, and the built-in
+/// extractors are built from. Read from its manifest by `build.rs`.
+const REPORTERS_DB_VERSION: &str = env!("REPORTERS_DB_VERSION");
+
+/// Version of the `reporters_db` data the built-in extractors, like
+/// [`EXTRACTORS`], are built from, to record along with extraction results
+/// so they can be reproduced.
+pub fn version() -> &'static str {
+    REPORTERS_DB_VERSION
+}
+
+/// The `reporters_db` data reporter extractors are built from. Build
+/// extractors from a snapshot pinned by the caller, rather than the data
+/// bundled with this build, with [`try_populate_reporter_extractors_from`],
+/// so every deployment extracts the same citations.
+#[derive(Debug, Clone)]
+pub struct ReportersSnapshot {
+    /// Version of the data, like "0.0.5".
+    pub version: String,
+    /// Reporters by their key in `reporters.json`, like "S.W."
+    pub reporters: HashMap<String, Vec<Reporter>>,
+    /// Regex variables by name, like "full_cite", from `regexes.json`.
+    pub regexes: HashMap<String, RegexVariable>,
+}
+
+impl ReportersSnapshot {
+    /// The data bundled with `reporters_db`, whose version is [`version`].
+    pub fn bundled() -> Self {
+        Self {
+            version: REPORTERS_DB_VERSION.to_string(),
+            reporters: reporters(),
+            regexes: reporters_db::regexes::raw_regexes(),
+        }
+    }
+}
+
 pub fn _populate_reporter_extractors() -> Vec<TokenExtractor> {
-    let mut raw_regex_variables = reporters_db::regexes::raw_regexes();
+    _populate_reporter_extractors_with(&default_page_formats())
+}
+
+/// Like [`_populate_reporter_extractors`], failing with a report of every
+/// step that failed rather than panicking at the first.
+pub fn try_populate_reporter_extractors() -> Result<Vec<TokenExtractor>, InitReport> {
+    try_populate_reporter_extractors_with(&default_page_formats())
+}
+
+/// The editions and reporter strings matched by a regex.
+#[derive(Default, Debug)]
+struct Lookup {
+    editions: Vec<ReporterEdition>,
+    variations: Vec<ReporterEdition>,
+    strings: HashSet<String>,
+    short: bool,
+}
+
+/// A regex template of an edition that couldn't be resolved into a regex,
+/// so the edition isn't matched with it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SkippedTemplate {
+    /// Key of the reporter in `reporters.json`, like "S.W."
+    pub reporter: String,
+    /// Name of the edition, like "S.W.2d"
+    pub edition: String,
+    pub template: String,
+}
+
+/// A step of building the built-in extractors that failed. See [`init`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InitFailure {
+    /// A regex variable eyecite adds formats to is missing from
+    /// `reporters_db`, like "full_cite".
+    MissingVariable { name: String },
+    /// The regex of an extractor didn't compile.
+    Regex {
+        /// `(reporter, edition)` pairs the extractor was for, like
+        /// `("S.W.", "S.W.2d")`, or none for extractors of other tokens.
+        editions: Vec<(String, String)>,
+        regex: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for InitFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitFailure::MissingVariable { name } => {
+                write!(f, "reporters_db has no \"{name}\" regex variable")
+            }
+            InitFailure::Regex {
+                editions,
+                regex,
+                error,
+            } => {
+                write!(f, "regex {regex:?}")?;
+                if !editions.is_empty() {
+                    let editions: Vec<_> = editions
+                        .iter()
+                        .map(|(reporter, edition)| format!("{edition} ({reporter})"))
+                        .collect();
+                    write!(f, " of {}", editions.join(", "))?;
+                }
+                write!(f, " doesn't compile: {error}")
+            }
+        }
+    }
+}
+
+/// Why the built-in extractors couldn't be built, along with the templates
+/// skipped on the way, which alone don't fail.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct InitReport {
+    pub failures: Vec<InitFailure>,
+    pub skipped: Vec<SkippedTemplate>,
+}
+
+impl fmt::Display for InitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failures", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n- {failure}")?;
+        }
+        for skipped in &self.skipped {
+            write!(
+                f,
+                "\n- skipped template {:?} of {} ({})",
+                skipped.template, skipped.edition, skipped.reporter
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn missing_variable(name: &str) -> InitFailure {
+    InitFailure::MissingVariable {
+        name: name.to_string(),
+    }
+}
+
+type ReporterRegexes = (Vec<(ResolvedRegex, Lookup)>, Vec<SkippedTemplate>);
+
+/// Build the regex of every reporter citation format of a snapshot, along
+/// with what it matches, sorted by regex, and the templates that couldn't
+/// be resolved.
+fn reporter_regexes(
+    snapshot: ReportersSnapshot,
+    page_formats: &HashMap<String, PageFormat>,
+) -> Result<ReporterRegexes, InitFailure> {
+    let mut raw_regex_variables = snapshot.regexes;
 
     raw_regex_variables
         .get_mut("full_cite")
-        .expect("full_cite should already exist")
+        .ok_or_else(|| missing_variable("full_cite"))?
         .add("", RegexTemplate::of("$volume $reporter,? $page"));
 
     raw_regex_variables
         .get_mut("page")
-        .expect("page should already exist")
+        .ok_or_else(|| missing_variable("page"))?
         .add("", RegexTemplate::of(regexes::PAGE_REGEX));
 
-    let regex_vars = process_variables(raw_regex_variables);
+    // Looseleaf reporters flagged with `$full_cite_paragraph` are cited by
+    // paragraph, often without a volume: "Bankr. L. Rep. (CCH) ¶ 12,345"
+    raw_regex_variables
+        .get_mut("full_cite")
+        .ok_or_else(|| missing_variable("full_cite"))?
+        .add(
+            "paragraph",
+            RegexTemplate::of("$volume_optional$reporter,? ¶ ?$page_paragraph"),
+        );
+
+    raw_regex_variables
+        .get_mut("volume")
+        .ok_or_else(|| missing_variable("volume"))?
+        .add(
+            "optional",
+            RegexTemplate::of(regexes::OPTIONAL_VOLUME_REGEX),
+        );
+
+    raw_regex_variables
+        .get_mut("page")
+        .ok_or_else(|| missing_variable("page"))?
+        .add(
+            "paragraph",
+            RegexTemplate::of(regexes::PARAGRAPH_PAGE_REGEX),
+        );
+
+    raw_regex_variables
+        .get_mut("full_cite")
+        .ok_or_else(|| missing_variable("full_cite"))?
+        .add(
+            "year_page",
+            RegexTemplate::of("$reporter $volume_year-$page"),
+        );
+
+    raw_regex_variables
+        .get_mut("volume")
+        .ok_or_else(|| missing_variable("volume"))?
+        .add("year", RegexTemplate::of(regexes::YEAR_VOLUME_REGEX));
+
+    raw_regex_variables
+        .get_mut("full_cite")
+        .ok_or_else(|| missing_variable("full_cite"))?
+        .add(
+            "hyphenated",
+            RegexTemplate::of("$volume $reporter,? $page_hyphenated"),
+        );
+
+    raw_regex_variables
+        .get_mut("page")
+        .ok_or_else(|| missing_variable("page"))?
+        .add(
+            "hyphenated",
+            RegexTemplate::of(regexes::HYPHENATED_PAGE_REGEX),
+        );
+
+    let mut regex_vars = process_variables(raw_regex_variables);
+
+    // Short citation variables, like "$short_cite" for "$full_cite", for
+    // the formats that have a short form
+    let mut short_vars = Vec::new();
+    for (name, template) in &regex_vars {
+        if let Some(suffix) = name.strip_prefix("full_cite") {
+            if let Some(short) = regexes::short_cite_template(template.value(), |_| false) {
+                short_vars.push((format!("short_cite{suffix}"), RegexTemplate::of(short)));
+            }
+        }
+    }
+    regex_vars.extend(short_vars);
 
     fn _substitute_edition(template: RegexTemplate, edition_name: &[EditionName]) -> RegexTemplate {
         let mut map: HashMap<String, RegexTemplate> = HashMap::new();
@@ -125,21 +531,14 @@ pub fn _populate_reporter_extractors() -> Vec<TokenExtractor> {
     //     # Keys in this dict will be regular expressions to handle a
     //     # particular reporter string, like (simplified)
     //     # r"(?P<volume>\d+) (?P<reporter>U\.S\.) (?P<page>\d+)"
-    #[derive(Default, Debug)]
-    struct Lookup {
-        editions: Vec<Edition>,
-        variations: Vec<Edition>,
-        strings: HashSet<String>,
-        short: bool,
-    }
 
     fn _add_regex(
         reporters: &[EditionName],
-        edition: &Edition,
+        edition: &ReporterEdition,
         regex: ResolvedRegex,
         is_short: bool,
         result: &mut HashMap<ResolvedRegex, Lookup>,
-        func: fn(&mut Lookup) -> &mut Vec<Edition>,
+        func: fn(&mut Lookup) -> &mut Vec<ReporterEdition>,
     ) {
         let entry = result.entry(regex.clone()).or_default();
 
@@ -162,32 +561,85 @@ pub fn _populate_reporter_extractors() -> Vec<TokenExtractor> {
     fn _add_regexes(
         regex_templates: &[RegexTemplate],
         edition_name: EditionName,
-        edition: Edition,
+        edition: ReporterEdition,
         variations: Vec<EditionName>,
         variables: &HashMap<String, RegexTemplate>,
         result: &mut HashMap<ResolvedRegex, Lookup>,
+        skipped: &mut Vec<SkippedTemplate>,
     ) {
+        // Nominative reporters, like "17 U.S. (4 Wheat.) 316", were only
+        // cited alongside editions that go back to the nineteenth century.
+        let with_nominative = !matches!(edition.edition.start, Some(start) if start.year() >= 1900);
+
         for template in regex_templates {
+            let skip = || SkippedTemplate {
+                reporter: edition.reporter.clone(),
+                edition: edition.short_name.clone(),
+                template: template.value().to_string(),
+            };
+            // Formats without a "reporter page" pair, like paragraph cites,
+            // have no short form.
+            let short_template =
+                regexes::short_cite_template(template.value(), |name| variables.contains_key(name))
+                    .map(|short| {
+                        reporters_db::utils::recursive_substitute(
+                            RegexTemplate::of(short),
+                            variables,
+                        )
+                    });
             let template = reporters_db::utils::recursive_substitute(template.clone(), variables);
             let arg = vec![edition_name.clone()];
-            let regex = _substitute_edition(template.clone(), arg.as_slice())
-                .resolved()
-                .expect("edition should have been the last thing to resolve");
+            // The edition should have been the last thing to resolve
+            let Some(regex) = _substitute_edition(template.clone(), arg.as_slice()).resolved()
+            else {
+                skipped.push(skip());
+                continue;
+            };
 
-            let short_regex = regexes::short_cite_re(regex.value());
+            let short_regex = short_template
+                .as_ref()
+                .and_then(|short| _substitute_edition(short.clone(), arg.as_slice()).resolved());
+            let nominative_regex = regexes::nominative_cite_re(regex.value());
+            if with_nominative && nominative_regex != regex {
+                _add_regex(
+                    arg.as_slice(),
+                    &edition,
+                    nominative_regex,
+                    false,
+                    result,
+                    |l| &mut l.editions,
+                );
+            }
             _add_regex(arg.as_slice(), &edition, regex, false, result, |l| {
                 &mut l.editions
             });
-            _add_regex(arg.as_slice(), &edition, short_regex, true, result, |l| {
-                &mut l.editions
-            });
+            if let Some(short_regex) = short_regex {
+                _add_regex(arg.as_slice(), &edition, short_regex, true, result, |l| {
+                    &mut l.editions
+                });
+            }
 
             if !variations.is_empty() {
-                let variation_regex = _substitute_edition(template, variations.as_slice())
-                    .resolved()
-                    .expect("edition should have been the last thing to resolve");
+                let Some(variation_regex) =
+                    _substitute_edition(template, variations.as_slice()).resolved()
+                else {
+                    skipped.push(skip());
+                    continue;
+                };
 
-                let short_variation_regex = regexes::short_cite_re(variation_regex.value());
+                let nominative_variation_regex =
+                    regexes::nominative_cite_re(variation_regex.value());
+
+                if with_nominative && nominative_variation_regex != variation_regex {
+                    _add_regex(
+                        variations.as_slice(),
+                        &edition,
+                        nominative_variation_regex,
+                        false,
+                        result,
+                        |l| &mut l.variations,
+                    );
+                }
 
                 _add_regex(
                     variations.as_slice(),
@@ -197,59 +649,150 @@ pub fn _populate_reporter_extractors() -> Vec<TokenExtractor> {
                     result,
                     |l| &mut l.variations,
                 );
-                _add_regex(
-                    variations.as_slice(),
-                    &edition,
-                    short_variation_regex,
-                    false,
-                    result,
-                    |l| &mut l.variations,
-                );
+                let short_variation_regex = short_template
+                    .clone()
+                    .and_then(|short| _substitute_edition(short, variations.as_slice()).resolved());
+                if let Some(short_variation_regex) = short_variation_regex {
+                    _add_regex(
+                        variations.as_slice(),
+                        &edition,
+                        short_variation_regex,
+                        true,
+                        result,
+                        |l| &mut l.variations,
+                    );
+                }
             }
         }
     }
 
     let mut editions_by_regex: HashMap<ResolvedRegex, Lookup> = HashMap::new();
+    let mut skipped = Vec::new();
 
     // # add reporters.json:
-    let reporters = reporters();
-    for (_key, cluster) in reporters {
+    //
+    // reporters_db hands out hash maps, so everything is sorted by name to
+    // build the same extractors, in the same order, every time. Ties between
+    // tokens with the same span go to the extractor that ran first.
+    let mut reporters: Vec<_> = snapshot.reporters.into_iter().collect();
+    reporters.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, cluster) in reporters {
         for source in cluster {
             let variations = source.variations;
+            let mut editions: Vec<_> = source.editions.into_iter().collect();
+            editions.sort_by(|(a, _), (b, _)| a.value().cmp(b.value()));
 
-            for (edition_name, edition_data) in source.editions {
-                let regexes = edition_data
-                    .regexes
-                    .clone()
-                    .unwrap_or_else(|| vec![RegexTemplate::of("$full_cite")]);
+            for (edition_name, edition_data) in editions {
+                let regexes = edition_data.regexes.clone().unwrap_or_else(|| {
+                    let format = page_formats.get(edition_name.value());
+                    vec![format.copied().unwrap_or_default().template()]
+                });
 
-                let edition_variations: Vec<_> = variations
+                let mut edition_variations: Vec<_> = variations
                     .iter()
                     .filter(|(_, v)| edition_name == (*v).clone())
                     .map(|(k, _)| k.clone())
                     .collect();
+                edition_variations.sort_by(|a, b| a.value().cmp(b.value()));
+
+                let edition = ReporterEdition {
+                    reporter: key.clone(),
+                    short_name: edition_name.value().into(),
+                    name: source.name.clone(),
+                    cite_type: source.cite_type.clone(),
+                    edition: edition_data,
+                };
 
                 _add_regexes(
                     &regexes,
                     edition_name,
-                    edition_data,
+                    edition,
                     edition_variations,
                     &regex_vars,
                     &mut editions_by_regex,
+                    &mut skipped,
                 )
             }
         }
     }
 
     // # add laws.json
+    // Not ported yet: laws are only found by extractors passed in with
+    // `TokenExtractorExtra::law` set
 
     // # add journals.json
 
+    let mut editions_by_regex: Vec<_> = editions_by_regex.into_iter().collect();
+    editions_by_regex.sort_by(|(a, _), (b, _)| a.value().cmp(b.value()));
+
+    Ok((editions_by_regex, skipped))
+}
+
+/// Like [`_populate_reporter_extractors`], with the page formats of editions
+/// keyed by edition name. Editions missing from `page_formats` are
+/// [`PageFormat::Standard`].
+pub fn _populate_reporter_extractors_with(
+    page_formats: &HashMap<String, PageFormat>,
+) -> Vec<TokenExtractor> {
+    try_populate_reporter_extractors_with(page_formats)
+        .unwrap_or_else(|report| panic!("unable to build extractors: {report}"))
+}
+
+/// Like [`_populate_reporter_extractors_with`], failing with a report of
+/// every step that failed rather than panicking at the first.
+pub fn try_populate_reporter_extractors_with(
+    page_formats: &HashMap<String, PageFormat>,
+) -> Result<Vec<TokenExtractor>, InitReport> {
+    try_populate_reporter_extractors_from(ReportersSnapshot::bundled(), page_formats)
+}
+
+/// Like [`try_populate_reporter_extractors_with`], building the reporter
+/// extractors from a snapshot of `reporters_db` data rather than the
+/// bundled data.
+pub fn try_populate_reporter_extractors_from(
+    snapshot: ReportersSnapshot,
+    page_formats: &HashMap<String, PageFormat>,
+) -> Result<Vec<TokenExtractor>, InitReport> {
+    let (editions_by_regex, skipped) =
+        reporter_regexes(snapshot, page_formats).map_err(|failure| InitReport {
+            failures: vec![failure],
+            skipped: Vec::new(),
+        })?;
+    let mut report = InitReport {
+        failures: Vec::new(),
+        skipped,
+    };
     let mut extractors = Vec::new();
+    let mut add = |regex: ResolvedRegex,
+                   token_factory: TokenFactories,
+                   ignore_case: bool,
+                   strings: HashSet<String>,
+                   extra: TokenExtractorExtra| {
+        let editions = extra
+            .exact_editions
+            .iter()
+            .chain(&extra.variation_editions)
+            .map(|edition| (edition.reporter.clone(), edition.short_name.clone()))
+            .collect();
+        let pattern = regex.value().to_string();
+        match TokenExtractor::try_new(regex, token_factory, ignore_case, strings, extra) {
+            Ok(extractor) => extractors.push(extractor),
+            Err(error) => report.failures.push(InitFailure::Regex {
+                editions,
+                regex: pattern,
+                error: error.to_string(),
+            }),
+        }
+    };
+    let us_reports = editions_by_regex
+        .iter()
+        .flat_map(|(_, lookup)| &lookup.editions)
+        .find(|edition| edition.short_name == "U.S.")
+        .cloned();
 
     // # Add each regex to EXTRACTORS
     for (regex, lookup) in editions_by_regex {
-        extractors.push(TokenExtractor::new(
+        add(
             regexes::nonalphanum_boundaries_re(&regex),
             TokenFactories::Citation,
             false,
@@ -258,63 +801,753 @@ pub fn _populate_reporter_extractors() -> Vec<TokenExtractor> {
                 exact_editions: lookup.editions,
                 variation_editions: lookup.variations,
                 short: lookup.short,
+                law: false,
+                fuzzy: None,
             },
-        ));
+        );
     }
 
-    extractors.push(TokenExtractor::new(
+    add(
+        regexes::nonalphanum_boundaries_re(&ResolvedRegex::of(
+            regexes::PLACEHOLDER_CITE_REGEX.into(),
+        )),
+        TokenFactories::Citation,
+        false,
+        vec!["__".into()].into_iter().collect(),
+        TokenExtractorExtra {
+            exact_editions: us_reports.into_iter().collect(),
+            ..Default::default()
+        },
+    );
+
+    add(
         ResolvedRegex::of(regexes::ID_REGEX.into()),
         TokenFactories::Id,
         true,
         vec!["id.".into(), "ibid.".into()].into_iter().collect(),
         Default::default(),
-    ));
+    );
 
-    extractors.push(TokenExtractor::new(
+    add(
+        ResolvedRegex::of(regexes::AT_PIN_CITE_REGEX.into()),
+        TokenFactories::Id,
+        false,
+        vec!["At ".into()].into_iter().collect(),
+        Default::default(),
+    );
+
+    add(
         ResolvedRegex::of(regexes::SUPRA_REGEX.into()),
         TokenFactories::Supra,
         true,
         vec!["supra".into()].into_iter().collect(),
         Default::default(),
-    ));
+    );
 
-    extractors.push(TokenExtractor::new(
+    add(
+        ResolvedRegex::of(regexes::INFRA_REGEX.into()),
+        TokenFactories::Infra,
+        true,
+        vec!["infra".into()].into_iter().collect(),
+        Default::default(),
+    );
+
+    add(
         ResolvedRegex::of(regexes::PARAGRAPH_REGEX.into()),
         TokenFactories::Paragraph,
         false,
         Default::default(),
         Default::default(),
-    ));
+    );
 
-    extractors.push(TokenExtractor::new(
+    add(
         ResolvedRegex::of(regexes::STOP_WORD_REGEX.into()),
         TokenFactories::StopWord,
         true,
-        regexes::STOP_WORDS.into_iter().map(|s| s.into()).collect(),
+        regexes::STOP_WORDS
+            .into_iter()
+            .chain(regexes::STOP_PHRASES)
+            .map(|s| s.into())
+            .collect(),
         Default::default(),
-    ));
+    );
 
-    extractors.push(TokenExtractor::new(
+    add(
         ResolvedRegex::of(regexes::SECTION_REGEX.into()),
         TokenFactories::Section,
         false,
         vec!["§"].into_iter().map(|s| s.into()).collect(),
         Default::default(),
-    ));
+    );
 
-    extractors
+    add(
+        ResolvedRegex::of(regexes::SLIP_OPINION_REGEX.into()),
+        TokenFactories::SlipOpinion,
+        false,
+        vec!["slip op."].into_iter().map(|s| s.into()).collect(),
+        Default::default(),
+    );
+
+    add(
+        ResolvedRegex::of(regexes::CROSS_REFERENCE_REGEX.into()),
+        TokenFactories::CrossReference,
+        false,
+        vec!["Part", "Section"]
+            .into_iter()
+            .map(|s| s.into())
+            .collect(),
+        Default::default(),
+    );
+
+    add(
+        ResolvedRegex::of(regexes::RECORD_REGEX.into()),
+        TokenFactories::Record,
+        false,
+        vec![
+            "ECF No.", "Dkt.", "Doc. No.", "Ex.", "Exh.", "R.", "J.A.", "S.A.", "Tr.",
+        ]
+        .into_iter()
+        .map(|s| s.into())
+        .collect(),
+        Default::default(),
+    );
+
+    if report.failures.is_empty() {
+        Ok(extractors)
+    } else {
+        Err(report)
+    }
+}
+
+/// Which editions of `reporters_db` the reporter extractors match, so
+/// coverage can be checked after upgrading `reporters_db`. Editions are
+/// `(reporter, edition)` pairs, like `("S.W.", "S.W.2d")`, sorted.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReporterCoverage {
+    /// Editions matched by at least one extractor.
+    pub covered: Vec<(String, String)>,
+    /// Editions no extractor matches.
+    pub missing: Vec<(String, String)>,
+    /// Templates that couldn't be resolved into regexes.
+    pub skipped: Vec<SkippedTemplate>,
+}
+
+/// Report which editions of `reporters_db` the reporter extractors built
+/// with `page_formats` match. Only the regexes are built, not the
+/// extractors, so this is cheap compared to building them. Fails like
+/// [`init`] if the regex variables eyecite adds formats to are missing.
+pub fn reporter_coverage(
+    page_formats: &HashMap<String, PageFormat>,
+) -> Result<ReporterCoverage, InitFailure> {
+    let (editions_by_regex, skipped) =
+        reporter_regexes(ReportersSnapshot::bundled(), page_formats)?;
+    let covered: BTreeSet<_> = editions_by_regex
+        .iter()
+        .flat_map(|(_, lookup)| lookup.editions.iter().chain(&lookup.variations))
+        .map(|edition| (edition.reporter.clone(), edition.short_name.clone()))
+        .collect();
+
+    let mut missing = BTreeSet::new();
+    for (key, cluster) in reporters() {
+        for source in cluster {
+            for edition in source.editions.keys() {
+                let edition = (key.clone(), edition.value().to_string());
+                if !covered.contains(&edition) {
+                    missing.insert(edition);
+                }
+            }
+        }
+    }
+
+    Ok(ReporterCoverage {
+        covered: covered.into_iter().collect(),
+        missing: missing.into_iter().collect(),
+        skipped,
+    })
 }
 
 lazy_static! {
-    pub static ref EXTRACTORS: Vec<TokenExtractor> = _populate_reporter_extractors();
+    static ref BUILT_EXTRACTORS: Result<Vec<TokenExtractor>, InitReport> =
+        try_populate_reporter_extractors();
+    /// The built-in extractors. Panics on first use if they can't be built;
+    /// call [`init`] first to handle that instead.
+    pub static ref EXTRACTORS: &'static Vec<TokenExtractor> = match &*BUILT_EXTRACTORS {
+        Ok(extractors) => extractors,
+        Err(report) => panic!("unable to build extractors, see eyecite::tokenizers::extractors::init: {report}"),
+    };
+}
+
+/// Build the built-in extractors now, rather than the first time they are
+/// needed, failing with a report of what in `reporters_db` couldn't be
+/// turned into an extractor instead of panicking. Safe to call from any
+/// thread, any number of times: they are only built once.
+pub fn init() -> Result<&'static [TokenExtractor], EyeciteError> {
+    BUILT_EXTRACTORS
+        .as_deref()
+        .map_err(|report| EyeciteError::Init {
+            report: report.clone(),
+        })
+}
+
+/**
+eyecite's built-in extractors, built the first time they are needed.
+
+These are guaranteed to include extractors for:
+
+- full and short citations of every edition in `reporters_db`, producing
+  [`Token::Citation`] tokens
+- "id." and "ibid.", and "At 745," at the start of a sentence
+  ([`Token::Id`])
+- "supra" ([`Token::Supra`]) and "infra" ([`Token::Infra`])
+- words containing a section symbol ([`Token::Section`])
+- the stop words in [`regexes::STOP_WORDS`] and [`regexes::STOP_PHRASES`]
+  ([`Token::StopWord`])
+- paragraph breaks ([`Token::Paragraph`])
+- slip opinions ([`Token::SlipOpinion`])
+- references to other parts of the document, like "Part II.A"
+  ([`Token::CrossReference`])
+- record citations, like "R. at 45" ([`Token::Record`]), which are only
+  extracted when asked for with
+  [`TokenKinds::RECORD`](crate::tokenizers::models::TokenKinds::RECORD)
+
+Their order and exact regexes aren't part of this guarantee. Use
+[`filtered`] to leave some of them out.
+ */
+pub fn default_extractors() -> &'static [TokenExtractor] {
+    EXTRACTORS.as_slice()
+}
+
+/// Start building a subset of the [`default_extractors`].
+pub fn filtered() -> FilteredExtractors {
+    FilteredExtractors::default()
+}
+
+/// A subset of the [`default_extractors`]. See [`filtered`].
+#[derive(Debug, Clone, Default)]
+pub struct FilteredExtractors {
+    without: HashSet<TokenFactories>,
+    reporters: Option<HashSet<String>>,
+    spacing_tolerant: HashSet<String>,
+    id_variants: Vec<String>,
+    mode: RegexMode,
+}
+
+impl FilteredExtractors {
+    /// Leave out the extractors producing a kind of token.
+    pub fn without(mut self, kind: TokenFactories) -> Self {
+        self.without.insert(kind);
+        self
+    }
+
+    /// Only keep the citation extractors of editions of these reporters,
+    /// keyed like in `reporters.json`, such as "U.S." or "F.".
+    pub fn with_reporters<S: Into<String>>(
+        mut self,
+        reporters: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.reporters = Some(reporters.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Match citations to these editions, keyed like "U.S." or "F. Supp.",
+    /// with optional periods and spaces between the parts of their
+    /// abbreviation, like "410 U. S 113", to find more citations in sloppy
+    /// text. See [`regexes::spacing_tolerant_reporter`]. This also finds
+    /// more false positives, like "US" in "1 US 2", so it is left to each
+    /// edition.
+    pub fn with_spacing_tolerance<S: Into<String>>(
+        mut self,
+        editions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.spacing_tolerant
+            .extend(editions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also match id citations with these regexes, like the sloppy forms in
+    /// [`regexes::ID_VARIANT_REGEXES`], ignoring case. Each regex should
+    /// have an `id` group holding the token, or group 1 if it doesn't. See
+    /// [`CitationSource::normalized_id`](crate::find::models::CitationSource::normalized_id).
+    pub fn with_id_variants<S: Into<String>>(
+        mut self,
+        variants: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.id_variants
+            .extend(variants.into_iter().map(Into::into));
+        self
+    }
+
+    /// Require the regexes of the extractors to work with a regex engine
+    /// other than the regex crate, like hyperscan. [`try_build`](Self::try_build)
+    /// checks them.
+    pub fn with_mode(mut self, mode: RegexMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn keeps(&self, extractor: &TokenExtractor) -> bool {
+        if self.without.contains(&extractor.token_factory) {
+            return false;
+        }
+
+        match (&self.reporters, extractor.token_factory) {
+            (Some(reporters), TokenFactories::Citation) => extractor
+                .extra
+                .exact_editions
+                .iter()
+                .chain(&extractor.extra.variation_editions)
+                .any(|edition| reporters.contains(&edition.reporter)),
+            _ => true,
+        }
+    }
+
+    /// The extractor with the reporter strings of its spacing tolerant
+    /// editions loosened.
+    fn tolerate_spacing(&self, extractor: &TokenExtractor) -> Result<TokenExtractor, EyeciteError> {
+        let mut regex = extractor.regex.value().to_string();
+        for edition in &extractor.extra.exact_editions {
+            if self.spacing_tolerant.contains(&edition.short_name) {
+                regex = regex.replace(
+                    &regex::escape(&edition.short_name),
+                    &regexes::spacing_tolerant_reporter(&edition.short_name),
+                );
+            }
+        }
+        if regex == extractor.regex.value() {
+            return Ok(extractor.clone());
+        }
+
+        // The exact reporter strings no longer appear in every match
+        let strings = regexes::required_literals(&regex)
+            .map(|literals| literals.into_iter().collect())
+            .unwrap_or_else(|| extractor.strings.clone());
+        TokenExtractor::try_new(
+            ResolvedRegex::of(regex),
+            extractor.token_factory,
+            extractor.ignore_case,
+            strings,
+            extractor.extra.clone(),
+        )
+    }
+
+    /// Extractors of the id variants, unless id tokens are left out.
+    fn id_variant_extractors(&self) -> Result<Vec<TokenExtractor>, EyeciteError> {
+        if self.without.contains(&TokenFactories::Id) {
+            return Ok(Vec::new());
+        }
+
+        self.id_variants
+            .iter()
+            .map(|regex| {
+                let strings = regexes::required_literals(regex)
+                    .map(|literals| literals.into_iter().collect())
+                    .unwrap_or_default();
+                TokenExtractor::try_new(
+                    ResolvedRegex::of(regex.clone()),
+                    TokenFactories::Id,
+                    true,
+                    strings,
+                    Default::default(),
+                )
+            })
+            .collect()
+    }
+
+    /// Build the extractors, failing if a regex isn't supported by the
+    /// [`RegexMode`], or a loosened reporter or an id variant doesn't
+    /// compile.
+    pub fn try_build(&self) -> Result<Vec<TokenExtractor>, EyeciteError> {
+        default_extractors()
+            .iter()
+            .filter(|e| self.keeps(e))
+            .map(|e| self.tolerate_spacing(e))
+            .chain(self.id_variant_extractors()?.into_iter().map(Ok))
+            .map(|e| {
+                let e = e?;
+                self.mode.check(e.regex.value())?;
+                Ok(e)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::EXTRACTORS;
+    use super::{
+        _populate_reporter_extractors, _populate_reporter_extractors_with, default_extractors,
+        default_page_formats, filtered, init, reporter_coverage,
+        try_populate_reporter_extractors_from, version, InitFailure, InitReport, PageFormat,
+        RegexMode, ReportersSnapshot, TokenExtractor, EXTRACTORS,
+    };
+    use crate::regexes;
+    use crate::tokenizers::models::{GroupName, Token, TokenFactories};
+    use crate::tokenizers::{Ahocorasick, Tokenizer};
+    use reporters_db::reporters::reporters;
+    use std::collections::HashSet;
 
     #[test]
     fn build_extractors() {
         assert_eq!(EXTRACTORS.is_empty(), false);
     }
+
+    #[test]
+    fn init_reports() {
+        assert_eq!(init().unwrap().len(), EXTRACTORS.len());
+
+        let report = InitReport {
+            failures: vec![
+                InitFailure::MissingVariable {
+                    name: "full_cite".to_string(),
+                },
+                InitFailure::Regex {
+                    editions: vec![("S.W.".to_string(), "S.W.2d".to_string())],
+                    regex: "(?P<volume>".to_string(),
+                    error: "unclosed group".to_string(),
+                },
+            ],
+            skipped: Vec::new(),
+        };
+        assert_eq!(
+            report.to_string(),
+            "2 failures\n\
+            - reporters_db has no \"full_cite\" regex variable\n\
+            - regex \"(?P<volume>\" of S.W.2d (S.W.) doesn't compile: unclosed group"
+        );
+    }
+
+    #[test]
+    fn filtered_extractors() {
+        let extractors = filtered()
+            .without(TokenFactories::Supra)
+            .with_reporters(["U.S."])
+            .try_build()
+            .unwrap();
+
+        assert!(extractors.len() < default_extractors().len());
+        assert!(!extractors
+            .iter()
+            .any(|e| e.token_factory == TokenFactories::Supra));
+        assert!(extractors
+            .iter()
+            .any(|e| e.token_factory == TokenFactories::Id));
+        for extractor in extractors
+            .iter()
+            .filter(|e| e.token_factory == TokenFactories::Citation)
+        {
+            assert!(extractor
+                .extra
+                .exact_editions
+                .iter()
+                .chain(&extractor.extra.variation_editions)
+                .any(|edition| edition.reporter == "U.S."));
+        }
+    }
+
+    #[test]
+    fn coverage() {
+        let coverage = reporter_coverage(&default_page_formats()).unwrap();
+        assert_eq!(coverage.skipped, vec![]);
+        assert_eq!(coverage.missing, vec![]);
+
+        let editions: HashSet<_> = reporters()
+            .into_iter()
+            .flat_map(|(key, cluster)| {
+                cluster.into_iter().flat_map(move |source| {
+                    let key = key.clone();
+                    source
+                        .editions
+                        .into_keys()
+                        .map(move |edition| (key.clone(), edition.value().to_string()))
+                })
+            })
+            .collect();
+        assert_eq!(coverage.covered.len(), editions.len());
+        assert!(coverage
+            .covered
+            .contains(&("U.S.".to_string(), "U.S.".to_string())));
+    }
+
+    #[test]
+    fn page_formats() {
+        let pages = |format: PageFormat, text: &str| -> Vec<String> {
+            let formats = [("U.S.".to_string(), format)].into_iter().collect();
+            let extractors = _populate_reporter_extractors_with(&formats);
+            let tokenizer = Ahocorasick::new(&extractors).unwrap();
+            let (_, citations) = tokenizer.tokenize(text);
+            citations
+                .iter()
+                .filter_map(|(_, token)| token.try_data()?.groups.get(GroupName::Page))
+                .map(String::from)
+                .collect()
+        };
+
+        assert_eq!(
+            pages(PageFormat::Paragraph, "See 1 U.S. ¶ 12,345."),
+            vec!["12,345"]
+        );
+        assert_eq!(
+            pages(PageFormat::Hyphenated, "See 1 U.S. 12-345."),
+            vec!["12-345"]
+        );
+        assert_eq!(
+            default_page_formats().get("T.C. Memo."),
+            Some(&PageFormat::YearPage)
+        );
+    }
+
+    #[test]
+    fn named_token_groups() {
+        for extractor in default_extractors() {
+            if let Some(group) = extractor.token_factory.token_group() {
+                assert!(
+                    extractor
+                        .built_regex
+                        .capture_names()
+                        .any(|name| name == Some(group.as_str())),
+                    "{:?} has no {} group",
+                    extractor.regex.value(),
+                    group.as_str()
+                );
+            }
+        }
+
+        let token = |factory: TokenFactories, text: &'static str| {
+            default_extractors()
+                .iter()
+                .filter(|e| e.token_factory == factory)
+                .find_map(|e| {
+                    let token = e.get_token(e.iter_matches(text).next()?);
+                    let data = token.try_data()?;
+                    Some((data.data, data.groups.get(factory.token_group()?)))
+                })
+        };
+        assert_eq!(
+            token(TokenFactories::Id, "Id. at 5"),
+            Some(("Id.", Some("Id.")))
+        );
+        assert_eq!(
+            token(TokenFactories::Id, "So held. At 5, the"),
+            Some(("At 5", Some("At 5")))
+        );
+        assert_eq!(
+            token(TokenFactories::Supra, "Smith, supra, at 5"),
+            Some(("supra,", Some("supra,")))
+        );
+        assert_eq!(
+            token(TokenFactories::Section, "§ 5"),
+            Some(("§", Some("§")))
+        );
+        assert_eq!(
+            token(TokenFactories::Paragraph, "a\nb"),
+            Some(("\n", Some("\n")))
+        );
+    }
+
+    #[test]
+    fn hyperscan_compatible_extractors() {
+        let extractors = filtered().with_mode(RegexMode::Hyperscan).try_build();
+        assert_eq!(extractors.unwrap().len(), default_extractors().len());
+    }
+
+    #[test]
+    fn deterministic_extractors() {
+        let describe = |extractors: Vec<TokenExtractor>| -> Vec<_> {
+            extractors
+                .into_iter()
+                .map(|e| {
+                    let editions: Vec<_> = e
+                        .extra
+                        .exact_editions
+                        .iter()
+                        .chain(&e.extra.variation_editions)
+                        .map(|edition| edition.short_name.clone())
+                        .collect();
+                    (e.regex.value().to_string(), editions)
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            describe(_populate_reporter_extractors()),
+            describe(_populate_reporter_extractors())
+        );
+    }
+
+    /// Looseleaf reporters flagged with `$full_cite_paragraph` are cited by
+    /// paragraph, with or without a volume.
+    #[test]
+    fn paragraph_cites_without_volume() {
+        let mut checked = 0;
+        for extractor in EXTRACTORS.iter().filter(|e| e.regex.value().contains('¶')) {
+            let Some(edition) = extractor.extra.exact_editions.first() else {
+                continue;
+            };
+            let reporter = &edition.short_name;
+            let text = format!("See {reporter} ¶ 12,345; 7 {reporter} ¶ 80.");
+            let groups: Vec<_> = extractor
+                .get_matches(&text)
+                .into_iter()
+                .map(|m| {
+                    let token = extractor.get_token(m);
+                    let groups = &token.try_data().unwrap().groups;
+                    (groups.get("volume"), groups.get("page").unwrap())
+                })
+                .collect();
+
+            assert_eq!(
+                groups,
+                vec![(None, "12,345"), (Some("7"), "80")],
+                "{reporter}"
+            );
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+
+    /// Synthesize a citation from the regex of every reporter extractor, and
+    /// make sure the default extractors find it with the same groups. This
+    /// catches mistakes resolving the regex templates of any edition.
+    #[test]
+    fn every_edition_is_found() {
+        let mut editions = HashSet::new();
+        let mut examples = Vec::new();
+        let mut text = String::new();
+        for extractor in EXTRACTORS
+            .iter()
+            .filter(|e| e.token_factory == TokenFactories::Citation)
+        {
+            editions.extend(extractor.extra.exact_editions.iter().map(|e| &e.short_name));
+            let example = regexes::example_match(extractor.regex.value())
+                .unwrap_or_else(|| panic!("no example for {}", extractor.regex.value()));
+            examples.push((text.len(), example.clone()));
+            text.push_str(&example.text);
+            text.push_str(";\n");
+        }
+
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let (_, tokens) = tokenizer.tokenize(&text);
+        let mut failures = Vec::new();
+        for (start, example) in &examples {
+            // the boundary after the citation adds a space
+            let end = start + example.text.trim_end().len();
+            let token = tokens
+                .iter()
+                .filter_map(|(_, token)| token.try_data())
+                .find(|data| (data.start, data.end) == (*start, end));
+            let found = token.is_some_and(|data| {
+                example
+                    .groups
+                    .iter()
+                    .all(|(name, value)| data.groups.get(name.as_str()) == Some(value.as_str()))
+            });
+            if !found {
+                failures.push(&example.text);
+            }
+        }
+        assert!(failures.is_empty(), "not found: {:?}", failures);
+
+        let missing: Vec<_> = reporters()
+            .into_values()
+            .flatten()
+            .flat_map(|reporter| reporter.editions.into_keys())
+            .filter(|name| !editions.contains(&name.value().to_string()))
+            .collect();
+        assert!(missing.is_empty(), "no extractor for {:?}", missing);
+    }
+
+    #[test]
+    fn short_forms_differ_from_full_forms() {
+        let full: HashSet<_> = EXTRACTORS
+            .iter()
+            .filter(|e| e.token_factory == TokenFactories::Citation && !e.extra.short)
+            .map(|e| e.regex.value())
+            .collect();
+        let short: Vec<_> = EXTRACTORS
+            .iter()
+            .filter(|e| e.extra.short)
+            .map(|e| e.regex.value())
+            .collect();
+
+        assert!(!short.is_empty());
+        for regex in short {
+            assert!(regex.contains(" at "), "not a short form: {regex}");
+            assert!(!full.contains(regex), "same as a full form: {regex}");
+        }
+    }
+
+    #[test]
+    fn spacing_tolerance() {
+        let reporters = |extractors: &[TokenExtractor], text: &'static str| -> Vec<String> {
+            let tokenizer = Ahocorasick::new(extractors).unwrap();
+            let (_, citations) = tokenizer.tokenize(text);
+            citations
+                .iter()
+                .filter_map(|(_, token)| token.try_data()?.groups.get(GroupName::Reporter))
+                .map(String::from)
+                .collect()
+        };
+        let text = "See 410 U.S 113, and 411 U. S 114.";
+
+        let strict = filtered().with_reporters(["U.S."]).try_build().unwrap();
+        assert!(reporters(&strict, text).is_empty());
+
+        let tolerant = filtered()
+            .with_reporters(["U.S."])
+            .with_spacing_tolerance(["U.S."])
+            .try_build()
+            .unwrap();
+        assert_eq!(reporters(&tolerant, text), vec!["U.S", "U. S"]);
+        assert_eq!(reporters(&tolerant, "See 410 U.S. 113."), vec!["U.S."]);
+    }
+
+    #[test]
+    fn id_variants() {
+        let ids = |extractors: &[TokenExtractor], text: &'static str| -> Vec<String> {
+            let tokenizer = Ahocorasick::new(extractors).unwrap();
+            let (_, citations) = tokenizer.tokenize(text);
+            citations
+                .iter()
+                .filter(|(_, token)| matches!(token, Token::Id(_)))
+                .filter_map(|(_, token)| Some(token.try_data()?.data.to_string()))
+                .collect()
+        };
+        let text = "Id; see id at 5. IBID. Ibid, at 7. The id at issue.";
+
+        let strict = filtered().try_build().unwrap();
+        assert_eq!(ids(&strict, text), vec!["IBID."]);
+
+        let sloppy = filtered()
+            .with_id_variants(regexes::ID_VARIANT_REGEXES)
+            .try_build()
+            .unwrap();
+        assert_eq!(ids(&sloppy, text), vec!["Id;", "id", "IBID.", "Ibid,"]);
+
+        let without = filtered()
+            .without(TokenFactories::Id)
+            .with_id_variants(regexes::ID_VARIANT_REGEXES)
+            .try_build()
+            .unwrap();
+        assert!(ids(&without, text).is_empty());
+    }
+
+    #[test]
+    fn reporters_db_snapshots() {
+        let mut snapshot = ReportersSnapshot::bundled();
+        assert_eq!(snapshot.version, version());
+        snapshot.reporters.retain(|key, _| key == "U.S.");
+        let extractors =
+            try_populate_reporter_extractors_from(snapshot, &default_page_formats()).unwrap();
+        let reporters: HashSet<_> = extractors
+            .iter()
+            .flat_map(|e| {
+                e.extra
+                    .exact_editions
+                    .iter()
+                    .chain(&e.extra.variation_editions)
+            })
+            .map(|edition| edition.reporter.as_str())
+            .collect();
+        assert_eq!(reporters, HashSet::from(["U.S."]));
+        assert!(extractors.len() < EXTRACTORS.len());
+    }
 }
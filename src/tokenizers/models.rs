@@ -1,6 +1,154 @@
-use crate::tokenizers::extractors::TokenExtractorExtra;
-use std::collections::HashMap;
-use std::fmt::Debug;
+use crate::tokenizers::extractors::{Editions, TokenExtractorExtra};
+use std::fmt::{Debug, Write};
+
+/// Name of a group captured by an extractor's regex. Groups used by
+/// eyecite's own regexes are interned, so comparing them doesn't compare
+/// strings.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GroupName<'a> {
+    Volume,
+    Reporter,
+    Page,
+    PinCite,
+    StopWord,
+    DocketNumber,
+    VolumeNominative,
+    ReporterNominative,
+    Id,
+    Supra,
+    Infra,
+    Section,
+    Paragraph,
+    /// Any other group, like those of regexes from `reporters_db`. Use
+    /// [`GroupName::from`] rather than building this directly, so names that
+    /// have their own variant are interned.
+    Other(&'a str),
+}
+
+impl<'a> GroupName<'a> {
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            GroupName::Volume => "volume",
+            GroupName::Reporter => "reporter",
+            GroupName::Page => "page",
+            GroupName::PinCite => "pin_cite",
+            GroupName::StopWord => "stop_word",
+            GroupName::DocketNumber => "docket_number",
+            GroupName::VolumeNominative => "volume_nominative",
+            GroupName::ReporterNominative => "reporter_nominative",
+            GroupName::Id => "id",
+            GroupName::Supra => "supra",
+            GroupName::Infra => "infra",
+            GroupName::Section => "section",
+            GroupName::Paragraph => "paragraph",
+            GroupName::Other(name) => name,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for GroupName<'a> {
+    fn from(name: &'a str) -> Self {
+        match name {
+            "volume" => GroupName::Volume,
+            "reporter" => GroupName::Reporter,
+            "page" => GroupName::Page,
+            "pin_cite" => GroupName::PinCite,
+            "stop_word" => GroupName::StopWord,
+            "docket_number" => GroupName::DocketNumber,
+            "volume_nominative" => GroupName::VolumeNominative,
+            "reporter_nominative" => GroupName::ReporterNominative,
+            "id" => GroupName::Id,
+            "supra" => GroupName::Supra,
+            "infra" => GroupName::Infra,
+            "section" => GroupName::Section,
+            "paragraph" => GroupName::Paragraph,
+            name => GroupName::Other(name),
+        }
+    }
+}
+
+/// The groups captured by a match, by name.
+///
+/// Matches capture only a handful of groups, so they are kept in a vector
+/// sorted by name, which is faster to build and search than a hash map.
+///
+/// Values are slices of the token rather than copies, so citations found
+/// from it keep them too; see `CitationSource::groups`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Groups<'a> {
+    groups: Vec<(GroupName<'a>, &'a str)>,
+}
+
+impl<'a> Groups<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a group, returning the value it replaced, if any.
+    pub fn insert(&mut self, name: impl Into<GroupName<'a>>, value: &'a str) -> Option<&'a str> {
+        let name = name.into();
+        match self.position(name) {
+            Ok(i) => Some(std::mem::replace(&mut self.groups[i].1, value)),
+            Err(i) => {
+                self.groups.insert(i, (name, value));
+                None
+            }
+        }
+    }
+
+    pub fn get<'n>(&self, name: impl Into<GroupName<'n>>) -> Option<&'a str> {
+        let i = self.position(name.into()).ok()?;
+        Some(self.groups[i].1)
+    }
+
+    pub fn remove<'n>(&mut self, name: impl Into<GroupName<'n>>) -> Option<&'a str> {
+        let i = self.position(name.into()).ok()?;
+        Some(self.groups.remove(i).1)
+    }
+
+    pub fn contains<'n>(&self, name: impl Into<GroupName<'n>>) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Names and values of the groups, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (GroupName<'a>, &'a str)> + '_ {
+        self.groups.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    fn position(&self, name: GroupName<'_>) -> Result<usize, usize> {
+        self.groups.binary_search_by(|(n, _)| n.cmp(&name))
+    }
+}
+
+impl<'a> FromIterator<(GroupName<'a>, &'a str)> for Groups<'a> {
+    fn from_iter<T: IntoIterator<Item = (GroupName<'a>, &'a str)>>(iter: T) -> Self {
+        let mut groups = Self::new();
+        for (name, value) in iter {
+            groups.insert(name, value);
+        }
+        groups
+    }
+}
+
+/// Panics if there is no group with the name, like indexing a `HashMap`.
+impl<'a> std::ops::Index<&str> for Groups<'a> {
+    type Output = &'a str;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        match self.position(name.into()) {
+            Ok(i) => &self.groups[i].1,
+            Err(_) => panic!("no group named {name:?}"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct TokenData<'a> {
@@ -8,7 +156,22 @@ pub struct TokenData<'a> {
     pub start: usize,
     pub end: usize,
     pub extra: &'a TokenExtractorExtra,
-    pub groups: HashMap<&'a str, &'a str>,
+    pub groups: Groups<'a>,
+    /// Extra data of the tokens with the same span and groups that were
+    /// merged into this one. See [`TokenData::editions`].
+    pub merged: Vec<&'a TokenExtractorExtra>,
+    /// The editions of `extra` and `merged`, merged when the token is made
+    /// and when tokens are merged into it, rather than each time they are
+    /// needed.
+    pub editions: Editions<'a>,
+}
+
+impl<'a> TokenData<'a> {
+    /// The editions the token might refer to, across it and the tokens
+    /// merged into it.
+    pub fn editions(&self) -> &Editions<'a> {
+        &self.editions
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -23,38 +186,153 @@ pub enum Token<'a> {
     Section(TokenData<'a>),
     /// Word matching "supra" with or without punctuation.
     Supra(TokenData<'a>),
+    /// Word matching "infra" with or without punctuation.
+    Infra(TokenData<'a>),
     /// Word matching "id" or "ibid".
     Id(TokenData<'a>),
     /// Word matching a break between paragraphs.
     Paragraph(TokenData<'a>),
     /// Word matching one of the STOP_TOKENS.
     StopWord(TokenData<'a>),
+    /// Docket number of an opinion cited by its slip opinion.
+    SlipOpinion(TokenData<'a>),
+    /// Reference to another part of the document, like "Part II.A".
+    CrossReference(TokenData<'a>),
+    /// Citation to the record of a case, like "R. at 45".
+    Record(TokenData<'a>),
+    /// Token of a kind defined outside of eyecite, by a custom extractor
+    /// using [`TokenFactories::Custom`].
+    Custom(&'static str, TokenData<'a>),
 }
 
-impl Token<'_> {
-    fn data(&self) -> &TokenData {
+impl<'a> Token<'a> {
+    /// Data for special tokens, or None for words and spaces.
+    pub fn try_data(&self) -> Option<&TokenData<'a>> {
+        match self {
+            Token::Citation(data)
+            | Token::StopWord(data)
+            | Token::Supra(data)
+            | Token::Infra(data)
+            | Token::Id(data)
+            | Token::Paragraph(data)
+            | Token::Section(data)
+            | Token::SlipOpinion(data)
+            | Token::CrossReference(data)
+            | Token::Record(data)
+            | Token::Custom(_, data) => Some(data),
+            Token::Word(_) | Token::Space => None,
+        }
+    }
+
+    /// Like [`try_data`](Self::try_data), to change the data.
+    pub fn try_data_mut(&mut self) -> Option<&mut TokenData<'a>> {
         match self {
             Token::Citation(data)
             | Token::StopWord(data)
             | Token::Supra(data)
+            | Token::Infra(data)
             | Token::Id(data)
             | Token::Paragraph(data)
-            | Token::Section(data) => data,
-            Token::Word(_) | Token::Space => todo!("Words don't have data"),
+            | Token::Section(data)
+            | Token::SlipOpinion(data)
+            | Token::CrossReference(data)
+            | Token::Record(data)
+            | Token::Custom(_, data) => Some(data),
+            Token::Word(_) | Token::Space => None,
+        }
+    }
+
+    /// The name of the token's kind, like "Citation", or the kind of a
+    /// custom token.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Token::Word(_) => "Word",
+            Token::Space => "Space",
+            Token::Citation(_) => "Citation",
+            Token::Section(_) => "Section",
+            Token::Supra(_) => "Supra",
+            Token::Infra(_) => "Infra",
+            Token::Id(_) => "Id",
+            Token::Paragraph(_) => "Paragraph",
+            Token::StopWord(_) => "StopWord",
+            Token::SlipOpinion(_) => "SlipOpinion",
+            Token::CrossReference(_) => "CrossReference",
+            Token::Record(_) => "Record",
+            Token::Custom(kind, _) => kind,
+        }
+    }
+
+    /// The kind of a custom token, or None for built-in tokens.
+    pub fn custom_kind(&self) -> Option<&'static str> {
+        match self {
+            Token::Custom(kind, _) => Some(kind),
+            _ => None,
         }
     }
 
+    /// Where the token starts in the text, or 0 for words and spaces,
+    /// which don't keep track of where they are.
     pub fn start(&self) -> usize {
-        self.data().start
+        self.try_data().map_or(0, |data| data.start)
     }
 
+    /// Where the token ends in the text, or 0 for words and spaces.
     pub fn end(&self) -> usize {
-        self.data().end
+        self.try_data().map_or(0, |data| data.end)
     }
 
-    pub(crate) fn merge(&self, _other: &Self) -> Option<Self> {
-        None
+    /// Merge a token of the same kind, with the same span and groups, like
+    /// the same citation matched by the regexes of two reporters, into a
+    /// token that keeps the extra data of both.
+    pub(crate) fn merge(&self, other: &Self) -> Option<Self> {
+        let (data, other_data) = (self.try_data()?, other.try_data()?);
+        if std::mem::discriminant(self) != std::mem::discriminant(other)
+            || self.custom_kind() != other.custom_kind()
+            || (data.start, data.end) != (other_data.start, other_data.end)
+            || data.groups != other_data.groups
+        {
+            return None;
+        }
+
+        let mut merged = self.clone();
+        let merged_data = merged.try_data_mut()?;
+        merged_data.merged.push(other_data.extra);
+        merged_data.merged.extend(&other_data.merged);
+        merged_data.editions.extend(&other_data.editions);
+        Some(merged)
+    }
+}
+
+/// Dump tokens as text for bug reports, one token per line. Words are
+/// quoted, and other tokens are followed by their span, the text they
+/// matched, their groups and the editions they might refer to, like
+/// `Citation 4..16 "410 U.S. 113" volume="410" reporter="U.S." page="113"
+/// editions=[U.S.]`. The same tokens always dump to the same text, so dumps
+/// of two runs can be diffed.
+pub fn dump_tokens(tokens: &[Token]) -> String {
+    let mut dump = String::new();
+    for token in tokens {
+        dump.push_str(token.kind_name());
+        if let Token::Word(word) = token {
+            let _ = write!(dump, " {word:?}");
+        }
+        if let Some(data) = token.try_data() {
+            let _ = write!(dump, " {}..{} {:?}", data.start, data.end, data.data);
+            for (name, value) in data.groups.iter() {
+                let _ = write!(dump, " {}={value:?}", name.as_str());
+            }
+            let editions: Vec<_> = data
+                .editions()
+                .iter()
+                .map(|edition| edition.short_name.as_str())
+                .collect();
+            if !editions.is_empty() {
+                let _ = write!(dump, " editions=[{}]", editions.join(", "));
+            }
+        }
+        dump.push('\n');
     }
+    dump
 }
 
 pub trait TokenFactory: Clone + Debug {
@@ -63,14 +341,36 @@ pub trait TokenFactory: Clone + Debug {
         'b: 'a;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum TokenFactories {
     Paragraph,
     Id,
     Supra,
+    Infra,
     Citation,
     StopWord,
     Section,
+    SlipOpinion,
+    CrossReference,
+    Record,
+    /// Create [`Token::Custom`] tokens of the given kind, for extractors of
+    /// things eyecite doesn't know about.
+    Custom(&'static str),
+}
+
+impl TokenFactories {
+    /// The named group holding the token in the regexes of built-in
+    /// extractors of this kind, or None for kinds whose token is group 1.
+    pub fn token_group(&self) -> Option<GroupName<'static>> {
+        match self {
+            TokenFactories::Paragraph => Some(GroupName::Paragraph),
+            TokenFactories::Id => Some(GroupName::Id),
+            TokenFactories::Supra => Some(GroupName::Supra),
+            TokenFactories::Infra => Some(GroupName::Infra),
+            TokenFactories::Section => Some(GroupName::Section),
+            _ => None,
+        }
+    }
 }
 
 impl TokenFactory for TokenFactories {
@@ -82,11 +382,119 @@ impl TokenFactory for TokenFactories {
             TokenFactories::Paragraph => Token::Paragraph(data),
             TokenFactories::Id => Token::Id(data),
             TokenFactories::Supra => Token::Supra(data),
+            TokenFactories::Infra => Token::Infra(data),
             TokenFactories::Citation => Token::Citation(data),
             TokenFactories::Section => Token::Section(data),
             TokenFactories::StopWord => Token::StopWord(data),
+            TokenFactories::SlipOpinion => Token::SlipOpinion(data),
+            TokenFactories::CrossReference => Token::CrossReference(data),
+            TokenFactories::Record => Token::Record(data),
+            TokenFactories::Custom(kind) => Token::Custom(kind, data),
         }
     }
 }
 
 pub type Tokens<'a> = Vec<Token<'a>>;
+
+/// A set of kinds of tokens, used to only run the extractors of some kinds,
+/// like `TokenKinds::ALL - TokenKinds::SUPRA - TokenKinds::ID` for text
+/// without short form citations.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TokenKinds(u16);
+
+impl TokenKinds {
+    pub const NONE: Self = Self(0);
+    pub const CITATION: Self = Self(1);
+    pub const ID: Self = Self(1 << 1);
+    pub const SUPRA: Self = Self(1 << 2);
+    pub const INFRA: Self = Self(1 << 3);
+    pub const PARAGRAPH: Self = Self(1 << 4);
+    pub const STOP_WORD: Self = Self(1 << 5);
+    pub const SECTION: Self = Self(1 << 6);
+    pub const SLIP_OPINION: Self = Self(1 << 7);
+    /// Tokens of every custom kind.
+    pub const CUSTOM: Self = Self(1 << 8);
+    pub const CROSS_REFERENCE: Self = Self(1 << 9);
+    /// Record citations, like "R. at 45". These are common in briefs but not
+    /// in opinions, so they aren't extracted by default.
+    pub const RECORD: Self = Self(1 << 10);
+    pub const ALL: Self = Self((1 << 11) - 1);
+
+    /// The kind of tokens a factory creates.
+    pub fn of(factory: &TokenFactories) -> Self {
+        match factory {
+            TokenFactories::Citation => Self::CITATION,
+            TokenFactories::Id => Self::ID,
+            TokenFactories::Supra => Self::SUPRA,
+            TokenFactories::Infra => Self::INFRA,
+            TokenFactories::Paragraph => Self::PARAGRAPH,
+            TokenFactories::StopWord => Self::STOP_WORD,
+            TokenFactories::Section => Self::SECTION,
+            TokenFactories::SlipOpinion => Self::SLIP_OPINION,
+            TokenFactories::CrossReference => Self::CROSS_REFERENCE,
+            TokenFactories::Record => Self::RECORD,
+            TokenFactories::Custom(_) => Self::CUSTOM,
+        }
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether tokens created by a factory are of one of these kinds.
+    pub fn allows(self, factory: &TokenFactories) -> bool {
+        self.contains(Self::of(factory))
+    }
+}
+
+impl Default for TokenKinds {
+    /// Every kind except [`TokenKinds::RECORD`], which is opt-in.
+    fn default() -> Self {
+        Self::ALL - Self::RECORD
+    }
+}
+
+impl std::ops::BitOr for TokenKinds {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::Sub for TokenKinds {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GroupName, Groups};
+
+    #[test]
+    fn interned_groups() {
+        let groups: Groups = [("page", "113"), ("volume", "410"), ("chapter", "7")]
+            .into_iter()
+            .map(|(name, value)| (GroupName::from(name), value))
+            .collect();
+
+        assert_eq!(groups.get(GroupName::Page), Some("113"));
+        assert_eq!(groups.get("volume"), Some("410"));
+        assert_eq!(groups.get("chapter"), Some("7"));
+        assert_eq!(GroupName::from("chapter"), GroupName::Other("chapter"));
+        assert!(!groups.contains(GroupName::Reporter));
+
+        let names: Vec<_> = groups.iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec![
+                GroupName::Volume,
+                GroupName::Page,
+                GroupName::Other("chapter")
+            ]
+        );
+    }
+}
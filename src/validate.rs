@@ -0,0 +1,359 @@
+/*!
+Plausibility checks for extracted citations.
+
+Extraction is purely pattern based, so "999 U.S. 1" is happily found even
+though the United States Reports haven't reached volume 999. These checks
+report such citations as warnings, leaving it up to the caller whether to
+drop them.
+
+`reporters_db` records when editions were published but not how many
+volumes they have, so [`VolumeLimits::default`] estimates the last volume
+of every edition from its dates and the courts its reporter covers. Known
+last volumes can be set on top of the estimates with
+[`VolumeLimits::with_max_volume`].
+
+[`validate_spans`] checks citations for bugs in eyecite rather than in the
+text: spans that aren't slices of the text or that overlap. Debug builds
+check every list of citations found.
+ */
+
+use crate::find::models::Citation;
+use crate::helpers;
+use crate::tokenizers::extractors::ReporterEdition;
+use chrono::Datelike;
+use lazy_static::lazy_static;
+use reporters_db::reporters::{reporters, Reporter};
+use std::collections::HashMap;
+
+/// Volumes an edition publishes a year, at most, for each court its
+/// reporter covers. Used to estimate the last volume of editions from
+/// their dates.
+pub const VOLUMES_PER_COURT_YEAR: f32 = 3.5;
+
+/// Series roll over to a new edition before reaching volume 1000, like
+/// "F.2d" to "F.3d".
+const MAX_SERIES_VOLUME: u32 = 999;
+
+lazy_static! {
+    static ref DEFAULT_LIMITS: VolumeLimits =
+        VolumeLimits::from_reporters(&reporters(), VOLUMES_PER_COURT_YEAR);
+}
+
+/// Why a citation's volume can't be right.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VolumeProblem {
+    /// Reporters start at volume 1.
+    Zero,
+    /// The volume is past the last volume the edition published.
+    AfterLastVolume { max_volume: u32 },
+    /// The volume is a year, like "2020 WL 1234", that the edition wasn't
+    /// published in.
+    OutsideEditionYears,
+}
+
+/// A citation whose volume is implausible for every edition it might refer to.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VolumeWarning {
+    /// Index of the citation in the validated list.
+    pub index: usize,
+    pub span: (usize, usize),
+    pub volume: String,
+    pub problem: VolumeProblem,
+}
+
+/// Last volumes of editions, keyed by edition name, like "F.2d".
+#[derive(Debug, Clone)]
+pub struct VolumeLimits {
+    max_volumes: HashMap<String, u32>,
+}
+
+impl Default for VolumeLimits {
+    /// Limits estimated from the editions bundled with `reporters_db`, with
+    /// [`VOLUMES_PER_COURT_YEAR`]. See [`VolumeLimits::from_reporters`].
+    fn default() -> Self {
+        DEFAULT_LIMITS.clone()
+    }
+}
+
+impl VolumeLimits {
+    /// Estimate the last volume of every edition of `reporters` from its
+    /// dates: editions publish at most `volumes_per_court_year` volumes a
+    /// year for each jurisdiction their reporter covers, up to volume 999.
+    /// Editions without a start date have no limit.
+    ///
+    /// The estimates are upper bounds meant to catch volumes that can't
+    /// exist, like "999 U.S. 1", rather than the actual last volumes.
+    pub fn from_reporters(
+        reporters: &HashMap<String, Vec<Reporter>>,
+        volumes_per_court_year: f32,
+    ) -> Self {
+        let this_year = helpers::highest_valid_year();
+        let mut limits = Self::empty();
+
+        for reporter in reporters.values().flatten() {
+            let courts = reporter.mlz_jurisdiction.len().max(1) as f32;
+            for (name, edition) in &reporter.editions {
+                let Some(start) = edition.start else {
+                    continue;
+                };
+                let end = edition.end.map_or(this_year, |end| end.year());
+                let years = (end - start.year() + 1).max(1) as f32;
+                let estimate = (years * courts * volumes_per_court_year).ceil() as u32;
+                let max_volume = estimate.min(MAX_SERIES_VOLUME);
+
+                // Editions with the same name in more than one reporter get
+                // the most lenient limit
+                let limit = limits
+                    .max_volumes
+                    .entry(name.value().to_string())
+                    .or_insert(max_volume);
+                *limit = max_volume.max(*limit);
+            }
+        }
+
+        limits
+    }
+
+    /// Limits without any known last volumes. Only zero volumes and year
+    /// volumes outside of the edition's dates will be flagged.
+    pub fn empty() -> Self {
+        Self {
+            max_volumes: HashMap::new(),
+        }
+    }
+
+    /// Set the last volume published for an edition, replacing its
+    /// estimate.
+    pub fn with_max_volume(mut self, edition: impl Into<String>, max_volume: u32) -> Self {
+        self.max_volumes.insert(edition.into(), max_volume);
+        self
+    }
+
+    pub fn max_volume(&self, edition: &str) -> Option<u32> {
+        self.max_volumes.get(edition).copied()
+    }
+
+    fn check_edition(&self, volume: u32, edition: &ReporterEdition) -> Option<VolumeProblem> {
+        if let Some(max_volume) = self.max_volume(&edition.short_name) {
+            if volume > max_volume {
+                return Some(VolumeProblem::AfterLastVolume { max_volume });
+            }
+        }
+
+        // Volumes this large are years, as in "2020 WL 1234"
+        let is_year = (1600..=helpers::highest_valid_year()).contains(&(volume as i32));
+        if is_year && !edition.includes_year(volume as i32) {
+            return Some(VolumeProblem::OutsideEditionYears);
+        }
+
+        None
+    }
+
+    /// Check a citation's volume against the editions it might refer to.
+    /// A volume is only a problem if it is implausible for all of them.
+    /// Citations without a numeric volume or editions are never a problem.
+    pub fn check(&self, citation: &Citation) -> Option<VolumeProblem> {
+        let source = citation.source();
+        let volume: u32 = source.groups.get("volume")?.parse().ok()?;
+
+        if volume == 0 {
+            return Some(VolumeProblem::Zero);
+        }
+
+        let editions = source.editions()?;
+        let editions = if editions.exact.is_empty() {
+            &editions.variations
+        } else {
+            &editions.exact
+        };
+
+        let mut problems = editions.iter().map(|e| self.check_edition(volume, e));
+        let first = problems.next()??;
+
+        problems.all(|p| p.is_some()).then_some(first)
+    }
+}
+
+/// Check the volume of every citation, returning a warning for each one
+/// whose volume is implausible.
+pub fn validate_volumes(citations: &[Citation], limits: &VolumeLimits) -> Vec<VolumeWarning> {
+    citations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, citation)| {
+            let problem = limits.check(citation)?;
+            let source = citation.source();
+
+            Some(VolumeWarning {
+                index,
+                span: source.span(),
+                volume: source.groups.get("volume").unwrap_or_default().to_string(),
+                problem,
+            })
+        })
+        .collect()
+}
+
+/// Drop citations with implausible volumes, returning the remaining citations
+/// along with a warning for each one that was dropped.
+pub fn remove_implausible_volumes<'a>(
+    citations: Vec<Citation<'a>>,
+    limits: &VolumeLimits,
+) -> (Vec<Citation<'a>>, Vec<VolumeWarning>) {
+    let warnings = validate_volumes(&citations, limits);
+
+    let citations = citations
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !warnings.iter().any(|w| w.index == *i))
+        .map(|(_, citation)| citation)
+        .collect();
+
+    (citations, warnings)
+}
+
+/// Why a citation's span can't be right.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SpanProblem {
+    /// The span ends before it starts, or past the end of the text.
+    OutOfBounds,
+    /// The span starts or ends in the middle of a character.
+    NotCharBoundary,
+    /// The matched text doesn't contain the reporter it was matched with.
+    MissingReporter,
+    /// The matched text overlaps the matched text of an earlier citation.
+    Overlaps { previous: usize },
+}
+
+/// A citation whose span isn't a valid part of the text it was found in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SpanViolation {
+    /// Index of the citation in the validated list.
+    pub index: usize,
+    pub span: (usize, usize),
+    pub problem: SpanProblem,
+}
+
+fn check_span(text: &str, (start, end): (usize, usize)) -> Option<SpanProblem> {
+    if start > end || end > text.len() {
+        Some(SpanProblem::OutOfBounds)
+    } else if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+        Some(SpanProblem::NotCharBoundary)
+    } else {
+        None
+    }
+}
+
+/// Check that the spans of citations, in the order they were found, are
+/// slices of the text they were found in, and that their matched text
+/// contains their reporter and doesn't overlap the matched text of another
+/// citation.
+pub fn validate_spans(text: &str, citations: &[Citation]) -> Vec<SpanViolation> {
+    let mut violations = Vec::new();
+    // Index and end of the citation whose matched text ends last so far
+    let mut last: Option<(usize, usize)> = None;
+
+    for (index, citation) in citations.iter().enumerate() {
+        let source = citation.source();
+        let span = source.span();
+        let matched = source.matched_span();
+
+        let problem = check_span(text, span)
+            .or_else(|| check_span(text, matched))
+            .or_else(|| {
+                let reporter = source.groups.get("reporter")?;
+                (!source.matched_text().contains(reporter)).then_some(SpanProblem::MissingReporter)
+            })
+            .or_else(|| {
+                let (previous, end) = last?;
+                (matched.0 < end).then_some(SpanProblem::Overlaps { previous })
+            });
+
+        if last.is_none_or(|(_, end)| matched.1 > end) {
+            last = Some((index, matched.1));
+        }
+        if let Some(problem) = problem {
+            violations.push(SpanViolation {
+                index,
+                span,
+                problem,
+            });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_spans, validate_volumes, SpanProblem, VolumeLimits, VolumeProblem};
+    use crate::find::get_citations;
+    use crate::tokenizers::extractors::EXTRACTORS;
+    use crate::tokenizers::Ahocorasick;
+
+    #[test]
+    fn implausible_volumes() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations("1 F. 1, 999 F. 1, 0 F. 1", false, &tokenizer);
+        let limits = VolumeLimits::default().with_max_volume("F.", 300);
+        let warnings = validate_volumes(&citations, &limits);
+
+        let problems: Vec<_> = warnings.iter().map(|w| (w.index, &w.problem)).collect();
+        assert_eq!(
+            problems,
+            vec![
+                (1, &VolumeProblem::AfterLastVolume { max_volume: 300 }),
+                (2, &VolumeProblem::Zero),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_spans() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let text = "é 1 U.S. 1";
+        let citations = get_citations(text, false, &tokenizer);
+        assert_eq!(validate_spans(text, &citations), vec![]);
+
+        let problems = |text, citations: &[_]| -> Vec<_> {
+            validate_spans(text, citations)
+                .into_iter()
+                .map(|v| (v.index, v.problem))
+                .collect()
+        };
+        assert_eq!(
+            problems("é 1 U.S.", &citations),
+            vec![(0, SpanProblem::OutOfBounds)]
+        );
+        assert_eq!(
+            problems("éé1 U.S. 1", &citations),
+            vec![(0, SpanProblem::NotCharBoundary)]
+        );
+
+        let twice = [citations[0].clone(), citations[0].clone()];
+        assert_eq!(
+            problems(text, &twice),
+            vec![(1, SpanProblem::Overlaps { previous: 0 })]
+        );
+    }
+
+    #[test]
+    fn estimated_volume_limits() {
+        let tokenizer = Ahocorasick::new(EXTRACTORS.as_slice()).unwrap();
+        let citations = get_citations("410 U.S. 113, 999 U.S. 1", false, &tokenizer);
+        let limits = VolumeLimits::default();
+
+        let max_volume = limits.max_volume("U.S.").unwrap();
+        assert!((500..999).contains(&max_volume), "{max_volume}");
+        let warnings = validate_volumes(&citations, &limits);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].index, 1);
+        assert_eq!(
+            warnings[0].problem,
+            VolumeProblem::AfterLastVolume { max_volume }
+        );
+
+        // Series roll over before volume 1000
+        assert!(limits.max_volume("F.2d").is_some_and(|max| max <= 999));
+    }
+}